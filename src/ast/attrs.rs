@@ -14,9 +14,12 @@ pub enum Linkage {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AstAttrs {
+    // `Invalid`, `Mut` and `Const` aren't produced by the parser yet
+    #[allow(dead_code)]
     Invalid,
     Privacy(bool),
     Static,
+    #[allow(dead_code)]
     Mut,
     Cfg(String),
     Test,
@@ -25,6 +28,7 @@ pub enum AstAttrs {
     Abstract,
     Final,
     Inline,
+    #[allow(dead_code)]
     Const,
     External(ExternalLinkage),
     BuiltIn,
@@ -67,15 +71,11 @@ impl AttrHandler {
 
     pub fn is_pub(&self) -> bool {
         for attr in &self.attrs {
-            match attr {
-                AstAttrs::Privacy(true) => return true,
-                _ => {}
+            if let AstAttrs::Privacy(true) = attr {
+                return true;
             }
         }
         false
     }
 
-    pub fn is_priv(&self) -> bool {
-        !self.is_pub()
-    }
 }
@@ -1,8 +1,8 @@
-use std::collections::HashMap;
 use std::option::Option;
 use std::vec::Vec;
 use crate::ast::source::SourceLocation;
 use crate::ast::attrs::AttrHandler;
+use crate::frontend::module::NamespacePath;
 
 #[derive(Debug, Clone)]
 pub enum BinaryOp {
@@ -19,11 +19,42 @@ pub enum BinaryOp {
     Ge,
     And,
     Or,
+    Not,
+    BitNot,
+    // there is no syntax for `new` and `del` yet
+    #[allow(dead_code)]
     New,
+    #[allow(dead_code)]
     Del,
     Index,
 }
 
+impl std::fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Not => "!",
+            BinaryOp::BitNot => "~",
+            BinaryOp::New => "new",
+            BinaryOp::Del => "delete",
+            BinaryOp::Index => "[]",
+        };
+        write!(f, "{}", op)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AstType {
     ast: Node,
@@ -64,24 +95,168 @@ impl<T: std::fmt::Debug + Clone> GenericDecl<T> {
     }
 }
 
+/// A parameter of a function, e.g. `mut count: i32 = 0`.
+#[derive(Debug, Clone)]
+pub struct Param<T: std::fmt::Debug + Clone = Node, TN: std::fmt::Debug + Clone = AstType> {
+    name: String,
+    ty: TN,
+    default: Option<T>,
+    mutable: bool,
+    location: SourceLocation,
+}
+
+impl<T: std::fmt::Debug + Clone, TN: std::fmt::Debug + Clone> Param<T, TN> {
+    pub fn new(name: String, ty: TN, default: Option<T>, mutable: bool, location: SourceLocation) -> Self {
+        Param { name, ty, default, mutable, location }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_ty(&self) -> &TN {
+        &self.ty
+    }
+
+    /// The value used when a call leaves the argument out.
+    pub fn get_default(&self) -> Option<&T> {
+        self.default.as_ref()
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    pub fn get_location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct ClassMember {
+pub struct ClassMember<T: std::fmt::Debug + Clone = AstType> {
     name: String,
-    ty: AstType,
+    ty: T,
+    attrs: AttrHandler,
 }
 
-impl ClassMember {
-    pub fn new(name: String, ty: AstType) -> Self {
-        ClassMember { name, ty }
+impl<T: std::fmt::Debug + Clone> ClassMember<T> {
+    pub fn new(name: String, ty: T, attrs: AttrHandler) -> Self {
+        ClassMember { name, ty, attrs }
     }
 
     pub fn get_name(&self) -> &String {
         &self.name
     }
 
-    pub fn get_ty(&self) -> &AstType {
+    pub fn get_ty(&self) -> &T {
         &self.ty
     }
+
+    pub fn get_attrs(&self) -> &AttrHandler {
+        &self.attrs
+    }
+}
+
+/// How the fields of an enum variant are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariantKind {
+    /// `Empty`
+    Unit,
+    /// `Circle(f64)`, the fields are named by their position (`0`, `1`, ...).
+    Tuple,
+    /// `Rect { w: f64, h: f64 }`
+    Struct,
+}
+
+/// A variant of an enum, e.g. `Rect { w: f64, h: f64 }`.
+#[derive(Debug, Clone)]
+pub struct EnumVariant<T: std::fmt::Debug + Clone = AstType> {
+    name: String,
+    kind: VariantKind,
+    fields: Vec<(String, T)>,
+    location: SourceLocation,
+}
+
+impl<T: std::fmt::Debug + Clone> EnumVariant<T> {
+    pub fn new(name: String, kind: VariantKind, fields: Vec<(String, T)>, location: SourceLocation) -> Self {
+        EnumVariant { name, kind, fields, location }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_kind(&self) -> VariantKind {
+        self.kind
+    }
+
+    pub fn get_fields(&self) -> &Vec<(String, T)> {
+        &self.fields
+    }
+
+    pub fn get_location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind<TN: std::fmt::Debug + Clone = AstType> {
+    /// `_`
+    Wildcard,
+    /// A name the matched value is bound to.
+    Binding(String),
+    Int(i64),
+    String(String),
+    Bool(bool),
+    /// `Shape::Circle(r)` or `Rect { w, .. }`. The enum is left out when only
+    /// the variant is written, the fields are named like the ones of the variant.
+    Variant(/* enum */ Option<TN>, /* variant */ String, VariantKind, Vec<(String, Pattern<TN>)>, /* has_rest (`..`) */ bool),
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern<TN: std::fmt::Debug + Clone = AstType> {
+    kind: PatternKind<TN>,
+    location: SourceLocation,
+}
+
+impl<TN: std::fmt::Debug + Clone> Pattern<TN> {
+    pub fn new(kind: PatternKind<TN>, location: SourceLocation) -> Self {
+        Pattern { kind, location }
+    }
+
+    pub fn get_kind(&self) -> &PatternKind<TN> {
+        &self.kind
+    }
+
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        Some(&self.location)
+    }
+}
+
+/// An arm of a `match`, `pattern if guard => body`.
+#[derive(Debug, Clone)]
+pub struct MatchArm<T: std::fmt::Debug + Clone = Node, TN: std::fmt::Debug + Clone = AstType> {
+    pattern: Pattern<TN>,
+    guard: Option<T>,
+    body: T,
+}
+
+impl<T: std::fmt::Debug + Clone, TN: std::fmt::Debug + Clone> MatchArm<T, TN> {
+    pub fn new(pattern: Pattern<TN>, guard: Option<T>, body: T) -> Self {
+        MatchArm { pattern, guard, body }
+    }
+
+    pub fn get_pattern(&self) -> &Pattern<TN> {
+        &self.pattern
+    }
+
+    pub fn get_guard(&self) -> Option<&T> {
+        self.guard.as_ref()
+    }
+
+    pub fn get_body(&self) -> &T {
+        &self.body
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,28 +267,59 @@ pub enum AST<T: std::fmt::Debug + Clone = Node, TN: std::fmt::Debug + Clone = As
     Continue,
     If(T, T, Vec<T>),
     While(T, T, /* is_do_while */ bool),
+    // `for` loops and casts aren't parsed yet
+    #[allow(dead_code)]
     For(T, T, T, Vec<T>),
     Block(Vec<T>),
-    FuncDef(/* name */ String, /* args */ HashMap<String, TN>, /* ret arg */TN, Option<T>, Option<Vec<GenericDecl<TN>>>, /*__id*/ Option<usize>),
+    FuncDef(/* name */ String, /* args */ Vec<Param<T, TN>>, /* ret arg */TN, Option<T>, Option<Vec<GenericDecl<TN>>>, /*__id*/ Option<usize>),
     VarDef(String, Option<TN>, Option<T>),
-    ClassDef(Option<T>, Vec<ClassMember>, Vec<GenericDecl<TN>>),
+    ClassDef(/* name */ String, /* parent */ Option<TN>, /* interfaces */ Vec<TN>, Vec<ClassMember<TN>>, /* methods */ Vec<T>, Vec<GenericDecl<TN>>, /*__id*/ Option<usize>),
     NamespaceDef(Option<T>, Vec<T>),
-    Import(T),
-    InterfaceDef(Option<T>, Vec<T>, Vec<GenericDecl<TN>>),
-    EnumDef(Option<T>, Vec<T>),
+    Import(/* path */ NamespacePath, /* alias */ Option<String>),
+    InterfaceDef(/* name */ String, /* methods */ Vec<T>, Vec<GenericDecl<TN>>, /*__id*/ Option<usize>),
+    EnumDef(/* name */ String, Vec<EnumVariant<TN>>, Vec<GenericDecl<TN>>, /*__id*/ Option<usize>),
     Empty,
+    /// Stands in for code that failed to parse, so the parser can keep going.
+    Error,
     ClassInit(TN, Vec<T>),
     ClassAccess(T, String),
-    NamespaceAccess(T, String),
+    NamespaceAccess(T, String, Option<Vec<TN>>),
     Ident(String, Option<Vec<TN>>),
     Int(i64),
     Float(f64),
     String(String),
     Bool(bool),
     Call(T, Vec<T>),
+    #[allow(dead_code)]
     Cast(T, TN),
-    BinaryOp(BinaryOp, T, T, /* is_unary */ bool),
-    Assign(T, T),
+    BinaryOp(BinaryOp, T, T, /* is_unary (the operand is the lhs, the rhs is Empty) */ bool),
+    /// `a = b`, or `a += b` with the operator of a compound assignment.
+    Assign(T, T, Option<BinaryOp>),
+    Match(/* scrutinee */ T, Vec<MatchArm<T, TN>>),
+    /// `Shape::Rect { w: 1.0, h: 2.0 }`, typed variants are always created through this node.
+    VariantInit(/* enum */ TN, /* variant */ String, /* fields */ Vec<(String, T)>),
+}
+
+impl<T: std::fmt::Debug + Clone, TN: std::fmt::Debug + Clone> AST<T, TN> {
+    /// The nodes directly inside of this one.
+    pub fn children(&self) -> Vec<&T> {
+        match self {
+            AST::TopLevel( nodes ) | AST::Block( nodes ) | AST::ClassInit( _, nodes ) => nodes.iter().collect(),
+            AST::Return( value ) | AST::VarDef( _, _, value ) | AST::FuncDef( _, _, _, value, .. ) => value.iter().collect(),
+            AST::If( cond, then, otherwise ) => [cond, then].into_iter().chain(otherwise).collect(),
+            AST::While( cond, body, _ ) => vec![cond, body],
+            AST::For( init, cond, step, body ) => [init, cond, step].into_iter().chain(body).collect(),
+            AST::ClassDef( _, _, _, _, methods, .. ) | AST::InterfaceDef( _, methods, .. ) => methods.iter().collect(),
+            AST::NamespaceDef( name, nodes ) => name.iter().chain(nodes).collect(),
+            AST::ClassAccess( base, _ ) | AST::NamespaceAccess( base, .. ) | AST::Cast( base, _ ) => vec![base],
+            AST::Call( callee, args ) => std::iter::once(callee).chain(args).collect(),
+            AST::BinaryOp( _, lhs, rhs, _ ) | AST::Assign( lhs, rhs, _ ) => vec![lhs, rhs],
+            AST::Match( scrutinee, arms ) => std::iter::once(scrutinee).chain(arms.iter().flat_map(|arm| arm.guard.iter().chain([&arm.body]))).collect(),
+            AST::VariantInit( _, _, fields ) => fields.iter().map(|(_, value)| value).collect(),
+            AST::Break | AST::Continue | AST::Import( .. ) | AST::EnumDef( .. ) | AST::Empty | AST::Error | AST::Ident( .. ) |
+            AST::Int( _ ) | AST::Float( _ ) | AST::String( _ ) | AST::Bool( _ ) => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
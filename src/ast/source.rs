@@ -1,15 +1,24 @@
+/// Identifies a file in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(usize);
+
+impl FileId {
+    /// Used by locations that don't point into any source.
+    pub const DUMMY: FileId = FileId(usize::MAX);
+}
+
 #[derive(Debug, Clone)]
 pub struct SourceLocation {
-    pub path: String,
+    pub file: FileId,
     pub line: usize,
     pub column: usize,
     pub width: usize,
 }
 
 impl SourceLocation {
-    pub fn new(path: String, line: usize, column: usize, width: usize) -> SourceLocation {
+    pub fn new(file: FileId, line: usize, column: usize, width: usize) -> SourceLocation {
         SourceLocation {
-            path,
+            file,
             line,
             column,
             width,
@@ -18,16 +27,26 @@ impl SourceLocation {
 
     pub fn with_width(&self, width: usize) -> SourceLocation {
         SourceLocation {
-            path: self.path.clone(),
+            file: self.file,
             line: self.line,
             column: self.column,
             width,
         }
     }
 
+    /// Returns a location that starts here and ends where `end` ends. Since
+    /// a location can only describe a single line, spans crossing lines keep
+    /// the width of the start location.
+    pub fn span_to(&self, end: &SourceLocation) -> SourceLocation {
+        if end.line != self.line || end.column + end.width < self.column {
+            return self.clone();
+        }
+        self.with_width(end.column + end.width - self.column)
+    }
+
     pub fn dummy() -> SourceLocation {
         SourceLocation {
-            path: String::new(),
+            file: FileId::DUMMY,
             line: 0,
             column: 0,
             width: 0,
@@ -35,12 +54,52 @@ impl SourceLocation {
     }
 }
 
-struct PathHolder {
+/// A source that was loaded into a `SourceMap`. The path is only used to
+/// display it, it doesn't have to exist on disk.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
     path: String,
+    content: String,
+}
+
+impl SourceFile {
+    pub fn get_content(&self) -> &String {
+        &self.content
+    }
+
+    /// Returns the line with the given 1 based number.
+    pub fn get_line(&self, line: usize) -> Option<&str> {
+        self.content.lines().nth(line.checked_sub(1)?)
+    }
+}
+
+/// Owns the contents of every source of a compilation, so diagnostics are
+/// rendered from exactly what was compiled.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
 }
 
-impl PathHolder {
-    pub fn new(path: String) -> PathHolder {
-        PathHolder { path }
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: Vec::new() }
+    }
+
+    pub fn add(&mut self, path: String, content: String) -> FileId {
+        self.files.push(SourceFile { path, content });
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.0)
+    }
+
+    /// The path of a file, as it is shown in diagnostics.
+    pub fn get_path(&self, file: FileId) -> &str {
+        self.get(file).map(|f| f.path.as_str()).unwrap_or("<unknown>")
+    }
+
+    pub fn find(&self, path: &str) -> Option<FileId> {
+        self.files.iter().position(|f| f.path == path).map(FileId)
     }
 }
@@ -1,20 +1,22 @@
-
 use crate::ast::nodes::AST;
 use crate::ast::attrs::AttrHandler;
-use crate::frontend::type_checker::UnificationType;
+use crate::ast::source::SourceLocation;
+use crate::frontend::type_checker::{Type, UnificationType};
 
 #[derive(Debug, Clone)]
 pub struct TypedNode {
     kind: Box<AST<TypedNode, UnificationType>>,
     attrs: Option<AttrHandler>,
+    ty: UnificationType,
+    location: Option<SourceLocation>,
 }
 
 impl TypedNode {
     pub fn new(kind: AST<TypedNode, UnificationType>, attrs: Option<AttrHandler>) -> Self {
-        TypedNode { kind: Box::new(kind), attrs }
+        TypedNode { kind: Box::new(kind), attrs, ty: UnificationType::Known(Type::Void), location: None }
     }
 
-    pub fn get_kind(&self) -> &Box<AST<TypedNode, UnificationType>> {
+    pub fn get_kind(&self) -> &AST<TypedNode, UnificationType> {
         &self.kind
     }
 
@@ -25,4 +27,26 @@ impl TypedNode {
     pub fn get_attrs(&self) -> &Option<AttrHandler> {
         &self.attrs
     }
+
+    pub fn with_type(mut self, ty: UnificationType) -> Self {
+        self.ty = ty;
+        self
+    }
+
+    pub fn get_type(&self) -> &UnificationType {
+        &self.ty
+    }
+
+    pub fn set_type(&mut self, ty: UnificationType) {
+        self.ty = ty;
+    }
+
+    pub fn with_location(mut self, location: Option<SourceLocation>) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
 }
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+
+use crate::ast::nodes::{BinaryOp, AST};
+use crate::ast::typed::TypedNode;
+use crate::backend::{symbol_name, top_nodes};
+use crate::frontend::module::{Module, NamespacePath};
+use crate::frontend::type_checker::{Type, UnificationType, BUILTIN_ASSERT};
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+
+/// Helpers every generated file starts with. Only compiler builtins and `exit`
+/// are used so no header that declares functions needs to be included, which
+/// keeps `external "C"` declarations from clashing with the C standard library.
+/// Integer division goes through `sn_div_*` and `sn_mod_*`, which stop the
+/// program like the interpreter does when dividing by zero. Dividing the
+/// smallest signed integer by -1 wraps around instead of trapping.
+const PRELUDE: &str = "#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+void exit(int status);
+
+static void sn_divide_by_zero(void) {
+    __builtin_printf(\"error[E0033]: attempt to divide by zero\\n\");
+    exit(1);
+}
+
+#define SN_SIGNED_DIVISION(T) \\
+static T sn_div_##T(T a, T b) { if (b == 0) sn_divide_by_zero(); return b == -1 ? -a : a / b; } \\
+static T sn_mod_##T(T a, T b) { if (b == 0) sn_divide_by_zero(); return b == -1 ? 0 : a % b; }
+#define SN_UNSIGNED_DIVISION(T) \\
+static T sn_div_##T(T a, T b) { if (b == 0) sn_divide_by_zero(); return a / b; } \\
+static T sn_mod_##T(T a, T b) { if (b == 0) sn_divide_by_zero(); return a % b; }
+SN_SIGNED_DIVISION(int32_t)
+SN_SIGNED_DIVISION(int64_t)
+SN_UNSIGNED_DIVISION(uint32_t)
+SN_UNSIGNED_DIVISION(uint64_t)
+
+static const char* sn_concat(const char* a, const char* b) {
+    size_t a_len = __builtin_strlen(a);
+    size_t b_len = __builtin_strlen(b);
+    char* result = __builtin_malloc(a_len + b_len + 1);
+    __builtin_memcpy(result, a, a_len);
+    __builtin_memcpy(result + a_len, b, b_len + 1);
+    return result;
+}
+";
+
+/// Lowers a type checked module to C source code.
+pub struct CBackend {
+    /// Maps the path of every function to the name it is emitted with.
+    symbols: HashMap<String, String>,
+    output: String,
+    indent: usize,
+    reports: Reports,
+}
+
+macro_rules! report {
+    ($self:ident, $error_type:expr, $node:expr) => { {
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()));
+        return Err(());
+    }
+    };
+    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
+        return Err(());
+    }
+    }
+}
+
+impl CBackend {
+    pub fn new() -> CBackend {
+        CBackend {
+            symbols: HashMap::new(),
+            output: String::new(),
+            indent: 0,
+            reports: Reports::new(),
+        }
+    }
+
+    pub fn get_reports(&self) -> &Reports {
+        &self.reports
+    }
+
+    pub fn generate(&mut self, modules: &[Module<TypedNode>], entry: Option<&NamespacePath>) -> Result<String, ()> {
+        let nodes = top_nodes(modules);
+        for node in &nodes {
+            match node.get_kind() {
+                AST::FuncDef( path, .. ) => {
+                    self.symbols.insert(path.clone(), symbol_name(node));
+                }
+                AST::ClassDef( .. ) => report!(self, Error::Unsupported("classes".to_string(), "the C backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                AST::EnumDef( .. ) => report!(self, Error::Unsupported("enums".to_string(), "the C backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                AST::InterfaceDef( .. ) => report!(self, Error::Unsupported("interfaces".to_string(), "the C backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                _ => panic!("Unexpected global node"),
+            }
+        }
+        self.output.push_str(PRELUDE);
+        self.output.push('\n');
+        for node in &nodes {
+            let prototype = self.prototype(node);
+            self.output.push_str(&format!("{};\n", prototype));
+        }
+        for node in &nodes {
+            if let AST::FuncDef( _, _, _, Some(body), .. ) = node.get_kind() {
+                let prototype = self.prototype(node);
+                self.output.push_str(&format!("\n{} ", prototype));
+                self.emit_stmt(body)?;
+            }
+        }
+        // libraries have no entry point
+        if let Some(entry) = entry {
+            let main = format!("{}::main", entry);
+            let Some(symbol) = self.symbols.get(&main) else {
+                self.reports.add_global_error(Error::MissingMain, Some(format!("Declare a function named 'main' in the '{}' module to build the program.", entry)));
+                return Err(());
+            };
+            let main_node = nodes.iter().find(|n| matches!(n.get_kind(), AST::FuncDef( path, .. ) if *path == main)).unwrap();
+            let AST::FuncDef( _, _, ret, .. ) = main_node.get_kind() else {
+                panic!("Expected FuncDef");
+            };
+            self.output.push_str("\nint main(void) {\n");
+            match ret {
+                UnificationType::Known(Type::Void) => self.output.push_str(&format!("    {}();\n    return 0;\n", symbol)),
+                _ => self.output.push_str(&format!("    return (int){}();\n", symbol)),
+            }
+            self.output.push_str("}\n");
+        }
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    fn prototype(&self, node: &TypedNode) -> String {
+        let AST::FuncDef( path, args, ret, .. ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let params = args.iter().map(|param| Self::declare(param.get_ty(), &Self::local(param.get_name()))).collect::<Vec<_>>();
+        let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+        Self::declare(ret, &format!("{}({})", self.symbols[path], params))
+    }
+
+    /// Local variables get a prefix so they can't collide with C keywords or
+    /// with the names of the emitted functions.
+    fn local(name: &str) -> String {
+        let mut result = String::from("l_");
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                result.push(c);
+            } else {
+                result.push_str(&format!("_{:02x}", c as u32));
+            }
+        }
+        result
+    }
+
+    fn c_type(ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(Type::Integer { size, signed }) => format!("{}int{}_t", if *signed { "" } else { "u" }, size),
+            UnificationType::Known(Type::Float { size: 32 }) => "float".to_string(),
+            UnificationType::Known(Type::Float { .. }) => "double".to_string(),
+            UnificationType::Known(Type::Bool) => "bool".to_string(),
+            UnificationType::Known(Type::String) => "const char*".to_string(),
+            UnificationType::Known(Type::Void) => "void".to_string(),
+            _ => panic!("Type {:?} has no C equivalent", ty),
+        }
+    }
+
+    /// Writes a C declaration of `name` with the given type. Function types
+    /// become function pointers, which need the name in the middle of the type.
+    fn declare(ty: &UnificationType, name: &str) -> String {
+        match ty {
+            UnificationType::Known(Type::Function { args, ret }) => {
+                let params = args.iter().map(|a| Self::declare(a, "")).collect::<Vec<_>>();
+                let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+                Self::declare(ret, &format!("(*{})({})", name, params))
+            }
+            UnificationType::Known(Type::Pointer { ty } | Type::Reference { ty }) => Self::declare(ty, &format!("*{}", name)),
+            _ if name.is_empty() => Self::c_type(ty),
+            _ => format!("{} {}", Self::c_type(ty), name),
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.output.push_str(&"    ".repeat(self.indent));
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    /// Emits a statement. The first line is expected to be already indented,
+    /// which lets blocks follow `if (...) ` or a function prototype.
+    fn emit_stmt(&mut self, node: &TypedNode) -> Result<(), ()> {
+        match node.get_kind() {
+            AST::Block( nodes ) => {
+                self.output.push_str("{\n");
+                self.indent += 1;
+                for node in nodes {
+                    self.output.push_str(&"    ".repeat(self.indent));
+                    self.emit_stmt(node)?;
+                }
+                self.indent -= 1;
+                self.output.push_str(&"    ".repeat(self.indent));
+                self.output.push_str("}\n");
+            }
+            AST::VarDef( name, ty, value ) => {
+                let ty = ty.as_ref().unwrap();
+                let value = match value {
+                    Some(value) => self.emit_expr(value)?,
+                    None => Self::default_value(ty),
+                };
+                self.output.push_str(&format!("{} = {};\n", Self::declare(ty, &Self::local(name)), value));
+            }
+            AST::Return( value ) => match value {
+                Some(value) => {
+                    let value = self.emit_expr(value)?;
+                    self.output.push_str(&format!("return {};\n", value));
+                }
+                None => self.output.push_str("return;\n"),
+            },
+            AST::Break => self.output.push_str("break;\n"),
+            AST::Continue => self.output.push_str("continue;\n"),
+            AST::If( cond, then, els ) => {
+                let cond = self.emit_expr(cond)?;
+                self.output.push_str(&format!("if ({}) ", cond));
+                self.emit_branch(then)?;
+                for e in els {
+                    self.output.push_str(&"    ".repeat(self.indent));
+                    match e.get_kind() {
+                        AST::If( cond, then, _ ) => {
+                            let cond = self.emit_expr(cond)?;
+                            self.output.push_str(&format!("else if ({}) ", cond));
+                            self.emit_branch(then)?;
+                        }
+                        _ => {
+                            self.output.push_str("else ");
+                            self.emit_branch(e)?;
+                        }
+                    }
+                }
+            }
+            AST::While( cond, body, is_do_while ) => {
+                let cond = self.emit_expr(cond)?;
+                if *is_do_while {
+                    self.output.push_str("do ");
+                    self.emit_branch(body)?;
+                    self.line(&format!("while ({});", cond));
+                } else {
+                    self.output.push_str(&format!("while ({}) ", cond));
+                    self.emit_branch(body)?;
+                }
+            }
+            AST::Empty => self.output.push_str(";\n"),
+            _ => {
+                let expr = self.emit_expr(node)?;
+                self.output.push_str(&format!("{};\n", expr));
+            }
+        }
+        Ok(())
+    }
+
+    /// The body of an `if` or a loop is always emitted as a block.
+    fn emit_branch(&mut self, node: &TypedNode) -> Result<(), ()> {
+        match node.get_kind() {
+            AST::Block( .. ) => self.emit_stmt(node),
+            _ => {
+                self.output.push_str("{\n");
+                self.indent += 1;
+                self.output.push_str(&"    ".repeat(self.indent));
+                self.emit_stmt(node)?;
+                self.indent -= 1;
+                self.line("}");
+                Ok(())
+            }
+        }
+    }
+
+    fn default_value(ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(Type::Bool) => "false".to_string(),
+            UnificationType::Known(Type::String) => "\"\"".to_string(),
+            _ => "0".to_string(),
+        }
+    }
+
+    fn emit_expr(&mut self, node: &TypedNode) -> Result<String, ()> {
+        let ty = node.get_type();
+        Ok(match node.get_kind() {
+            // the smallest i64 cannot be written as a literal in C
+            AST::Int( i64::MIN ) => format!("(({})(-9223372036854775807LL - 1))", Self::c_type(ty)),
+            AST::Int( value ) => format!("(({}){}LL)", Self::c_type(ty), value),
+            AST::Float( value ) => format!("(({}){:?})", Self::c_type(ty), value),
+            AST::Bool( value ) => value.to_string(),
+            AST::String( value ) => Self::string_literal(value),
+            AST::Ident( name, _ ) => match self.symbols.get(name) {
+                Some(symbol) => symbol.clone(),
+                None => Self::local(name),
+            },
+            AST::Call( callee, _ ) if matches!(callee.get_kind(), AST::Ident( name, _ ) if name == BUILTIN_ASSERT) => {
+                report!(self, Error::Unsupported("'assert' calls".to_string(), "the C backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' or 'snowball test' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                });
+            }
+            AST::Call( callee, args ) => {
+                let callee = self.emit_expr(callee)?;
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.emit_expr(arg)?);
+                }
+                format!("{}({})", callee, values.join(", "))
+            }
+            AST::Assign( target, value, None ) => {
+                let target = self.emit_expr(target)?;
+                let value = self.emit_expr(value)?;
+                format!("({} = {})", target, value)
+            }
+            // a variable can be repeated on the right hand side, other targets could have side effects
+            AST::Assign( target, value, Some(op) ) => {
+                if !matches!(target.get_kind(), AST::Ident( .. )) {
+                    report!(self, Error::Unsupported("compound assignments like this one".to_string(), "the C backend".to_string()), node);
+                }
+                let place = self.emit_expr(target)?;
+                let value = self.emit_expr(value)?;
+                let result = self.emit_binary_values(node, op, target.get_type(), place.clone(), value)?;
+                format!("({} = {})", place, result)
+            }
+            AST::BinaryOp( op, lhs, rhs, is_unary ) => self.emit_binary_op(node, op, lhs, rhs, *is_unary)?,
+            _ => report!(self, Error::Unsupported("expressions like this one".to_string(), "the C backend".to_string()), node),
+        })
+    }
+
+    fn emit_binary_op(&mut self, node: &TypedNode, op: &BinaryOp, lhs: &TypedNode, rhs: &TypedNode, is_unary: bool) -> Result<String, ()> {
+        let operand_ty = lhs.get_type();
+        let a = self.emit_expr(lhs)?;
+        if is_unary {
+            return Ok(match op {
+                BinaryOp::Not => format!("(!{})", a),
+                _ => format!("(({})({}{}))", Self::c_type(operand_ty), op, a),
+            });
+        }
+        let b = self.emit_expr(rhs)?;
+        self.emit_binary_values(node, op, operand_ty, a, b)
+    }
+
+    /// Applies an operator to already emitted operands, `operand_ty` is the type of the left one.
+    fn emit_binary_values(&mut self, node: &TypedNode, op: &BinaryOp, operand_ty: &UnificationType, a: String, b: String) -> Result<String, ()> {
+        let is_string = matches!(operand_ty, UnificationType::Known(Type::String));
+        Ok(match op {
+            BinaryOp::Add if is_string => format!("sn_concat({}, {})", a, b),
+            BinaryOp::Eq | BinaryOp::Ne if is_string => {
+                format!("(__builtin_strcmp({}, {}) {} 0)", a, b, op)
+            }
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge | BinaryOp::And | BinaryOp::Or => {
+                format!("({} {} {})", a, op, b)
+            }
+            BinaryOp::Mod if matches!(operand_ty, UnificationType::Known(Type::Float { size: 32 })) => format!("__builtin_fmodf({}, {})", a, b),
+            BinaryOp::Mod if matches!(operand_ty, UnificationType::Known(Type::Float { .. })) => format!("__builtin_fmod({}, {})", a, b),
+            BinaryOp::Index => format!("{}[{}]", a, b),
+            BinaryOp::Div | BinaryOp::Mod if matches!(operand_ty, UnificationType::Known(Type::Integer { .. })) => {
+                format!("sn_{}_{}({}, {})", if matches!(op, BinaryOp::Div) { "div" } else { "mod" }, Self::c_type(operand_ty), a, b)
+            }
+            // the result is converted back since C promotes small integers to 'int'
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                format!("(({})({} {} {}))", Self::c_type(operand_ty), a, op, b)
+            }
+            _ => report!(self, Error::Unsupported(format!("'{}' operations", op), "the C backend".to_string()), node),
+        })
+    }
+
+    fn string_literal(value: &str) -> String {
+        let mut result = String::from("\"");
+        for byte in value.bytes() {
+            match byte {
+                b'"' => result.push_str("\\\""),
+                b'\\' => result.push_str("\\\\"),
+                b'?' => result.push_str("\\?"),
+                b'\n' => result.push_str("\\n"),
+                b'\t' => result.push_str("\\t"),
+                0x20..=0x7e => result.push(byte as char),
+                // octal escapes never swallow the characters that follow them
+                _ => result.push_str(&format!("\\{:03o}", byte)),
+            }
+        }
+        result.push('"');
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+
+    /// Builds `source` with the system C compiler if there is one and runs
+    /// it. Returns the exit code and what the program printed.
+    fn run(source: &str) -> Option<(i32, String)> {
+        let (compiler, modules) = Compiler::compile_source(source);
+        let Ok(modules) = modules else {
+            panic!("the program doesn't compile");
+        };
+        let c = CBackend::new().generate(&modules, compiler.get_entry().as_ref()).unwrap();
+        let path = std::env::temp_dir().join(format!("snowball-c-{}-{}", std::process::id(), source.len()));
+        let c_path = path.with_extension("c");
+        std::fs::write(&c_path, c).unwrap();
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let built = std::process::Command::new(cc).args(["-std=c99", "-O2", "-fwrapv", "-o"]).arg(&path).arg(&c_path).status();
+        std::fs::remove_file(&c_path).unwrap();
+        if !built.is_ok_and(|status| status.success()) {
+            return None;
+        }
+        let output = std::process::Command::new(&path).output().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        Some((output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap()))
+    }
+
+    #[test]
+    fn division_by_zero_stops_the_program() {
+        for op in ["/", "%"] {
+            let source = format!("external \"C\" func puts(s: String) i32;\n\nfunc main() i32 {{\n    let zero: i64 = 0;\n    puts(\"before\");\n    let x = 7 {} zero;\n    puts(\"after\");\n    return 0;\n}}\n", op);
+            if let Some((code, output)) = run(&source) {
+                assert_eq!(code, 1);
+                assert_eq!(output, "before\nerror[E0033]: attempt to divide by zero\n");
+            }
+        }
+    }
+
+    #[test]
+    fn dividing_the_smallest_integer_by_minus_one_wraps() {
+        let source = "func main() i32 {\n    let min: i32 = -2147483648;\n    let big: i64 = -9223372036854775808;\n    let minus_one: i32 = -1;\n    if min / minus_one != min || min % minus_one != 0 { return 1; }\n    if big / -1 != big || big % -1 != 0 { return 2; }\n    return 0;\n}\n";
+        if let Some((code, _)) = run(source) {
+            assert_eq!(code, 0);
+        }
+    }
+}
@@ -0,0 +1,651 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::ast::attrs::{AstAttrs, ExternalLinkage};
+use crate::ast::nodes::{BinaryOp, MatchArm, Pattern, PatternKind, AST};
+use crate::ast::source::SourceMap;
+use crate::ast::typed::TypedNode;
+use crate::backend::top_nodes;
+use crate::frontend::module::{Module, NamespacePath};
+use crate::frontend::type_checker::{Type, UnificationType, BUILTIN_ASSERT};
+use crate::reports::format::MessageFormat;
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+
+/// Calls nested deeper than this are reported as a stack overflow instead of
+/// crashing the interpreter itself.
+const MAX_CALL_DEPTH: usize = 10_000;
+pub const STACK_SIZE: usize = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Object(Rc<RefCell<Instance>>),
+    Function(String),
+    /// A method bound to the instance it was accessed on.
+    Method(Box<Value>, String),
+    /// An enum value, the name of its variant and its fields in declaration order.
+    Variant(String, Vec<Value>),
+    Void,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    class: usize,
+    fields: HashMap<String, Value>,
+}
+
+struct Class {
+    path: String,
+    parent: Option<usize>,
+    members: Vec<(String, UnificationType)>,
+}
+
+/// Where an assignment stores its value, the object of a member is
+/// evaluated once even for compound assignments.
+enum Place {
+    Variable(String),
+    Field(Rc<RefCell<Instance>>, String),
+}
+
+/// How a statement finished executing.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+pub struct Interpreter {
+    main: String,
+    functions: HashMap<String, TypedNode>,
+    classes: HashMap<usize, Class>,
+    scopes: Vec<HashMap<String, Value>>,
+    depth: usize,
+    reports: Reports,
+    /// Set once the program called `exit`, which unwinds like an error.
+    exit_code: Option<i32>,
+}
+
+macro_rules! report {
+    ($self:ident, $error_type:expr, $node:expr) => { {
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()));
+        return Err(());
+    }
+    };
+    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
+        return Err(());
+    }
+    }
+}
+
+impl Interpreter {
+    pub fn new(modules: &[Module<TypedNode>], entry: &NamespacePath) -> Interpreter {
+        let mut interpreter = Interpreter {
+            main: format!("{}::main", entry),
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            scopes: Vec::new(),
+            depth: 0,
+            reports: Reports::new(),
+            exit_code: None,
+        };
+        for node in top_nodes(modules) {
+            interpreter.declare(node);
+        }
+        interpreter
+    }
+
+    /// Runs the program on a thread with a big stack so deeply recursive
+    /// programs hit `MAX_CALL_DEPTH` before overflowing the real stack.
+    /// Returns the exit code of the program.
+    pub fn run(modules: Vec<Module<TypedNode>>, entry: NamespacePath, sources: &SourceMap, format: MessageFormat) -> i32 {
+        let sources = sources.clone();
+        std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || Interpreter::new(&modules, &entry).run_main(&sources, format))
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    fn declare(&mut self, node: &TypedNode) {
+        match node.get_kind() {
+            AST::FuncDef( name, .. ) => {
+                self.functions.insert(name.clone(), node.clone());
+            }
+            AST::ClassDef( path, parent, _, members, methods, _, id ) => {
+                let parent = match parent {
+                    Some(UnificationType::Known(Type::Object { id, .. })) => Some(*id),
+                    _ => None,
+                };
+                let members = members.iter().map(|m| (m.get_name().clone(), m.get_ty().clone())).collect();
+                self.classes.insert(id.unwrap(), Class { path: path.clone(), parent, members });
+                for method in methods {
+                    self.declare(method);
+                }
+            }
+            AST::EnumDef( .. ) | AST::InterfaceDef( .. ) => {}
+            _ => panic!("Unexpected global node"),
+        }
+    }
+
+    pub fn run_main(&mut self, sources: &SourceMap, format: MessageFormat) -> i32 {
+        if !self.functions.contains_key(&self.main) {
+            self.reports.add_global_error(Error::MissingMain, Some(format!("Declare a function named 'main' in the '{}' module to run the program.", self.main.rsplit_once("::").unwrap().0)));
+            self.reports.handle_errors(sources, format);
+            return 1;
+        }
+        let result = self.call(&self.main.clone(), None, Vec::new());
+        std::io::stdout().flush().unwrap();
+        match result {
+            Ok(Value::Int(code)) => code as i32,
+            Ok(_) => 0,
+            Err(()) => match self.exit_code {
+                Some(code) => code,
+                None => {
+                    self.reports.handle_errors(sources, format);
+                    1
+                }
+            },
+        }
+    }
+
+    /// Calls a function that takes no arguments, used to run tests. The
+    /// errors of a failed call are left in the reports.
+    pub fn run_function(&mut self, name: &str) -> Result<Value, ()> {
+        let result = self.call(name, None, Vec::new());
+        std::io::stdout().flush().unwrap();
+        result
+    }
+
+    pub fn get_reports(&self) -> &Reports {
+        &self.reports
+    }
+
+    /// The code the program passed to `exit`, if it called it.
+    pub fn get_exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    fn call(&mut self, name: &str, this: Option<Value>, args: Vec<Value>) -> Result<Value, ()> {
+        let function = self.functions[name].clone();
+        let AST::FuncDef( _, params, _, body, .. ) = function.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let Some(body) = body else {
+            return self.call_external(&function, args);
+        };
+        if self.depth == MAX_CALL_DEPTH {
+            report!(self, Error::StackOverflow, function, ErrorInfo {
+                info: Some(format!("More than {} nested calls.", MAX_CALL_DEPTH)),
+                help: Some("Make sure the recursion of this function has a base case.".to_string()),
+                ..Default::default()
+            });
+        }
+        let mut scope = HashMap::new();
+        let mut args = args.into_iter();
+        for param in params {
+            let value = match (param.get_name().as_str(), &this) {
+                ("self", Some(this)) => this.clone(),
+                _ => args.next().unwrap(),
+            };
+            scope.insert(param.get_name().clone(), value);
+        }
+        let scopes = std::mem::replace(&mut self.scopes, vec![scope]);
+        self.depth += 1;
+        let result = self.exec(body);
+        self.depth -= 1;
+        self.scopes = scopes;
+        match result? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::Void),
+        }
+    }
+
+    /// External functions have no body to run, a few functions of the C
+    /// standard library are emulated instead.
+    fn call_external(&mut self, function: &TypedNode, args: Vec<Value>) -> Result<Value, ()> {
+        let AST::FuncDef( path, .. ) = function.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let name = path.rsplit("::").next().unwrap();
+        let is_c = function.get_attrs().as_ref().is_some_and(|a| a.get_attrs().contains(&AstAttrs::External(ExternalLinkage::C)));
+        match (is_c, name, args.as_slice()) {
+            (true, "puts", [Value::String(s)]) => {
+                println!("{}", s);
+                Ok(Value::Int(0))
+            }
+            (true, "putchar", [Value::Int(c)]) => {
+                print!("{}", char::from_u32(*c as u32).unwrap_or(char::REPLACEMENT_CHARACTER));
+                Ok(Value::Int(*c))
+            }
+            (true, "exit", [Value::Int(code)]) => {
+                self.exit_code = Some(*code as i32);
+                Err(())
+            }
+            _ => report!(self, Error::Unsupported(format!("external functions like '{}'", name), "the interpreter".to_string()), function, ErrorInfo {
+                info: Some("This function has no body the interpreter could run.".to_string()),
+                help: Some("Use 'snowball build' to link against external functions.".to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn exec(&mut self, node: &TypedNode) -> Result<Flow, ()> {
+        match node.get_kind() {
+            AST::Block( nodes ) => {
+                self.scopes.push(HashMap::new());
+                let mut result = Ok(Flow::Normal);
+                for node in nodes {
+                    result = self.exec(node);
+                    if !matches!(result, Ok(Flow::Normal)) {
+                        break;
+                    }
+                }
+                self.scopes.pop();
+                result
+            }
+            AST::VarDef( name, ty, value ) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Self::default_value(ty.as_ref().unwrap()),
+                };
+                self.scopes.last_mut().unwrap().insert(name.clone(), value);
+                Ok(Flow::Normal)
+            }
+            AST::Return( value ) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Void,
+                };
+                Ok(Flow::Return(value))
+            }
+            AST::Break => Ok(Flow::Break),
+            AST::Continue => Ok(Flow::Continue),
+            AST::If( cond, then, els ) => {
+                if self.eval_bool(cond)? {
+                    return self.exec(then);
+                }
+                for e in els {
+                    match e.get_kind() {
+                        AST::If( cond, then, _ ) => if self.eval_bool(cond)? {
+                            return self.exec(then);
+                        },
+                        _ => return self.exec(e),
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            AST::While( cond, body, is_do_while ) => {
+                let mut first = *is_do_while;
+                while first || self.eval_bool(cond)? {
+                    first = false;
+                    match self.exec(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            AST::Match( scrutinee, arms ) => {
+                let value = self.eval(scrutinee)?;
+                let Some(arm) = self.select_arm(&value, arms)? else {
+                    return Ok(Flow::Normal);
+                };
+                let result = match arm.get_body().get_kind() {
+                    AST::Block( .. ) => self.exec(arm.get_body()),
+                    _ => self.eval(arm.get_body()).map(|_| Flow::Normal),
+                };
+                self.scopes.pop();
+                result
+            }
+            AST::Empty => Ok(Flow::Normal),
+            _ => {
+                self.eval(node)?;
+                Ok(Flow::Normal)
+            }
+        }
+    }
+
+    fn eval_bool(&mut self, node: &TypedNode) -> Result<bool, ()> {
+        match self.eval(node)? {
+            Value::Bool(value) => Ok(value),
+            value => panic!("Expected a boolean, found {:?}", value),
+        }
+    }
+
+    fn eval(&mut self, node: &TypedNode) -> Result<Value, ()> {
+        match node.get_kind() {
+            AST::Int( value ) => Ok(match node.get_type() {
+                UnificationType::Known(Type::Float { .. }) => Value::Float(*value as f64),
+                _ => Value::Int(*value),
+            }),
+            AST::Float( value ) => Ok(Value::Float(*value)),
+            AST::Bool( value ) => Ok(Value::Bool(*value)),
+            AST::String( value ) => Ok(Value::String(value.clone())),
+            AST::Ident( name, _ ) => {
+                for scope in self.scopes.iter().rev() {
+                    if let Some(value) = scope.get(name) {
+                        return Ok(value.clone());
+                    }
+                }
+                Ok(Value::Function(name.clone()))
+            }
+            AST::ClassAccess( base, name ) => {
+                let base = self.eval(base)?;
+                let Value::Object(instance) = &base else {
+                    panic!("Expected an object, found {:?}", base);
+                };
+                if let Some(value) = instance.borrow().fields.get(name) {
+                    return Ok(value.clone());
+                }
+                let method = self.find_method(instance.borrow().class, name).unwrap();
+                Ok(Value::Method(Box::new(base.clone()), method))
+            }
+            AST::Call( callee, args ) if matches!(callee.get_kind(), AST::Ident( name, _ ) if name == BUILTIN_ASSERT) => {
+                if !self.eval_bool(&args[0])? {
+                    report!(self, Error::AssertionFailed, node, ErrorInfo {
+                        info: Some("This condition is false.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                Ok(Value::Void)
+            }
+            AST::Call( callee, args ) => {
+                let callee = self.eval(callee)?;
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                match callee {
+                    Value::Function(name) => self.call(&name, None, values),
+                    Value::Method(this, name) => self.call(&name, Some(*this), values),
+                    value => panic!("Expected a function, found {:?}", value),
+                }
+            }
+            AST::ClassInit( ty, args ) => {
+                let UnificationType::Known(Type::Object { id, .. }) = ty else {
+                    panic!("Expected an object type");
+                };
+                let mut fields = HashMap::new();
+                let mut class = Some(*id);
+                while let Some(current) = class {
+                    for (name, ty) in &self.classes[&current].members {
+                        fields.entry(name.clone()).or_insert_with(|| Self::default_value(ty));
+                    }
+                    class = self.classes[&current].parent;
+                }
+                let instance = Value::Object(Rc::new(RefCell::new(Instance { class: *id, fields })));
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                if let Some(constructor) = self.find_method(*id, "constructor") {
+                    self.call(&constructor, Some(instance.clone()), values)?;
+                }
+                Ok(instance)
+            }
+            AST::BinaryOp( op, lhs, rhs, is_unary ) => self.eval_binary_op(node, op, lhs, rhs, *is_unary),
+            AST::Assign( target, value, op ) => {
+                let value = self.eval(value)?;
+                let place = self.place(target)?;
+                let value = match op {
+                    Some(op) => {
+                        let current = self.load(&place);
+                        self.apply_binary_op(node, op, target.get_type(), current, value)?
+                    }
+                    None => value,
+                };
+                self.store(place, value.clone());
+                Ok(value)
+            }
+            AST::VariantInit( _, variant, fields ) => {
+                let mut values = Vec::new();
+                for (_, value) in fields {
+                    values.push(self.eval(value)?);
+                }
+                Ok(Value::Variant(variant.clone(), values))
+            }
+            AST::Match( scrutinee, arms ) => {
+                let value = self.eval(scrutinee)?;
+                let arm = self.select_arm(&value, arms)?.expect("the type checker makes matches exhaustive");
+                let result = self.eval(arm.get_body());
+                self.scopes.pop();
+                result
+            }
+            _ => panic!("Unexpected node {:?}", node.get_kind()),
+        }
+    }
+
+    /// Finds the first arm matching a value. The bindings of the arm are left
+    /// in a new scope the caller pops after running the arm.
+    fn select_arm<'a>(&mut self, value: &Value, arms: &'a [MatchArm<TypedNode, UnificationType>]) -> Result<Option<&'a MatchArm<TypedNode, UnificationType>>, ()> {
+        for arm in arms {
+            let mut bindings = HashMap::new();
+            if !Self::matches(arm.get_pattern(), value, &mut bindings) {
+                continue;
+            }
+            self.scopes.push(bindings);
+            let guard = match arm.get_guard() {
+                Some(guard) => self.eval_bool(guard),
+                None => Ok(true),
+            };
+            match guard {
+                Ok(true) => return Ok(Some(arm)),
+                Ok(false) => {
+                    self.scopes.pop();
+                }
+                Err(()) => {
+                    self.scopes.pop();
+                    return Err(());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn matches(pattern: &Pattern<UnificationType>, value: &Value, bindings: &mut HashMap<String, Value>) -> bool {
+        match (pattern.get_kind(), value) {
+            (PatternKind::Wildcard, _) => true,
+            (PatternKind::Binding( name ), _) => {
+                bindings.insert(name.clone(), value.clone());
+                true
+            }
+            (PatternKind::Int( expected ), Value::Int( value )) => expected == value,
+            (PatternKind::String( expected ), Value::String( value )) => expected == value,
+            (PatternKind::Bool( expected ), Value::Bool( value )) => expected == value,
+            (PatternKind::Variant( _, expected, _, patterns, _ ), Value::Variant( variant, fields )) => {
+                expected == variant && patterns.iter().zip(fields).all(|((_, pattern), field)| Self::matches(pattern, field, bindings))
+            }
+            (_, value) => panic!("Unexpected value {:?} for a pattern", value),
+        }
+    }
+
+    /// Structural equality of enum values.
+    fn equal(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            (Value::Variant(a, a_fields), Value::Variant(b, b_fields)) => a == b && a_fields.iter().zip(b_fields).all(|(a, b)| Self::equal(a, b)),
+            _ => false,
+        }
+    }
+
+    fn place(&mut self, target: &TypedNode) -> Result<Place, ()> {
+        match target.get_kind() {
+            AST::Ident( name, _ ) => Ok(Place::Variable(name.clone())),
+            AST::ClassAccess( base, name ) => {
+                let Value::Object(instance) = self.eval(base)? else {
+                    panic!("Expected an object");
+                };
+                Ok(Place::Field(instance, name.clone()))
+            }
+            _ => report!(self, Error::Unsupported("assignments through a pointer".to_string(), "the interpreter".to_string()), target),
+        }
+    }
+
+    fn load(&self, place: &Place) -> Value {
+        match place {
+            Place::Variable(name) => self.scopes.iter().rev().find_map(|scope| scope.get(name)).cloned().unwrap_or_else(|| panic!("Unknown variable '{}'", name)),
+            Place::Field(instance, name) => instance.borrow().fields[name].clone(),
+        }
+    }
+
+    fn store(&mut self, place: Place, value: Value) {
+        match place {
+            Place::Variable(name) => {
+                let slot = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&name));
+                *slot.unwrap_or_else(|| panic!("Unknown variable '{}'", name)) = value;
+            }
+            Place::Field(instance, name) => {
+                instance.borrow_mut().fields.insert(name, value);
+            }
+        }
+    }
+
+    /// Looks for a method starting at the runtime class of an instance, which
+    /// makes overridden methods take precedence over the parent ones.
+    fn find_method(&self, class: usize, name: &str) -> Option<String> {
+        let mut current = Some(class);
+        while let Some(id) = current {
+            let method = format!("{}::{}", self.classes[&id].path, name);
+            if self.functions.contains_key(&method) {
+                return Some(method);
+            }
+            current = self.classes[&id].parent;
+        }
+        None
+    }
+
+    fn default_value(ty: &UnificationType) -> Value {
+        match ty {
+            UnificationType::Known(Type::Integer { .. }) => Value::Int(0),
+            UnificationType::Known(Type::Float { .. }) => Value::Float(0.0),
+            UnificationType::Known(Type::Bool) => Value::Bool(false),
+            UnificationType::Known(Type::String) => Value::String(String::new()),
+            _ => Value::Void,
+        }
+    }
+
+    fn eval_binary_op(&mut self, node: &TypedNode, op: &BinaryOp, lhs: &TypedNode, rhs: &TypedNode, is_unary: bool) -> Result<Value, ()> {
+        let ty = lhs.get_type().clone();
+        if is_unary {
+            return Ok(match (op, self.eval(lhs)?) {
+                (BinaryOp::Sub, Value::Int(v)) => Self::wrap_int(-Self::int_value(v, &ty), &ty),
+                (BinaryOp::Sub, Value::Float(v)) => Self::wrap_float(-v, &ty),
+                (BinaryOp::BitNot, Value::Int(v)) => Self::wrap_int(!Self::int_value(v, &ty), &ty),
+                (BinaryOp::Not, Value::Bool(v)) => Value::Bool(!v),
+                (_, value) => panic!("Invalid unary operand {:?}", value),
+            });
+        }
+        // '&&' and '||' only evaluate the right hand side when needed
+        match op {
+            BinaryOp::And => return Ok(Value::Bool(self.eval_bool(lhs)? && self.eval_bool(rhs)?)),
+            BinaryOp::Or => return Ok(Value::Bool(self.eval_bool(lhs)? || self.eval_bool(rhs)?)),
+            _ => {}
+        }
+        let left = self.eval(lhs)?;
+        let right = self.eval(rhs)?;
+        self.apply_binary_op(node, op, &ty, left, right)
+    }
+
+    /// Applies an operator to evaluated operands, `ty` is the type of the left one.
+    fn apply_binary_op(&mut self, node: &TypedNode, op: &BinaryOp, ty: &UnificationType, left: Value, right: Value) -> Result<Value, ()> {
+        let value = match (left, right) {
+            (Value::Int(a), Value::Int(b)) => {
+                let (a, b) = (Self::int_value(a, ty), Self::int_value(b, ty));
+                match op {
+                    BinaryOp::Add => Self::wrap_int(a + b, ty),
+                    BinaryOp::Sub => Self::wrap_int(a - b, ty),
+                    BinaryOp::Mul => Self::wrap_int(a.wrapping_mul(b), ty),
+                    BinaryOp::Div | BinaryOp::Mod if b == 0 => report!(self, Error::DivisionByZero, node, ErrorInfo {
+                        info: Some("The right hand side of this operation is zero.".to_string()),
+                        ..Default::default()
+                    }),
+                    BinaryOp::Div => Self::wrap_int(a / b, ty),
+                    BinaryOp::Mod => Self::wrap_int(a % b, ty),
+                    _ => Self::compare(op, a.cmp(&b)),
+                }
+            }
+            (Value::Float(a), Value::Float(b)) => match op {
+                BinaryOp::Add => Self::wrap_float(a + b, ty),
+                BinaryOp::Sub => Self::wrap_float(a - b, ty),
+                BinaryOp::Mul => Self::wrap_float(a * b, ty),
+                BinaryOp::Div => Self::wrap_float(a / b, ty),
+                BinaryOp::Mod => Self::wrap_float(a % b, ty),
+                BinaryOp::Ne => Value::Bool(a != b),
+                _ => match a.partial_cmp(&b) {
+                    Some(ordering) => Self::compare(op, ordering),
+                    None => Value::Bool(false),
+                },
+            },
+            (Value::String(a), Value::String(b)) => match op {
+                BinaryOp::Add => Value::String(a + &b),
+                _ => Self::compare(op, a.cmp(&b)),
+            },
+            (Value::Bool(a), Value::Bool(b)) => Self::compare(op, a.cmp(&b)),
+            (Value::Object(a), Value::Object(b)) => match op {
+                BinaryOp::Eq => Value::Bool(Rc::ptr_eq(&a, &b)),
+                _ => Value::Bool(!Rc::ptr_eq(&a, &b)),
+            },
+            (a @ Value::Variant( .. ), b) => match op {
+                BinaryOp::Eq => Value::Bool(Self::equal(&a, &b)),
+                _ => Value::Bool(!Self::equal(&a, &b)),
+            },
+            (_, _) => report!(self, Error::Unsupported(format!("'{}' operations on these values", op), "the interpreter".to_string()), node),
+        };
+        Ok(value)
+    }
+
+    fn compare(op: &BinaryOp, ordering: std::cmp::Ordering) -> Value {
+        Value::Bool(match op {
+            BinaryOp::Eq => ordering.is_eq(),
+            BinaryOp::Ne => ordering.is_ne(),
+            BinaryOp::Lt => ordering.is_lt(),
+            BinaryOp::Le => ordering.is_le(),
+            BinaryOp::Gt => ordering.is_gt(),
+            BinaryOp::Ge => ordering.is_ge(),
+            _ => panic!("Expected a comparison operator"),
+        })
+    }
+
+    /// Integers are stored as the bits of an `i64`, this widens them to their real value.
+    fn int_value(value: i64, ty: &UnificationType) -> i128 {
+        match ty {
+            UnificationType::Known(Type::Integer { signed: false, .. }) => value as u64 as i128,
+            _ => value as i128,
+        }
+    }
+
+    /// Truncates the result of an operation to the size of its integer type.
+    fn wrap_int(value: i128, ty: &UnificationType) -> Value {
+        let UnificationType::Known(Type::Integer { size, signed }) = ty else {
+            return Value::Int(value as i64);
+        };
+        let bits = *size as u32;
+        let truncated = value & ((1i128 << bits) - 1);
+        let value = if *signed && truncated >> (bits - 1) == 1 {
+            truncated - (1i128 << bits)
+        } else {
+            truncated
+        };
+        Value::Int(value as i64)
+    }
+
+    fn wrap_float(value: f64, ty: &UnificationType) -> Value {
+        match ty {
+            UnificationType::Known(Type::Float { size: 32 }) => Value::Float(value as f32 as f64),
+            _ => Value::Float(value),
+        }
+    }
+}
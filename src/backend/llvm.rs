@@ -0,0 +1,626 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::attrs::{AstAttrs, Linkage};
+use crate::ast::nodes::{BinaryOp, AST};
+use crate::ast::typed::TypedNode;
+use crate::backend::{symbol_name, top_nodes};
+use crate::frontend::module::{Module, NamespacePath};
+use crate::frontend::type_checker::{Type, UnificationType, BUILTIN_ASSERT};
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+
+/// Functions of the C standard library used by the string operations, with
+/// their signatures. They are only declared if the program didn't already.
+const RUNTIME_FUNCTIONS: [(&str, &str); 3] = [
+    ("strlen", "declare i64 @strlen(ptr)"),
+    ("malloc", "declare ptr @malloc(i64)"),
+    ("strcmp", "declare i32 @strcmp(ptr, ptr)"),
+];
+
+const CONCAT: &str = "define private ptr @sn.concat(ptr %a, ptr %b) {
+entry:
+  %a.len = call i64 @strlen(ptr %a)
+  %b.len = call i64 @strlen(ptr %b)
+  %len = add i64 %a.len, %b.len
+  %size = add i64 %len, 1
+  %result = call ptr @malloc(i64 %size)
+  call void @llvm.memcpy.p0.p0.i64(ptr %result, ptr %a, i64 %a.len, i1 false)
+  %end = getelementptr i8, ptr %result, i64 %a.len
+  %b.size = add i64 %b.len, 1
+  call void @llvm.memcpy.p0.p0.i64(ptr %end, ptr %b, i64 %b.size, i1 false)
+  ret ptr %result
+}
+
+declare void @llvm.memcpy.p0.p0.i64(ptr, ptr, i64, i1)
+";
+
+/// Lowers a type checked module to textual LLVM IR. Every local variable
+/// lives in an `alloca` of the entry block, where `mem2reg` can promote it
+/// to a register. The IR uses opaque pointers (`ptr`), so it needs LLVM 15
+/// or newer.
+pub struct LLVMBackend {
+    /// Maps the path of every function to the name it is emitted with.
+    symbols: HashMap<String, String>,
+    output: String,
+    /// The instructions of the function being emitted.
+    body: String,
+    /// The `alloca`s of the function being emitted, they are moved to the
+    /// entry block so loops don't grow the stack.
+    allocas: String,
+    strings: Vec<String>,
+    uses_runtime: bool,
+    next_register: usize,
+    next_label: usize,
+    locals: Vec<HashMap<String, (String, UnificationType)>>,
+    /// The `continue` and `break` targets of the loops around the current statement.
+    loops: Vec<(String, String)>,
+    /// Whether the current basic block already ended with a terminator.
+    terminated: bool,
+    reports: Reports,
+}
+
+macro_rules! report {
+    ($self:ident, $error_type:expr, $node:expr) => { {
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()));
+        return Err(());
+    }
+    };
+    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
+        return Err(());
+    }
+    }
+}
+
+impl LLVMBackend {
+    pub fn new() -> LLVMBackend {
+        LLVMBackend {
+            symbols: HashMap::new(),
+            output: String::new(),
+            body: String::new(),
+            allocas: String::new(),
+            strings: Vec::new(),
+            uses_runtime: false,
+            next_register: 0,
+            next_label: 0,
+            locals: Vec::new(),
+            loops: Vec::new(),
+            terminated: false,
+            reports: Reports::new(),
+        }
+    }
+
+    pub fn get_reports(&self) -> &Reports {
+        &self.reports
+    }
+
+    pub fn generate(&mut self, modules: &[Module<TypedNode>], entry: Option<&NamespacePath>) -> Result<String, ()> {
+        let nodes = top_nodes(modules);
+        for node in &nodes {
+            match node.get_kind() {
+                AST::FuncDef( path, .. ) => {
+                    self.symbols.insert(path.clone(), symbol_name(node));
+                }
+                AST::ClassDef( .. ) => report!(self, Error::Unsupported("classes".to_string(), "the LLVM backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                AST::EnumDef( .. ) => report!(self, Error::Unsupported("enums".to_string(), "the LLVM backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                AST::InterfaceDef( .. ) => report!(self, Error::Unsupported("interfaces".to_string(), "the LLVM backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                }),
+                _ => panic!("Unexpected global node"),
+            }
+        }
+        let mut functions = String::new();
+        let mut emitted = HashSet::new();
+        for node in &nodes {
+            // the same C function may be declared by more than one module
+            if emitted.insert(symbol_name(node)) {
+                functions.push_str(&self.emit_function(node)?);
+            }
+        }
+        // libraries have no entry point
+        if let Some(entry) = entry {
+            let main = format!("{}::main", entry);
+            if let Some(main_node) = nodes.iter().find(|n| matches!(n.get_kind(), AST::FuncDef( path, .. ) if *path == main)) {
+                functions.push_str(&self.emit_entry_point(main_node));
+            }
+        }
+
+        let module = entry.and_then(|entry| modules.iter().find(|m| m.get_path() == entry)).or(modules.first());
+        if let Some(module) = module {
+            self.output.push_str(&format!("; ModuleID = '{}'\nsource_filename = \"{}\"\n\n", module.get_path(), module.get_file_name().clone().unwrap_or_default()));
+        }
+        for (i, string) in self.strings.iter().enumerate() {
+            self.output.push_str(&format!("@.str.{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"\n", i, string.len() + 1, Self::escape(string)));
+        }
+        if !self.strings.is_empty() {
+            self.output.push('\n');
+        }
+        self.output.push_str(&functions);
+        if self.uses_runtime {
+            let declared = self.symbols.values().cloned().collect::<HashSet<_>>();
+            self.output.push('\n');
+            for (name, declaration) in RUNTIME_FUNCTIONS {
+                if !declared.contains(name) {
+                    self.output.push_str(declaration);
+                    self.output.push('\n');
+                }
+            }
+            self.output.push('\n');
+            self.output.push_str(CONCAT);
+        }
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    fn llvm_type(ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(Type::Integer { size, .. }) => format!("i{}", size),
+            UnificationType::Known(Type::Float { size: 32 }) => "float".to_string(),
+            UnificationType::Known(Type::Float { .. }) => "double".to_string(),
+            UnificationType::Known(Type::Bool) => "i1".to_string(),
+            UnificationType::Known(Type::String | Type::Pointer { .. } | Type::Reference { .. } | Type::Function { .. }) => "ptr".to_string(),
+            UnificationType::Known(Type::Void) => "void".to_string(),
+            _ => panic!("Type {:?} has no LLVM equivalent", ty),
+        }
+    }
+
+    fn is_signed(ty: &UnificationType) -> bool {
+        matches!(ty, UnificationType::Known(Type::Integer { signed: true, .. }))
+    }
+
+    fn is_float(ty: &UnificationType) -> bool {
+        matches!(ty, UnificationType::Known(Type::Float { .. }))
+    }
+
+    /// Maps the attributes of a function to its linkage and LLVM function attributes.
+    fn function_attributes(node: &TypedNode) -> (String, String) {
+        let attrs = node.get_attrs().as_ref().map(|a| a.get_attrs().clone()).unwrap_or_default();
+        let linkage = if attrs.contains(&AstAttrs::Linkage(Linkage::Internal)) { "internal " } else { "" };
+        let mut function_attrs = Vec::new();
+        if attrs.contains(&AstAttrs::NoOptimize) {
+            // 'optnone' is only valid together with 'noinline'
+            function_attrs.push("noinline");
+            function_attrs.push("optnone");
+        } else if attrs.contains(&AstAttrs::NoInline) {
+            function_attrs.push("noinline");
+        } else if attrs.contains(&AstAttrs::Inline) {
+            function_attrs.push("inlinehint");
+        }
+        let function_attrs = function_attrs.iter().map(|a| format!(" {}", a)).collect::<String>();
+        (linkage.to_string(), function_attrs)
+    }
+
+    fn emit_function(&mut self, node: &TypedNode) -> Result<String, ()> {
+        let AST::FuncDef( path, args, ret, body, .. ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let symbol = self.symbols[path].clone();
+        let ret_ty = Self::llvm_type(ret);
+        let Some(body) = body else {
+            let params = args.iter().map(|param| Self::llvm_type(param.get_ty())).collect::<Vec<_>>().join(", ");
+            return Ok(format!("declare {} @{}({})\n\n", ret_ty, symbol, params));
+        };
+        let (linkage, function_attrs) = Self::function_attributes(node);
+        let params = args.iter().map(|param| format!("{} %{}", Self::llvm_type(param.get_ty()), Self::local_name(param.get_name()))).collect::<Vec<_>>().join(", ");
+        let header = format!("define {}{} @{}({}){} {{\n", linkage, ret_ty, symbol, params, function_attrs);
+        if node.get_attrs().as_ref().is_some_and(|a| a.get_attrs().contains(&AstAttrs::LLVMFunct)) {
+            // the body of an LLVM function is a string with the instructions to emit
+            let instructions = match body.get_kind() {
+                AST::Block( nodes ) => match nodes.as_slice() {
+                    [string] => match string.get_kind() {
+                        AST::String( instructions ) => Some(instructions.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some(instructions) = instructions else {
+                report!(self, Error::UnexpectedItem("LLVM IR string".to_string(), "statements".to_string()), node, ErrorInfo {
+                    info: Some("Functions marked as 'llvm_function' must only contain a string with their LLVM IR.".to_string()),
+                    ..Default::default()
+                });
+            };
+            return Ok(format!("{}{}\n}}\n\n", header, instructions.trim_end()));
+        }
+
+        self.body.clear();
+        self.allocas.clear();
+        self.next_register = 0;
+        self.next_label = 0;
+        self.terminated = false;
+        self.locals = vec![HashMap::new()];
+        self.body.push_str("entry:\n");
+        for param in args {
+            let slot = self.alloca(param.get_name(), param.get_ty());
+            self.instruction(&format!("store {} %{}, ptr {}", Self::llvm_type(param.get_ty()), Self::local_name(param.get_name()), slot));
+        }
+        self.emit_stmt(body)?;
+        if !self.terminated {
+            match ret {
+                UnificationType::Known(Type::Void) => self.terminator("ret void"),
+                // the type checker makes sure every path returns
+                _ => self.terminator("unreachable"),
+            }
+        }
+        let body = self.body.replacen("entry:\n", &format!("entry:\n{}", self.allocas), 1);
+        Ok(format!("{}{}}}\n\n", header, body))
+    }
+
+    /// The program starts at a C style `main` that calls the Snowball one.
+    fn emit_entry_point(&self, main: &TypedNode) -> String {
+        let AST::FuncDef( path, _, ret, .. ) = main.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let symbol = &self.symbols[path];
+        let ret_ty = Self::llvm_type(ret);
+        let conversion = match ret {
+            UnificationType::Known(Type::Void) => return format!("define i32 @main() {{\nentry:\n  call void @{}()\n  ret i32 0\n}}\n", symbol),
+            UnificationType::Known(Type::Integer { size: 32, .. }) => None,
+            UnificationType::Known(Type::Integer { size, .. }) if *size > 32 => Some("trunc"),
+            UnificationType::Known(Type::Integer { .. } | Type::Bool) => Some("zext"),
+            UnificationType::Known(Type::Float { .. }) => Some("fptosi"),
+            _ => return format!("define i32 @main() {{\nentry:\n  call {} @{}()\n  ret i32 0\n}}\n", ret_ty, symbol),
+        };
+        let body = match conversion {
+            Some(conversion) => format!("  %result = call {} @{}()\n  %code = {} {} %result to i32\n  ret i32 %code\n", ret_ty, symbol, conversion, ret_ty),
+            None => format!("  %result = call i32 @{}()\n  ret i32 %result\n", symbol),
+        };
+        format!("define i32 @main() {{\nentry:\n{}}}\n", body)
+    }
+
+    /// Parameters get a prefix so they can't collide with the registers.
+    fn local_name(name: &str) -> String {
+        if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            format!("p.{}", name)
+        } else {
+            format!("\"p.{}\"", Self::escape(name))
+        }
+    }
+
+    fn register(&mut self) -> String {
+        self.next_register += 1;
+        format!("%t{}", self.next_register)
+    }
+
+    fn label(&mut self, name: &str) -> String {
+        self.next_label += 1;
+        format!("{}.{}", name, self.next_label)
+    }
+
+    fn instruction(&mut self, instruction: &str) {
+        if self.terminated {
+            // code after a 'return', 'break' or 'continue' is unreachable but
+            // still needs a block to live in
+            let label = self.label("dead");
+            self.start_block(&label);
+        }
+        self.body.push_str(&format!("  {}\n", instruction));
+    }
+
+    fn terminator(&mut self, instruction: &str) {
+        self.instruction(instruction);
+        self.terminated = true;
+    }
+
+    fn start_block(&mut self, label: &str) {
+        self.body.push_str(&format!("{}:\n", label));
+        self.terminated = false;
+    }
+
+    fn alloca(&mut self, name: &str, ty: &UnificationType) -> String {
+        let slot = self.register();
+        self.allocas.push_str(&format!("  {} = alloca {}\n", slot, Self::llvm_type(ty)));
+        self.locals.last_mut().unwrap().insert(name.to_string(), (slot.clone(), ty.clone()));
+        slot
+    }
+
+    fn lookup(&self, name: &str) -> Option<(String, UnificationType)> {
+        self.locals.iter().rev().find_map(|scope| scope.get(name).cloned())
+    }
+
+    fn emit_stmt(&mut self, node: &TypedNode) -> Result<(), ()> {
+        match node.get_kind() {
+            AST::Block( nodes ) => {
+                self.locals.push(HashMap::new());
+                let result = nodes.iter().try_for_each(|node| self.emit_stmt(node));
+                self.locals.pop();
+                result?;
+            }
+            AST::VarDef( name, ty, value ) => {
+                let ty = ty.as_ref().unwrap();
+                let value = match value {
+                    Some(value) => self.emit_expr(value)?,
+                    None => Self::default_value(ty),
+                };
+                let slot = self.alloca(name, ty);
+                self.instruction(&format!("store {} {}, ptr {}", Self::llvm_type(ty), value, slot));
+            }
+            AST::Return( value ) => match value {
+                Some(value) => {
+                    let ty = Self::llvm_type(value.get_type());
+                    let value = self.emit_expr(value)?;
+                    self.terminator(&format!("ret {} {}", ty, value));
+                }
+                None => self.terminator("ret void"),
+            },
+            AST::Break => {
+                let (_, end) = self.loops.last().unwrap().clone();
+                self.terminator(&format!("br label %{}", end));
+            }
+            AST::Continue => {
+                let (next, _) = self.loops.last().unwrap().clone();
+                self.terminator(&format!("br label %{}", next));
+            }
+            AST::If( cond, then, els ) => {
+                let end = self.label("if.end");
+                let mut branches = vec![(Some(cond), then)];
+                for e in els {
+                    match e.get_kind() {
+                        AST::If( cond, then, _ ) => branches.push((Some(cond), then)),
+                        _ => branches.push((None, e)),
+                    }
+                }
+                for (cond, body) in branches {
+                    match cond {
+                        Some(cond) => {
+                            let value = self.emit_expr(cond)?;
+                            let then_label = self.label("if.then");
+                            let next = self.label("if.next");
+                            self.terminator(&format!("br i1 {}, label %{}, label %{}", value, then_label, next));
+                            self.start_block(&then_label);
+                            self.emit_stmt(body)?;
+                            self.terminator(&format!("br label %{}", end));
+                            self.start_block(&next);
+                        }
+                        None => self.emit_stmt(body)?,
+                    }
+                }
+                self.terminator(&format!("br label %{}", end));
+                self.start_block(&end);
+            }
+            AST::While( cond, body, is_do_while ) => {
+                let cond_label = self.label("loop.cond");
+                let body_label = self.label("loop.body");
+                let end = self.label("loop.end");
+                let first = if *is_do_while { &body_label } else { &cond_label };
+                self.terminator(&format!("br label %{}", first));
+                self.start_block(&cond_label);
+                let value = self.emit_expr(cond)?;
+                self.terminator(&format!("br i1 {}, label %{}, label %{}", value, body_label, end));
+                self.start_block(&body_label);
+                self.loops.push((cond_label.clone(), end.clone()));
+                let result = self.emit_stmt(body);
+                self.loops.pop();
+                result?;
+                self.terminator(&format!("br label %{}", cond_label));
+                self.start_block(&end);
+            }
+            AST::Empty => {}
+            _ => {
+                self.emit_expr(node)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn default_value(ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(Type::Float { .. }) => "0.0".to_string(),
+            UnificationType::Known(Type::Bool) => "false".to_string(),
+            UnificationType::Known(Type::String | Type::Pointer { .. } | Type::Reference { .. } | Type::Function { .. }) => "null".to_string(),
+            _ => "0".to_string(),
+        }
+    }
+
+    /// Emits an expression and returns the operand holding its value.
+    fn emit_expr(&mut self, node: &TypedNode) -> Result<String, ()> {
+        let ty = node.get_type();
+        Ok(match node.get_kind() {
+            AST::Int( value ) => value.to_string(),
+            // floating point constants are written in hexadecimal so they are
+            // exact, 'float' constants use the 'double' with the same value
+            AST::Float( value ) => match ty {
+                UnificationType::Known(Type::Float { size: 32 }) => format!("0x{:016X}", (*value as f32 as f64).to_bits()),
+                _ => format!("0x{:016X}", value.to_bits()),
+            },
+            AST::Bool( value ) => value.to_string(),
+            AST::String( value ) => {
+                self.strings.push(value.clone());
+                format!("@.str.{}", self.strings.len() - 1)
+            }
+            AST::Ident( name, _ ) => match self.lookup(name) {
+                Some((slot, ty)) => {
+                    let register = self.register();
+                    self.instruction(&format!("{} = load {}, ptr {}", register, Self::llvm_type(&ty), slot));
+                    register
+                }
+                None => format!("@{}", self.symbols[name]),
+            },
+            AST::Call( callee, _ ) if matches!(callee.get_kind(), AST::Ident( name, _ ) if name == BUILTIN_ASSERT) => {
+                report!(self, Error::Unsupported("'assert' calls".to_string(), "the LLVM backend".to_string()), node, ErrorInfo {
+                    help: Some("Use 'snowball run' or 'snowball test' to interpret the program instead.".to_string()),
+                    ..Default::default()
+                });
+            }
+            AST::Call( callee, args ) => {
+                let callee = self.emit_expr(callee)?;
+                let mut values = Vec::new();
+                for arg in args {
+                    let value = self.emit_expr(arg)?;
+                    values.push(format!("{} {}", Self::llvm_type(arg.get_type()), value));
+                }
+                let ret = Self::llvm_type(ty);
+                if ret == "void" {
+                    self.instruction(&format!("call void {}({})", callee, values.join(", ")));
+                    String::new()
+                } else {
+                    let register = self.register();
+                    self.instruction(&format!("{} = call {} {}({})", register, ret, callee, values.join(", ")));
+                    register
+                }
+            }
+            AST::Assign( target, value, op ) => {
+                let mut value = self.emit_expr(value)?;
+                let AST::Ident( name, _ ) = target.get_kind() else {
+                    report!(self, Error::Unsupported("assignments like this one".to_string(), "the LLVM backend".to_string()), target);
+                };
+                if let Some(op) = op {
+                    let current = self.emit_expr(target)?;
+                    value = self.emit_binary_values(node, op, target.get_type().clone(), current, value)?;
+                }
+                let (slot, ty) = self.lookup(name).unwrap();
+                self.instruction(&format!("store {} {}, ptr {}", Self::llvm_type(&ty), value, slot));
+                value
+            }
+            AST::BinaryOp( op, lhs, rhs, is_unary ) => self.emit_binary_op(node, op, lhs, rhs, *is_unary)?,
+            _ => report!(self, Error::Unsupported("expressions like this one".to_string(), "the LLVM backend".to_string()), node),
+        })
+    }
+
+    fn emit_binary_op(&mut self, node: &TypedNode, op: &BinaryOp, lhs: &TypedNode, rhs: &TypedNode, is_unary: bool) -> Result<String, ()> {
+        let operand_ty = lhs.get_type().clone();
+        let ty = Self::llvm_type(&operand_ty);
+        if let BinaryOp::And | BinaryOp::Or = op {
+            return self.emit_short_circuit(op, lhs, rhs);
+        }
+        let a = self.emit_expr(lhs)?;
+        if is_unary {
+            let register = self.register();
+            let instruction = match op {
+                BinaryOp::Sub if Self::is_float(&operand_ty) => format!("fneg {} {}", ty, a),
+                BinaryOp::Sub => format!("sub {} 0, {}", ty, a),
+                BinaryOp::BitNot => format!("xor {} {}, -1", ty, a),
+                _ => format!("xor i1 {}, true", a),
+            };
+            self.instruction(&format!("{} = {}", register, instruction));
+            return Ok(register);
+        }
+        let b = self.emit_expr(rhs)?;
+        self.emit_binary_values(node, op, operand_ty, a, b)
+    }
+
+    /// Applies an operator to already emitted operands, `operand_ty` is the type of the left one.
+    fn emit_binary_values(&mut self, node: &TypedNode, op: &BinaryOp, operand_ty: UnificationType, a: String, b: String) -> Result<String, ()> {
+        let ty = Self::llvm_type(&operand_ty);
+        let register = self.register();
+        if let UnificationType::Known(Type::String) = operand_ty {
+            self.uses_runtime = true;
+            return Ok(match op {
+                BinaryOp::Add => {
+                    self.instruction(&format!("{} = call ptr @sn.concat(ptr {}, ptr {})", register, a, b));
+                    register
+                }
+                _ => {
+                    self.instruction(&format!("{} = call i32 @strcmp(ptr {}, ptr {})", register, a, b));
+                    let result = self.register();
+                    let predicate = if let BinaryOp::Eq = op { "eq" } else { "ne" };
+                    self.instruction(&format!("{} = icmp {} i32 {}, 0", result, predicate, register));
+                    result
+                }
+            });
+        }
+        let float = Self::is_float(&operand_ty);
+        let signed = Self::is_signed(&operand_ty);
+        let instruction = match op {
+            BinaryOp::Add => if float { "fadd" } else { "add" },
+            BinaryOp::Sub => if float { "fsub" } else { "sub" },
+            BinaryOp::Mul => if float { "fmul" } else { "mul" },
+            BinaryOp::Div => if float { "fdiv" } else if signed { "sdiv" } else { "udiv" },
+            BinaryOp::Mod => if float { "frem" } else if signed { "srem" } else { "urem" },
+            BinaryOp::Eq => if float { "fcmp oeq" } else { "icmp eq" },
+            BinaryOp::Ne => if float { "fcmp une" } else { "icmp ne" },
+            BinaryOp::Lt => if float { "fcmp olt" } else if signed { "icmp slt" } else { "icmp ult" },
+            BinaryOp::Le => if float { "fcmp ole" } else if signed { "icmp sle" } else { "icmp ule" },
+            BinaryOp::Gt => if float { "fcmp ogt" } else if signed { "icmp sgt" } else { "icmp ugt" },
+            BinaryOp::Ge => if float { "fcmp oge" } else if signed { "icmp sge" } else { "icmp uge" },
+            _ => report!(self, Error::Unsupported(format!("'{}' operations", op), "the LLVM backend".to_string()), node),
+        };
+        self.instruction(&format!("{} = {} {} {}, {}", register, instruction, ty, a, b));
+        Ok(register)
+    }
+
+    /// `&&` and `||` only evaluate their right hand side when it can change the result.
+    fn emit_short_circuit(&mut self, op: &BinaryOp, lhs: &TypedNode, rhs: &TypedNode) -> Result<String, ()> {
+        let a = self.emit_expr(lhs)?;
+        let lhs_block = self.current_block();
+        let rhs_label = self.label("logic.rhs");
+        let end = self.label("logic.end");
+        let (short_value, branch) = match op {
+            BinaryOp::And => ("false", format!("br i1 {}, label %{}, label %{}", a, rhs_label, end)),
+            _ => ("true", format!("br i1 {}, label %{}, label %{}", a, end, rhs_label)),
+        };
+        self.terminator(&branch);
+        self.start_block(&rhs_label);
+        let b = self.emit_expr(rhs)?;
+        let rhs_block = self.current_block();
+        self.terminator(&format!("br label %{}", end));
+        self.start_block(&end);
+        let register = self.register();
+        self.instruction(&format!("{} = phi i1 [ {}, %{} ], [ {}, %{} ]", register, short_value, lhs_block, b, rhs_block));
+        Ok(register)
+    }
+
+    /// The label of the basic block instructions are currently emitted into.
+    fn current_block(&self) -> String {
+        self.body.lines().rev().find(|l| !l.starts_with(' ')).unwrap().trim_end_matches(':').to_string()
+    }
+
+    fn escape(value: &str) -> String {
+        let mut result = String::new();
+        for byte in value.bytes() {
+            match byte {
+                b'"' | b'\\' => result.push_str(&format!("\\{:02X}", byte)),
+                0x20..=0x7e => result.push(byte as char),
+                _ => result.push_str(&format!("\\{:02X}", byte)),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+
+    /// Runs the IR with `lli` if it is installed. LLVM 14 only reads opaque
+    /// pointers behind a flag. Returns the exit code of the program.
+    fn run_ir(ir: &str) -> Option<i32> {
+        let path = std::env::temp_dir().join(format!("snowball-llvm-{}.ll", std::process::id()));
+        std::fs::write(&path, ir).unwrap();
+        let mut code = None;
+        for flags in [&[][..], &["-opaque-pointers"][..]] {
+            let Ok(status) = std::process::Command::new("lli").args(flags).arg(&path).stderr(std::process::Stdio::null()).status() else {
+                break;
+            };
+            code = status.code();
+            if status.success() {
+                break;
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+        code
+    }
+
+    #[test]
+    fn variables_in_loops_are_allocated_once() {
+        let (compiler, modules) = Compiler::compile_source("func main() i32 {\n    let i = 0;\n    while i < 100000000 { let x = i; i = x + 1; }\n    return i - 100000000;\n}\n");
+        let Ok(modules) = modules else {
+            panic!("the program doesn't compile");
+        };
+        let ir = LLVMBackend::new().generate(&modules, compiler.get_entry().as_ref()).unwrap();
+        let function = ir.split("define i32 @_SN4main4mainE").nth(1).unwrap();
+        let (entry, rest) = function.split_once("br label").unwrap();
+        assert_eq!(entry.matches("alloca").count(), 2);
+        assert!(!rest.contains("alloca"));
+        if let Some(code) = run_ir(&ir) {
+            assert_eq!(code, 0);
+        }
+    }
+}
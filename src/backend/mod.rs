@@ -0,0 +1,53 @@
+pub mod c;
+pub mod interpreter;
+pub mod llvm;
+
+use crate::ast::attrs::{AstAttrs, ExternalLinkage};
+use crate::ast::nodes::AST;
+use crate::ast::typed::TypedNode;
+use crate::frontend::module::Module;
+
+/// The global declarations of every module of the program.
+pub fn top_nodes(modules: &[Module<TypedNode>]) -> Vec<&TypedNode> {
+    modules.iter().flat_map(|module| {
+        let AST::TopLevel( nodes ) = module.get_top() else {
+            panic!("Expected TopLevel node");
+        };
+        nodes
+    }).collect()
+}
+
+/// Turns the path of a function into a symbol name that is a valid identifier
+/// in every backend (e.g. `app::math::max` becomes `_SN3app4math3maxE`).
+/// Characters that can't be part of an identifier are written as `_` followed
+/// by their hexadecimal code.
+pub fn mangle(path: &str) -> String {
+    let mut result = String::from("_SN");
+    for segment in path.split("::") {
+        let mut encoded = String::new();
+        for c in segment.chars() {
+            if c.is_ascii_alphanumeric() {
+                encoded.push(c);
+            } else {
+                encoded.push_str(&format!("_{:02x}", c as u32));
+            }
+        }
+        result.push_str(&format!("{}{}", encoded.len(), encoded));
+    }
+    result.push('E');
+    result
+}
+
+/// The name a function is emitted with. Functions implemented in C and
+/// the ones marked as `no_mangle` keep their name so they can be linked against.
+pub fn symbol_name(function: &TypedNode) -> String {
+    let AST::FuncDef( path, .. ) = function.get_kind() else {
+        panic!("Expected FuncDef");
+    };
+    let attrs = function.get_attrs().as_ref().map(|a| a.get_attrs().clone()).unwrap_or_default();
+    if attrs.contains(&AstAttrs::External(ExternalLinkage::C)) || attrs.contains(&AstAttrs::NoMangle) {
+        path.rsplit("::").next().unwrap().to_string()
+    } else {
+        mangle(path)
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::attrs::AstAttrs;
+use crate::ast::nodes::AST;
+use crate::ast::typed::TypedNode;
+use crate::backend::interpreter::{Interpreter, STACK_SIZE};
+use crate::frontend::module::{Module, NamespacePath};
+use crate::reports::format;
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+use crate::{bold, green, red, reset};
+
+use super::Compiler;
+
+/// A change of the mean time bigger than this (in percent) is highlighted
+/// when comparing against a baseline.
+const NOISE_THRESHOLD: f64 = 5.0;
+
+/// How often every benchmark is run and where its results are compared to.
+pub struct BenchOptions<'a> {
+    pub filter: Option<&'a str>,
+    /// Runs that aren't measured, e.g. to warm up caches.
+    pub warmup: usize,
+    pub iterations: usize,
+    /// A file written by `save_baseline` to compare the results against.
+    pub baseline: Option<&'a Path>,
+    pub save_baseline: Option<&'a Path>,
+}
+
+/// The timings of a benchmark in nanoseconds, as stored in a baseline file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Measurement {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub iterations: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Baseline {
+    benchmarks: BTreeMap<String, Measurement>,
+}
+
+impl Measurement {
+    fn from_samples(mut samples: Vec<f64>) -> Measurement {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) { (samples[middle - 1] + samples[middle]) / 2.0 } else { samples[middle] };
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+        Measurement { mean, median, stddev: variance.sqrt(), iterations: samples.len() }
+    }
+}
+
+impl Compiler {
+    /// Runs every function of the package marked with `@[bench]` whose path
+    /// contains the filter and prints how long it took. Returns whether all
+    /// of them ran without errors.
+    pub fn bench(&self, modules: &[Module<TypedNode>], options: &BenchOptions) -> bool {
+        let mut reports = Reports::new();
+        let mut benchmarks = Vec::new();
+        for node in self.marked_functions(modules, &AstAttrs::Benchmark) {
+            match Self::check_benchmark(node) {
+                Ok(name) => benchmarks.push(name),
+                Err((name, reason, help)) => reports.add_error(CompileError::new(Error::InvalidBenchmark(name, reason), node.get_location().unwrap().clone())
+                    .with_info(ErrorInfo { help: Some(help), ..Default::default() })),
+            }
+        }
+        if reports.handle_errors(&self.sources, self.message_format) {
+            return false;
+        }
+        let baseline = match options.baseline.map(Self::read_baseline).transpose() {
+            Ok(baseline) => baseline,
+            Err((error, help)) => {
+                self.emit_global_error(error, Some(help));
+                return false;
+            }
+        };
+        let total = benchmarks.len();
+        benchmarks.retain(|name| options.filter.is_none_or(|filter| name.contains(filter)));
+        println!("\nrunning {} benchmark{}", benchmarks.len(), if benchmarks.len() == 1 { "" } else { "s" });
+        let mut results = Baseline::default();
+        let mut failed = false;
+        for name in &benchmarks {
+            match Self::run_benchmark(modules, name, options) {
+                Ok(measurement) => {
+                    let comparison = baseline.as_ref()
+                        .and_then(|b| b.benchmarks.get(name))
+                        .map(|old| Self::compare(old, &measurement))
+                        .unwrap_or_default();
+                    println!("bench {} ... mean {}, median {}, stddev {} ({} iterations){}", name, format_duration(measurement.mean),
+                        format_duration(measurement.median), format_duration(measurement.stddev), measurement.iterations, comparison);
+                    results.benchmarks.insert(name.clone(), measurement);
+                }
+                Err((reason, errors)) => {
+                    println!("bench {} ... {}FAILED{} ({})", name, red!(), reset!(), reason);
+                    format::emit(self.message_format, &self.sources, &errors, &[]);
+                    failed = true;
+                }
+            }
+        }
+        println!("\nbench result: {} measured; {} filtered out\n", results.benchmarks.len(), total - benchmarks.len());
+        if let Some(path) = options.save_baseline {
+            let json = serde_json::to_string_pretty(&results).unwrap();
+            if let Err(error) = fs::write(path, json) {
+                self.emit_global_error(Error::InvalidBaseline(path.display().to_string(), error.to_string()), None);
+                return false;
+            }
+            println!("{}{}Saved{} baseline to '{}'", bold!(), green!(), reset!(), path.display());
+        }
+        !failed
+    }
+
+    /// Benchmarks are called without arguments, their result is ignored.
+    fn check_benchmark(node: &TypedNode) -> Result<String, (String, String, String)> {
+        let AST::FuncDef( name, params, _, body, generics, _ ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        if body.is_none() {
+            return Err((name.clone(), "has no body".to_string(), "Only functions defined in the package can be benchmarks.".to_string()));
+        }
+        if generics.as_ref().is_some_and(|g| !g.is_empty()) {
+            return Err((name.clone(), "cannot be generic".to_string(), "Call the generic function from a benchmark with concrete types instead.".to_string()));
+        }
+        if !params.is_empty() {
+            return Err((name.clone(), "cannot take parameters".to_string(), "Benchmarks are called without arguments.".to_string()));
+        }
+        Ok(name.clone())
+    }
+
+    /// Runs a benchmark on its own thread with a big stack. Every run gets
+    /// a fresh interpreter, creating it isn't part of the measured time.
+    /// Fails with the reason and the errors reported while running it.
+    fn run_benchmark(modules: &[Module<TypedNode>], name: &str, options: &BenchOptions) -> Result<Measurement, (String, Vec<CompileError>)> {
+        let result = std::thread::scope(|scope| {
+            std::thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, || {
+                    let mut samples = Vec::with_capacity(options.iterations);
+                    for iteration in 0..options.warmup + options.iterations {
+                        let mut interpreter = Interpreter::new(modules, &NamespacePath::new(Vec::new()));
+                        let start = Instant::now();
+                        let result = interpreter.run_function(name);
+                        let elapsed = start.elapsed().as_nanos() as f64;
+                        if result.is_err() && interpreter.get_exit_code() != Some(0) {
+                            let reason = match interpreter.get_exit_code() {
+                                Some(code) => format!("exited with {}", code),
+                                None => "failed while running".to_string(),
+                            };
+                            return Err((reason, interpreter.get_reports().get_errors().clone()));
+                        }
+                        if iteration >= options.warmup {
+                            samples.push(elapsed);
+                        }
+                    }
+                    Ok(Measurement::from_samples(samples))
+                })
+                .unwrap()
+                .join()
+        });
+        result.unwrap_or_else(|_| Err(("the interpreter panicked".to_string(), Vec::new())))
+    }
+
+    fn read_baseline(path: &Path) -> Result<Baseline, (Error, String)> {
+        let invalid = |reason: String| (Error::InvalidBaseline(path.display().to_string(), reason), "Baselines are written with 'snowball bench --save-baseline <file>'.".to_string());
+        let content = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// How the mean time changed since the baseline, slower benchmarks are red.
+    fn compare(old: &Measurement, new: &Measurement) -> String {
+        let change = (new.mean - old.mean) / old.mean * 100.0;
+        let color = if change > NOISE_THRESHOLD {
+            red!()
+        } else if change < -NOISE_THRESHOLD {
+            green!()
+        } else {
+            ""
+        };
+        let reset = if color.is_empty() { "" } else { reset!() };
+        format!(" {}{:+.2}%{} vs baseline", color, change, reset)
+    }
+}
+
+/// Formats nanoseconds with the largest unit that keeps the number above 1.
+fn format_duration(nanos: f64) -> String {
+    match nanos {
+        n if n >= 1e9 => format!("{:.3} s", n / 1e9),
+        n if n >= 1e6 => format!("{:.3} ms", n / 1e6),
+        n if n >= 1e3 => format!("{:.3} µs", n / 1e3),
+        n => format!("{:.0} ns", n),
+    }
+}
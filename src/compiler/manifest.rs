@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::frontend::module::NamespacePath;
+use crate::reports::Error;
+
+pub const MANIFEST_FILE: &str = "snowball.toml";
+
+const MAIN_TEMPLATE: &str = "external \"C\" func puts(s: String) i32;
+
+func main() i32 {
+    puts(\"Hello, world!\");
+    return 0;
+}
+";
+
+/// What building a package produces.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputType {
+    #[default]
+    Executable,
+    Library,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    name: String,
+    /// Required in every manifest, but nothing depends on it yet.
+    #[allow(dead_code)]
+    version: String,
+    /// The module whose `main` function starts the program.
+    #[serde(default = "Package::default_entry")]
+    entry: String,
+    /// The directory the modules live in, relative to the manifest.
+    #[serde(default = "Package::default_src")]
+    src: String,
+    #[serde(default, rename = "type")]
+    output: OutputType,
+}
+
+impl Package {
+    fn default_entry() -> String {
+        "main".to_string()
+    }
+
+    fn default_src() -> String {
+        "src".to_string()
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_output(&self) -> &OutputType {
+        &self.output
+    }
+}
+
+/// A package on the local file system (`name = { path = "../name" }`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    path: String,
+}
+
+/// The contents of a `snowball.toml` file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    package: Package,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+    /// Optional parts of the package, each enabling the features it lists.
+    /// `default` is enabled unless asked otherwise.
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    /// The directory the manifest was loaded from.
+    #[serde(skip)]
+    root: PathBuf,
+    /// Set when compiling a single file without a manifest.
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// Loads the manifest of the project at `path`. Directories without a
+    /// manifest and single files are compiled as a package of their own.
+    pub fn from_path(path: &str) -> Result<Manifest, (Error, String)> {
+        let input = Path::new(path);
+        if input.is_file() {
+            return Ok(Self::for_file(input));
+        }
+        if input.join(MANIFEST_FILE).is_file() {
+            return Self::load(input);
+        }
+        let name = Self::directory_name(input);
+        Ok(Manifest {
+            package: Package { name, version: "0.0.0".to_string(), entry: Package::default_entry(), src: ".".to_string(), output: OutputType::Executable },
+            dependencies: BTreeMap::new(),
+            features: BTreeMap::new(),
+            root: input.to_path_buf(),
+            file: None,
+        })
+    }
+
+    /// Reads and validates the `snowball.toml` file inside of `dir`.
+    pub fn load(dir: &Path) -> Result<Manifest, (Error, String)> {
+        let path = dir.join(MANIFEST_FILE);
+        let invalid = |help: String| (Error::InvalidManifest(path.display().to_string()), help);
+        let content = fs::read_to_string(&path).map_err(|error| invalid(error.to_string()))?;
+        let mut manifest: Manifest = toml::from_str(&content).map_err(|error| invalid(match error.span() {
+            Some(span) => format!("Line {}: {}", content[..span.start].matches('\n').count() + 1, error.message()),
+            None => error.message().to_string(),
+        }))?;
+        manifest.root = dir.to_path_buf();
+        if !Self::is_valid_name(&manifest.package.name) {
+            return Err(invalid(format!("'{}' is not a valid package name, names can only contain letters, digits and '_'.", manifest.package.name)));
+        }
+        if let Some(name) = manifest.dependencies.keys().find(|name| !Self::is_valid_name(name)) {
+            return Err(invalid(format!("'{}' is not a valid dependency name, names can only contain letters, digits and '_'.", name)));
+        }
+        for (feature, enables) in &manifest.features {
+            if !Self::is_valid_name(feature) {
+                return Err(invalid(format!("'{}' is not a valid feature name, names can only contain letters, digits and '_'.", feature)));
+            }
+            if let Some(missing) = enables.iter().find(|f| !manifest.features.contains_key(*f)) {
+                return Err(invalid(format!("The feature '{}' enables '{}', which isn't declared in [features].", feature, missing)));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Finds the manifest of the package a file belongs to by looking at the
+    /// directories above it. Files outside of a package are a package of their own.
+    pub fn for_source_file(file: &Path) -> Result<Manifest, (Error, String)> {
+        for dir in file.ancestors().skip(1) {
+            if dir.join(MANIFEST_FILE).is_file() {
+                return Self::load(dir);
+            }
+        }
+        Ok(Self::for_file(file))
+    }
+
+    fn for_file(file: &Path) -> Manifest {
+        let root = file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let name = file.file_stem().unwrap().to_string_lossy().to_string();
+        Manifest {
+            package: Package { name: name.clone(), version: "0.0.0".to_string(), entry: name, src: ".".to_string(), output: OutputType::Executable },
+            dependencies: BTreeMap::new(),
+            features: BTreeMap::new(),
+            root: root.to_path_buf(),
+            file: Some(file.to_path_buf()),
+        }
+    }
+
+    /// The manifest written by `snowball new` and `snowball init`.
+    pub fn template(name: &str) -> String {
+        format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nentry = \"main\"\nsrc = \"src\"\ntype = \"executable\"\n\n[dependencies]\n", name)
+    }
+
+    /// Writes the files of a new package into `dir`. Existing sources are kept.
+    pub fn create_project(dir: &Path, name: &str) -> std::io::Result<()> {
+        fs::create_dir_all(dir.join("src"))?;
+        fs::write(dir.join(MANIFEST_FILE), Self::template(name))?;
+        let main = dir.join("src").join("main.sn");
+        if !main.exists() {
+            fs::write(main, MAIN_TEMPLATE)?;
+        }
+        let gitignore = dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(gitignore, "/build\n")?;
+        }
+        Ok(())
+    }
+
+    pub fn is_valid_name(name: &str) -> bool {
+        name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_') && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+
+    /// The name of a directory, used as the package name when there is no manifest.
+    pub fn directory_name(dir: &Path) -> String {
+        let dir = fs::canonicalize(dir).unwrap_or(dir.to_path_buf());
+        dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or("main".to_string())
+    }
+
+    pub fn get_package(&self) -> &Package {
+        &self.package
+    }
+
+    pub fn get_root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    pub fn get_file(&self) -> Option<&PathBuf> {
+        self.file.as_ref()
+    }
+
+    pub fn get_entry(&self) -> NamespacePath {
+        NamespacePath::new(self.package.entry.split("::").map(|s| s.to_string()).collect())
+    }
+
+    /// The directory the modules of the package live in.
+    pub fn get_src_dir(&self) -> PathBuf {
+        self.root.join(&self.package.src)
+    }
+
+    /// Where the build outputs are written. Single files are built next to themselves.
+    pub fn get_output_dir(&self) -> PathBuf {
+        match self.file {
+            Some(_) => self.root.clone(),
+            None => self.root.join("build"),
+        }
+    }
+
+    /// The features to compile the package with: the requested ones, `default`
+    /// unless it is turned off, and every feature they enable in turn.
+    pub fn resolve_features(&self, requested: &[String], default_features: bool) -> Result<BTreeSet<String>, (Error, String)> {
+        let mut pending: Vec<String> = requested.to_vec();
+        if default_features && self.features.contains_key("default") {
+            pending.push("default".to_string());
+        }
+        let mut features = BTreeSet::new();
+        while let Some(feature) = pending.pop() {
+            let Some(enables) = self.features.get(&feature) else {
+                return Err((Error::UnknownFeature(feature, self.package.name.clone()),
+                    format!("Features are declared in the [features] table of '{}'.", self.root.join(MANIFEST_FILE).display())));
+            };
+            if features.insert(feature) {
+                pending.extend(enables.iter().cloned());
+            }
+        }
+        Ok(features)
+    }
+
+    /// Loads the manifests of the dependencies, paths are relative to this manifest.
+    pub fn get_dependencies(&self) -> Result<Vec<(String, Manifest)>, (Error, String)> {
+        let mut dependencies = Vec::new();
+        for (name, dependency) in &self.dependencies {
+            let dir = self.root.join(&dependency.path);
+            if !dir.join(MANIFEST_FILE).is_file() {
+                return Err((Error::InvalidManifest(self.root.join(MANIFEST_FILE).display().to_string()),
+                    format!("The dependency '{}' points to '{}', which has no '{}' file.", name, dir.display(), MANIFEST_FILE)));
+            }
+            dependencies.push((name.clone(), Self::load(&dir)?));
+        }
+        Ok(dependencies)
+    }
+}
@@ -1,34 +1,375 @@
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-use crate::frontend::module::NamespacePath;
+use crate::ast::attrs::AstAttrs;
+use crate::ast::nodes::AST;
+use crate::ast::source::SourceMap;
+use crate::ast::typed::TypedNode;
+use crate::backend::top_nodes;
+use crate::frontend::cfg::CfgSet;
+use crate::frontend::module::{Module, NamespacePath};
+use crate::frontend::monomorphize::Monomorphizer;
+use crate::frontend::type_checker::Typechecker;
+use crate::reports::format::{self, MessageFormat};
+use crate::reports::{CompileError, Error, ErrorType, Reports};
+
+pub mod benchmarking;
+pub mod manifest;
+pub mod testing;
+
+use manifest::{Manifest, OutputType};
+
+/// Everything the frontend found out about a package.
+pub struct Analysis {
+    /// Errors that don't belong to a location (e.g. an invalid manifest).
+    pub global_errors: Vec<(Error, Option<String>)>,
+    pub errors: Vec<CompileError>,
+    /// The contents of every file that was compiled.
+    pub sources: SourceMap,
+    /// The parsed modules, only type checked if all of them parsed.
+    pub modules: Vec<Module>,
+    pub typed_modules: Vec<Module<TypedNode>>,
+    pub typechecker: Option<Typechecker>,
+}
 
 pub struct Compiler {
-    path: String,
+    manifest: Manifest,
+    /// The sources of the last run, used to render the diagnostics of the backends.
+    sources: SourceMap,
+    message_format: MessageFormat,
+    /// The options `@[cfg(..)]` predicates are evaluated against, without features.
+    cfg: CfgSet,
+    /// The features of the package that were asked for.
+    features: Vec<String>,
+    default_features: bool,
 }
 
 impl Compiler {
-    pub fn new(path: String) -> Compiler {
-        Compiler { path }
+    pub fn new(manifest: Manifest) -> Compiler {
+        // editors see the package like a debug build sees it
+        let mut cfg = CfgSet::for_target();
+        cfg.enable("debug");
+        Compiler { manifest, sources: SourceMap::new(), message_format: MessageFormat::Human, cfg, features: Vec::new(), default_features: true }
     }
 
-    pub fn run(&self) {
-        let source = fs::read_to_string(self.path.clone()).expect("Something went wrong reading the file");
+    pub fn with_message_format(mut self, message_format: MessageFormat) -> Compiler {
+        self.message_format = message_format;
+        self
+    }
 
-        // TODO: Iterate through the folder but for now, we just get the file
-        let mut lexer = crate::frontend::lexer::Lexer::new(source, self.path.clone());
-        lexer.lex();
-        if lexer.get_reports().handle_errors() {
-            return;
+    pub fn with_cfg(mut self, cfg: CfgSet) -> Compiler {
+        self.cfg = cfg;
+        self
+    }
+
+    pub fn with_features(mut self, features: Vec<String>, default_features: bool) -> Compiler {
+        self.features = features;
+        self.default_features = default_features;
+        self
+    }
+
+    pub fn get_manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn get_sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    pub fn get_message_format(&self) -> MessageFormat {
+        self.message_format
+    }
+
+    /// The module whose `main` function starts the program, libraries have none.
+    pub fn get_entry(&self) -> Option<NamespacePath> {
+        match self.manifest.get_package().get_output() {
+            OutputType::Executable => Some(self.manifest.get_entry()),
+            OutputType::Library => None,
         }
-        let mut parser = crate::frontend::parser::Parser::new(NamespacePath::from_path(self.path.clone()), self.path.clone(), &lexer);
-        let result = &parser.parse();
-        if parser.get_reports().handle_errors() {
-            return;
+    }
+
+    /// Compiles every module of the package and its dependencies and returns
+    /// the type checked modules, or `None` if any error was reported.
+    pub fn run(&mut self) -> Option<Vec<Module<TypedNode>>> {
+        let analysis = self.analyze(&HashMap::new());
+        format::emit(self.message_format, &analysis.sources, &analysis.errors, &analysis.global_errors);
+        let failed = !analysis.global_errors.is_empty() || analysis.errors.iter().any(|e| *e.get_error_type() == ErrorType::Error);
+        self.sources = analysis.sources;
+        if failed {
+            return None;
+        }
+        let mut monomorphizer = Monomorphizer::new();
+        let Ok(modules) = monomorphizer.monomorphize(analysis.typed_modules) else {
+            monomorphizer.get_reports().handle_errors(&self.sources, self.message_format);
+            return None;
+        };
+        Some(modules)
+    }
+
+    /// Runs the frontend over the package without printing anything.
+    /// `overlay` holds the contents of files that have not been saved yet.
+    pub fn analyze(&self, overlay: &HashMap<PathBuf, String>) -> Analysis {
+        let mut analysis = Analysis { global_errors: Vec::new(), errors: Vec::new(), sources: SourceMap::new(), modules: Vec::new(), typed_modules: Vec::new(), typechecker: None };
+        let mut sources = Vec::new();
+        match self.manifest.get_file() {
+            Some(file) => match self.package_cfg(&self.manifest, true) {
+                Ok(cfg) => sources.push((NamespacePath::new(vec![self.manifest.get_package().get_name().clone()]), file.clone(), cfg)),
+                Err((error, help)) => {
+                    analysis.global_errors.push((error, Some(help)));
+                    return analysis;
+                }
+            },
+            None => if let Err((error, help)) = self.collect_sources(&self.manifest, NamespacePath::new(Vec::new()), overlay, &mut Vec::new(), &mut sources) {
+                analysis.global_errors.push((error, Some(help)));
+                return analysis;
+            },
+        }
+        if sources.is_empty() {
+            analysis.global_errors.push((Error::NoSourceFiles(self.manifest.get_src_dir().display().to_string()), Some("Snowball source files end with '.sn'.".to_string())));
+            return analysis;
+        }
+        let mut failed = false;
+        // every file is parsed even if one fails so all syntax errors get reported at once
+        for (path, file, cfg) in sources {
+            let file_path = file.to_string_lossy().to_string();
+            if let Some(other) = analysis.modules.iter().find(|m| *m.get_path() == path) {
+                analysis.global_errors.push((Error::ConflictingModules(path.display()),
+                    Some(format!("Both '{}' and '{}' declare this module.", other.get_file_name().clone().unwrap_or_default(), file_path))));
+                failed = true;
+                continue;
+            }
+            let source = match overlay.get(&file) {
+                Some(source) => source.clone(),
+                None => match fs::read_to_string(&file) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        analysis.global_errors.push((Error::FileAccess("read".to_string(), file_path), Some(error.to_string())));
+                        failed = true;
+                        continue;
+                    }
+                },
+            };
+            let file_id = analysis.sources.add(file_path.clone(), source);
+            let mut lexer = crate::frontend::lexer::Lexer::new(analysis.sources.get(file_id).unwrap().get_content(), file_id);
+            lexer.lex();
+            analysis.errors.extend(lexer.get_reports().get_errors().iter().cloned());
+            if Self::has_errors(lexer.get_reports()) {
+                failed = true;
+                continue;
+            }
+            let mut parser = crate::frontend::parser::Parser::new(path, file_path, &lexer);
+            let result = parser.parse();
+            analysis.errors.extend(parser.get_reports().get_errors().iter().cloned());
+            if Self::has_errors(parser.get_reports()) {
+                failed = true;
+                continue;
+            }
+            let mut module = result.unwrap();
+            let mut reports = Reports::new();
+            cfg.strip(&mut module, &mut reports);
+            analysis.errors.extend(reports.get_errors().iter().cloned());
+            if Self::has_errors(&reports) {
+                failed = true;
+                continue;
+            }
+            analysis.modules.push(module);
+        }
+        if failed {
+            return analysis;
         }
         let mut typechecker = crate::frontend::type_checker::Typechecker::new();
-        let typed_module = typechecker.typecheck(&mut (result.clone().unwrap()));
-        if typechecker.get_reports().handle_errors() {
+        let mut modules = analysis.modules.clone();
+        analysis.typed_modules = typechecker.typecheck(&mut modules);
+        analysis.errors.extend(typechecker.get_reports().get_errors().iter().cloned());
+        analysis.typechecker = Some(typechecker);
+        analysis
+    }
+
+    /// Whether the reports contain errors, warnings don't stop the compilation.
+    fn has_errors(reports: &Reports) -> bool {
+        reports.get_errors().iter().any(|e| *e.get_error_type() == ErrorType::Error)
+    }
+
+    /// Finds the source files of a package and of its dependencies. The
+    /// modules of a dependency are prefixed with its name (e.g. `json::parser`).
+    fn collect_sources(&self, manifest: &Manifest, prefix: NamespacePath, overlay: &HashMap<PathBuf, String>,
+                       visited: &mut Vec<PathBuf>, sources: &mut Vec<(NamespacePath, PathBuf, CfgSet)>) -> Result<(), (Error, String)> {
+        let root = fs::canonicalize(manifest.get_root()).unwrap_or(manifest.get_root().clone());
+        if visited.contains(&root) {
+            return Ok(());
+        }
+        visited.push(root);
+        let cfg = self.package_cfg(manifest, visited.len() == 1)?;
+        let src = manifest.get_src_dir();
+        let mut files = Vec::new();
+        Self::discover_files(&src, &manifest.get_output_dir(), &mut files);
+        // files that only exist in an editor are part of the package too
+        for file in overlay.keys() {
+            if file.starts_with(&src) && file.extension().is_some_and(|e| e == "sn") && !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(&src).unwrap_or(&file).to_string_lossy().to_string();
+            let mut path = prefix.clone();
+            path.push_path(NamespacePath::from_path(relative));
+            sources.push((path, file, cfg.clone()));
+        }
+        for (name, dependency) in manifest.get_dependencies()? {
+            let mut path = prefix.clone();
+            path.push(name);
+            self.collect_sources(&dependency, path, overlay, visited, sources)?;
+        }
+        Ok(())
+    }
+
+    /// The options a package is compiled with. Dependencies are compiled with
+    /// their default features, the features asked for belong to the package itself.
+    fn package_cfg(&self, manifest: &Manifest, is_root: bool) -> Result<CfgSet, (Error, String)> {
+        let features = match is_root {
+            true => manifest.resolve_features(&self.features, self.default_features)?,
+            false => manifest.resolve_features(&[], true)?,
+        };
+        let mut cfg = self.cfg.clone();
+        for feature in features {
+            cfg.set("feature", &feature);
+        }
+        Ok(cfg)
+    }
+
+    /// Collects the `.sn` files inside a directory and its subdirectories,
+    /// skipping hidden ones and the `output` directory of the package.
+    fn discover_files(dir: &Path, output: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
             return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                // the outputs of previous builds are not part of the sources
+                if path.join(manifest::MANIFEST_FILE).is_file() || path == output {
+                    continue;
+                }
+                Self::discover_files(&path, output, files);
+            } else if path.extension().is_some_and(|e| e == "sn") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// The functions of the package itself (not of its dependencies) that
+    /// are marked with the given attribute, e.g. tests.
+    fn marked_functions<'a>(&self, modules: &'a [Module<TypedNode>], attr: &AstAttrs) -> Vec<&'a TypedNode> {
+        let src = self.manifest.get_src_dir();
+        modules.iter()
+            .filter(|m| self.manifest.get_file().is_some() || m.get_file_name().as_ref().is_some_and(|file| Path::new(file).starts_with(&src)))
+            .flat_map(|m| top_nodes(std::slice::from_ref(m)))
+            .filter(|n| matches!(n.get_kind(), AST::FuncDef( .. )) && n.get_attrs().as_ref().is_some_and(|a| a.get_attrs().contains(attr)))
+            .collect()
+    }
+
+    pub fn emit_global_error(&self, error: Error, help: Option<String>) {
+        format::emit(self.message_format, &self.sources, &[], &[(error, help)]);
+    }
+
+    /// Where the outputs of a build are written, named after the package.
+    /// Fails if the output directory can't be created.
+    fn output_path(&self, extension: &str) -> Result<PathBuf, ()> {
+        let dir = self.manifest.get_output_dir();
+        if let Err(error) = fs::create_dir_all(&dir) {
+            self.emit_global_error(Error::FileAccess("create the output directory".to_string(), dir.display().to_string()), Some(error.to_string()));
+            return Err(());
+        }
+        Ok(dir.join(self.manifest.get_package().get_name()).with_extension(extension))
+    }
+
+    /// Lowers the type checked modules to C, writes it to the output directory
+    /// and compiles it with the system C compiler. Executables are linked,
+    /// libraries are left as an object file. Returns the path of the output.
+    pub fn build(&self, modules: &[Module<TypedNode>]) -> Option<PathBuf> {
+        let mut backend = crate::backend::c::CBackend::new();
+        let Ok(source) = backend.generate(modules, self.get_entry().as_ref()) else {
+            backend.get_reports().handle_errors(&self.sources, self.message_format);
+            return None;
+        };
+        let is_library = *self.manifest.get_package().get_output() == OutputType::Library;
+        let Ok(c_path) = self.output_path("c") else {
+            return None;
+        };
+        let output = c_path.with_extension(if is_library { "o" } else { "" });
+        if let Err(error) = fs::write(&c_path, source) {
+            self.emit_global_error(Error::FileAccess("write".to_string(), c_path.display().to_string()), Some(error.to_string()));
+            return None;
+        }
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let mut command = Command::new(&cc);
+        command.arg("-std=c99")
+            .arg("-O2")
+            // integer overflow wraps around like it does in the interpreter
+            .arg("-fwrapv")
+            .arg("-o").arg(&output)
+            .arg(&c_path);
+        if is_library {
+            command.arg("-c");
+        } else {
+            command.arg("-lm");
+        }
+        let status = command.status();
+        match status {
+            Ok(status) if status.success() => Some(output),
+            Ok(status) => {
+                self.emit_global_error(Error::CCompilerFailed(format!("'{}' exited with {}", cc, status)), None);
+                None
+            }
+            Err(error) => {
+                self.emit_global_error(Error::CCompilerFailed(format!("could not run '{}': {}", cc, error)), Some("Set the 'CC' environment variable to the C compiler to use.".to_string()));
+                None
+            }
+        }
+    }
+
+    /// Writes the modules as textual LLVM IR to the output directory and
+    /// returns the path of the `.ll` file.
+    pub fn emit_llvm_ir(&self, modules: &[Module<TypedNode>]) -> Option<PathBuf> {
+        let mut backend = crate::backend::llvm::LLVMBackend::new();
+        let Ok(ir) = backend.generate(modules, self.get_entry().as_ref()) else {
+            backend.get_reports().handle_errors(&self.sources, self.message_format);
+            return None;
+        };
+        let Ok(ll_path) = self.output_path("ll") else {
+            return None;
+        };
+        if let Err(error) = fs::write(&ll_path, ir) {
+            self.emit_global_error(Error::FileAccess("write".to_string(), ll_path.display().to_string()), Some(error.to_string()));
+            return None;
+        }
+        Some(ll_path)
+    }
+}
+
+#[cfg(test)]
+impl Compiler {
+    /// Compiles `source` as the only file of a package, without touching the
+    /// disk. Returns the modules ready for a backend or the errors reported.
+    pub fn compile_source(source: &str) -> (Compiler, Result<Vec<Module<TypedNode>>, Vec<CompileError>>) {
+        let file = std::env::temp_dir().join("snowball-tests").join("main.sn");
+        let Ok(manifest) = Manifest::for_source_file(&file) else {
+            panic!("no manifest for {}", file.display());
+        };
+        let mut compiler = Compiler::new(manifest);
+        let analysis = compiler.analyze(&HashMap::from([(file, source.to_string())]));
+        compiler.sources = analysis.sources;
+        if !analysis.global_errors.is_empty() || !analysis.errors.is_empty() {
+            return (compiler, Err(analysis.errors));
         }
+        let mut monomorphizer = Monomorphizer::new();
+        let result = monomorphizer.monomorphize(analysis.typed_modules).map_err(|_| monomorphizer.get_reports().get_errors().clone());
+        (compiler, result)
     }
 }
@@ -0,0 +1,133 @@
+use crate::ast::attrs::AstAttrs;
+use crate::ast::nodes::AST;
+use crate::ast::typed::TypedNode;
+use crate::backend::interpreter::{Interpreter, Value, STACK_SIZE};
+use crate::frontend::module::{Module, NamespacePath};
+use crate::frontend::type_checker::{Type, UnificationType};
+use crate::reports::format;
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+use crate::{bold, green, red, reset};
+
+use super::Compiler;
+
+/// How a single test finished.
+enum Outcome {
+    Passed,
+    /// Why the test failed and the errors reported while running it.
+    Failed(String, Vec<CompileError>),
+}
+
+impl Compiler {
+    /// Runs every function of the package marked with `@[test]` whose path
+    /// contains `filter`. Every test gets a fresh interpreter so tests can't
+    /// see each other's state. Returns whether all of them passed.
+    pub fn test(&self, modules: &[Module<TypedNode>], filter: Option<&str>) -> bool {
+        let mut reports = Reports::new();
+        let mut tests = Vec::new();
+        for node in self.marked_functions(modules, &AstAttrs::Test) {
+            match Self::check_test(node) {
+                Ok(name) => tests.push(name),
+                Err((name, reason, help)) => reports.add_error(CompileError::new(Error::InvalidTest(name, reason), node.get_location().unwrap().clone())
+                    .with_info(ErrorInfo { help: Some(help), ..Default::default() })),
+            }
+        }
+        if reports.handle_errors(&self.sources, self.message_format) {
+            return false;
+        }
+        let total = tests.len();
+        tests.retain(|name| filter.is_none_or(|filter| name.contains(filter)));
+        println!("\nrunning {} test{}", tests.len(), if tests.len() == 1 { "" } else { "s" });
+        let mut failures = Vec::new();
+        for name in &tests {
+            match Self::run_test(modules, name) {
+                Outcome::Passed => println!("test {} ... {}ok{}", name, green!(), reset!()),
+                Outcome::Failed(reason, errors) => {
+                    println!("test {} ... {}FAILED{}", name, red!(), reset!());
+                    failures.push((name, reason, errors));
+                }
+            }
+        }
+        if !failures.is_empty() {
+            println!("\n{}failures:{}", bold!(), reset!());
+            for (name, reason, errors) in &failures {
+                println!("\n---- {} ----\n{}", name, reason);
+                format::emit(self.message_format, &self.sources, errors, &[]);
+            }
+        }
+        let result = if failures.is_empty() { format!("{}ok{}", green!(), reset!()) } else { format!("{}FAILED{}", red!(), reset!()) };
+        println!("\ntest result: {}. {} passed; {} failed; {} filtered out\n", result, tests.len() - failures.len(), failures.len(), total - tests.len());
+        failures.is_empty()
+    }
+
+    /// Makes sure a test can be called without arguments and its result can
+    /// be judged. Returns the path of the test or why it can't be run.
+    fn check_test(node: &TypedNode) -> Result<String, (String, String, String)> {
+        let AST::FuncDef( name, params, ret, body, generics, _ ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        if body.is_none() {
+            return Err((name.clone(), "has no body".to_string(), "Only functions defined in the package can be tests.".to_string()));
+        }
+        if generics.as_ref().is_some_and(|g| !g.is_empty()) {
+            return Err((name.clone(), "cannot be generic".to_string(), "Call the generic function from a test with concrete types instead.".to_string()));
+        }
+        if !params.is_empty() {
+            return Err((name.clone(), "cannot take parameters".to_string(), "Tests are called without arguments.".to_string()));
+        }
+        match ret {
+            UnificationType::Known(Type::Void | Type::Bool | Type::Integer { size: 32, signed: true }) => Ok(name.clone()),
+            _ => Err((name.clone(), "must return nothing, 'i32' or 'bool'".to_string(), "An 'i32' test passes with 0 and a 'bool' test passes with true.".to_string())),
+        }
+    }
+
+    /// Runs a test on its own thread with a big stack, a panic of the
+    /// interpreter fails the test instead of the whole run.
+    fn run_test(modules: &[Module<TypedNode>], name: &str) -> Outcome {
+        let result = std::thread::scope(|scope| {
+            std::thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, || {
+                    let mut interpreter = Interpreter::new(modules, &NamespacePath::new(Vec::new()));
+                    let result = interpreter.run_function(name);
+                    match (result, interpreter.get_exit_code()) {
+                        (Ok(Value::Int(0) | Value::Bool(true) | Value::Void), _) | (Err(()), Some(0)) => Outcome::Passed,
+                        (Ok(Value::Int(code)), _) => Outcome::Failed(format!("returned {}", code), Vec::new()),
+                        (Ok(Value::Bool(false)), _) => Outcome::Failed("returned false".to_string(), Vec::new()),
+                        (Ok(_), _) => Outcome::Failed("returned an unexpected value".to_string(), Vec::new()),
+                        (Err(()), Some(code)) => Outcome::Failed(format!("exited with {}", code), Vec::new()),
+                        (Err(()), None) => Outcome::Failed("failed while running".to_string(), interpreter.get_reports().get_errors().clone()),
+                    }
+                })
+                .unwrap()
+                .join()
+        });
+        result.unwrap_or_else(|_| Outcome::Failed("the interpreter panicked".to_string(), Vec::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::manifest::Manifest;
+
+    #[test]
+    fn failed_assert_fails_the_test_at_its_location() {
+        let dir = std::env::temp_dir().join(format!("snowball-assert-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("main.sn");
+        std::fs::write(&file, "@[test]\nfunc wrong() {\n    let total = 1 + 1;\n    assert(total == 3);\n}\n\nfunc main() i32 { return 0; }\n").unwrap();
+        let Ok(manifest) = Manifest::for_source_file(&file) else {
+            panic!("no manifest for {}", file.display());
+        };
+        let mut compiler = Compiler::new(manifest);
+        let modules = compiler.run();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let Outcome::Failed(_, errors) = Compiler::run_test(&modules.unwrap(), "main::wrong") else {
+            panic!("the test passed");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_message().code(), "E0061");
+        let location = errors[0].get_location();
+        assert_eq!((location.line, location.column, location.width), (4, 4, 18));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeSet;
+
+use crate::ast::attrs::{AstAttrs, AttrHandler};
+use crate::ast::nodes::{Node, AST};
+use crate::ast::source::{FileId, SourceLocation};
+use crate::frontend::lexer::token::{Token, TokenType};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::module::Module;
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+
+/// The options `@[cfg(..)]` predicates are evaluated against. Options are
+/// either names (`unix`, `debug`, `test`) or key value pairs
+/// (`target_os = "linux"`, `feature = "json"`).
+#[derive(Debug, Clone, Default)]
+pub struct CfgSet {
+    names: BTreeSet<String>,
+    values: BTreeSet<(String, String)>,
+}
+
+impl CfgSet {
+    /// The options describing the machine the compiler runs on.
+    pub fn for_target() -> CfgSet {
+        let mut cfg = CfgSet::default();
+        cfg.set("target_os", std::env::consts::OS);
+        cfg.set("target_arch", std::env::consts::ARCH);
+        cfg.set("target_family", std::env::consts::FAMILY);
+        cfg.enable(std::env::consts::FAMILY);
+        cfg
+    }
+
+    pub fn enable(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.values.insert((key.to_string(), value.to_string()));
+    }
+
+    /// Adds an option written like on the command line, `name` or `key="value"`.
+    pub fn parse_option(option: &str) -> Result<(String, Option<String>), String> {
+        let (name, value) = match option.split_once('=') {
+            Some((key, value)) => {
+                let value = value.trim();
+                let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+                    return Err(format!("the value of '{}' has to be quoted, e.g. {}=\"{}\"", key.trim(), key.trim(), value));
+                };
+                (key.trim(), Some(value.to_string()))
+            }
+            None => (option.trim(), None),
+        };
+        if !crate::compiler::manifest::Manifest::is_valid_name(name) {
+            return Err(format!("'{}' is not a valid option name", name));
+        }
+        Ok((name.to_string(), value))
+    }
+
+    pub fn add_option(&mut self, (name, value): &(String, Option<String>)) {
+        match value {
+            Some(value) => self.set(name, value),
+            None => self.enable(name),
+        }
+    }
+
+    /// Evaluates the text of a `cfg` attribute, e.g. `all(unix, not(debug))`.
+    /// Options that aren't set are false.
+    pub fn evaluate(&self, predicate: &str) -> Result<bool, String> {
+        let mut lexer = Lexer::new(predicate, FileId::DUMMY);
+        lexer.lex();
+        if lexer.get_reports().has_errors() {
+            return Err("it contains characters that aren't allowed".to_string());
+        }
+        let tokens = lexer.get_tokens();
+        let mut position = 0;
+        let result = self.evaluate_predicate(tokens, &mut position)?;
+        match tokens[position].get_type() {
+            TokenType::EOF => Ok(result),
+            _ => Err(format!("unexpected '{}' after the predicate", tokens[position].value())),
+        }
+    }
+
+    fn evaluate_predicate(&self, tokens: &[Token], position: &mut usize) -> Result<bool, String> {
+        let TokenType::Identifier(name) = tokens[*position].get_type() else {
+            return Err(format!("expected an option but found '{}'", tokens[*position].value()));
+        };
+        *position += 1;
+        match tokens[*position].get_type() {
+            TokenType::Equal => {
+                *position += 1;
+                let TokenType::String(value) = tokens[*position].get_type() else {
+                    return Err(format!("expected a string after '{} =' but found '{}'", name, tokens[*position].value()));
+                };
+                *position += 1;
+                Ok(self.values.contains(&(name.clone(), value.clone())))
+            }
+            TokenType::OpenParen => {
+                *position += 1;
+                // every argument is evaluated so mistakes are found in all branches
+                let mut results = Vec::new();
+                while !matches!(tokens[*position].get_type(), TokenType::CloseParen) {
+                    results.push(self.evaluate_predicate(tokens, position)?);
+                    match tokens[*position].get_type() {
+                        TokenType::Comma => *position += 1,
+                        TokenType::CloseParen => {}
+                        _ => return Err(format!("expected ',' or ')' but found '{}'", tokens[*position].value())),
+                    }
+                }
+                *position += 1;
+                match (name.as_str(), results.as_slice()) {
+                    ("all", _) => Ok(results.iter().all(|r| *r)),
+                    ("any", _) => Ok(results.iter().any(|r| *r)),
+                    ("not", [result]) => Ok(!result),
+                    ("not", _) => Err(format!("'not' takes one predicate but was given {}", results.len())),
+                    _ => Err(format!("unknown operator '{}', use 'all', 'any' or 'not'", name)),
+                }
+            }
+            _ => Ok(self.names.contains(name)),
+        }
+    }
+
+    /// Whether every `cfg` attribute of an item holds. Invalid predicates
+    /// are reported and keep the item.
+    fn is_enabled(&self, attrs: Option<&AttrHandler>, location: &SourceLocation, reports: &mut Reports) -> bool {
+        let mut enabled = true;
+        for attr in attrs.map(|a| a.get_attrs().as_slice()).unwrap_or_default() {
+            let AstAttrs::Cfg(predicate) = attr else {
+                continue;
+            };
+            match self.evaluate(predicate) {
+                Ok(result) => enabled &= result,
+                Err(reason) => reports.add_error(CompileError::new(Error::InvalidCfg(predicate.clone(), reason), location.clone())
+                    .with_info(ErrorInfo {
+                        help: Some("Predicates look like 'all(unix, not(debug))' or 'feature = \"json\"'.".to_string()),
+                        ..Default::default()
+                    })),
+            }
+        }
+        enabled
+    }
+
+    /// Removes the items (and class members or interface methods) whose `cfg` predicates are false.
+    /// Runs after parsing so the type checker never sees disabled code.
+    pub fn strip(&self, module: &mut Module, reports: &mut Reports) {
+        let AST::TopLevel( nodes ) = module.get_top_mut() else {
+            panic!("Expected TopLevel node");
+        };
+        self.strip_items(nodes, reports);
+    }
+
+    fn strip_items(&self, nodes: &mut Vec<Node>, reports: &mut Reports) {
+        nodes.retain(|node| self.is_enabled(node.get_attrs(), node.get_location().unwrap(), reports));
+        for node in nodes {
+            // members have no location of their own, their class is used instead
+            let location = node.get_location().unwrap().clone();
+            match node.get_kind_mut() {
+                AST::ClassDef( _, _, _, members, methods, .. ) => {
+                    members.retain(|member| self.is_enabled(Some(member.get_attrs()), &location, reports));
+                    methods.retain(|method| self.is_enabled(method.get_attrs(), method.get_location().unwrap(), reports));
+                }
+                AST::InterfaceDef( _, methods, .. ) => methods.retain(|method| self.is_enabled(method.get_attrs(), method.get_location().unwrap(), reports)),
+                AST::NamespaceDef( _, items ) => self.strip_items(items, reports),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::module::NamespacePath;
+    use crate::frontend::parser::Parser;
+
+    fn cfg() -> CfgSet {
+        let mut cfg = CfgSet::default();
+        cfg.enable("unix");
+        cfg.enable("debug");
+        cfg.set("target_os", "linux");
+        cfg.set("feature", "json");
+        cfg.set("feature", "yaml");
+        cfg
+    }
+
+    /// Parses `source`, strips it with `cfg()` and returns the names of the
+    /// remaining items together with the codes of the errors reported.
+    fn strip(source: &str) -> (Vec<String>, Vec<&'static str>) {
+        let mut lexer = Lexer::new(source, FileId::DUMMY);
+        lexer.lex();
+        let mut parser = Parser::new(NamespacePath::new(Vec::new()), "test.sn".to_string(), &lexer);
+        let mut module = parser.parse().expect("the source should parse");
+        let mut reports = Reports::new();
+        cfg().strip(&mut module, &mut reports);
+        let AST::TopLevel( nodes ) = module.get_top_mut() else {
+            panic!("Expected TopLevel node");
+        };
+        let names = nodes.iter().map(|node| match node.get_kind() {
+            AST::FuncDef( name, .. ) => name.clone(),
+            kind => panic!("unexpected item: {:?}", kind),
+        }).collect();
+        (names, reports.get_errors().iter().map(|e| e.get_message().code()).collect())
+    }
+
+    #[test]
+    fn names_are_true_when_enabled() {
+        assert_eq!(cfg().evaluate("unix"), Ok(true));
+        assert_eq!(cfg().evaluate("debug"), Ok(true));
+        assert_eq!(cfg().evaluate("windows"), Ok(false));
+    }
+
+    #[test]
+    fn unknown_keys_are_false() {
+        assert_eq!(cfg().evaluate("no_such_option"), Ok(false));
+        assert_eq!(cfg().evaluate("no_such_key = \"linux\""), Ok(false));
+        // a key only matches as a key value pair, never as a name
+        assert_eq!(cfg().evaluate("target_os"), Ok(false));
+        assert_eq!(cfg().evaluate("feature"), Ok(false));
+    }
+
+    #[test]
+    fn target_and_feature_keys_match_their_values() {
+        assert_eq!(cfg().evaluate("target_os = \"linux\""), Ok(true));
+        assert_eq!(cfg().evaluate("target_os = \"windows\""), Ok(false));
+        assert_eq!(cfg().evaluate("feature = \"json\""), Ok(true));
+        assert_eq!(cfg().evaluate("feature = \"yaml\""), Ok(true));
+        assert_eq!(cfg().evaluate("feature = \"toml\""), Ok(false));
+    }
+
+    #[test]
+    fn the_target_is_described() {
+        let cfg = CfgSet::for_target();
+        assert_eq!(cfg.evaluate(&format!("target_os = \"{}\"", std::env::consts::OS)), Ok(true));
+        assert_eq!(cfg.evaluate(&format!("target_arch = \"{}\"", std::env::consts::ARCH)), Ok(true));
+        assert_eq!(cfg.evaluate(std::env::consts::FAMILY), Ok(true));
+        assert_eq!(cfg.evaluate("debug"), Ok(false));
+    }
+
+    #[test]
+    fn all_any_and_not() {
+        assert_eq!(cfg().evaluate("all(unix, debug)"), Ok(true));
+        assert_eq!(cfg().evaluate("all(unix, windows)"), Ok(false));
+        assert_eq!(cfg().evaluate("all()"), Ok(true));
+        assert_eq!(cfg().evaluate("any(windows, debug)"), Ok(true));
+        assert_eq!(cfg().evaluate("any(windows, test)"), Ok(false));
+        assert_eq!(cfg().evaluate("any()"), Ok(false));
+        assert_eq!(cfg().evaluate("not(windows)"), Ok(true));
+        assert_eq!(cfg().evaluate("not(unix)"), Ok(false));
+        assert_eq!(cfg().evaluate("all(unix, not(test), any(feature = \"toml\", feature = \"json\"))"), Ok(true));
+        assert_eq!(cfg().evaluate("not(any(windows, target_os = \"linux\"))"), Ok(false));
+    }
+
+    #[test]
+    fn malformed_predicates_are_errors() {
+        for predicate in [
+            "",
+            "not(unix, debug)",
+            "not()",
+            "either(unix, debug)",
+            "all(unix debug)",
+            "all(unix,",
+            "unix debug",
+            "target_os = linux",
+            "\"linux\"",
+            "unix $",
+        ] {
+            assert!(cfg().evaluate(predicate).is_err(), "'{}' should be rejected", predicate);
+        }
+        // mistakes are found in branches that don't decide the result
+        assert!(cfg().evaluate("any(unix, not(a, b))").is_err());
+        assert!(cfg().evaluate("all(windows, either(a))").is_err());
+    }
+
+    #[test]
+    fn command_line_options() {
+        assert_eq!(CfgSet::parse_option("unix"), Ok(("unix".to_string(), None)));
+        assert_eq!(CfgSet::parse_option("feature=\"json\""), Ok(("feature".to_string(), Some("json".to_string()))));
+        assert_eq!(CfgSet::parse_option(" feature = \"json\" "), Ok(("feature".to_string(), Some("json".to_string()))));
+        assert!(CfgSet::parse_option("feature=json").is_err());
+        assert!(CfgSet::parse_option("not an option").is_err());
+    }
+
+    #[test]
+    fn disabled_items_are_removed() {
+        let (names, errors) = strip("@[cfg(unix)] func kept() {}\n@[cfg(windows)] func removed() {}\n@[cfg(not(feature = \"toml\"))] func plain() {}");
+        assert_eq!(names, ["kept", "plain"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn invalid_predicates_are_reported_and_keep_the_item() {
+        let (names, errors) = strip("@[cfg(not(unix, debug))] func kept() {}");
+        assert_eq!(names, ["kept"]);
+        assert_eq!(errors, ["E0050"]);
+    }
+}
@@ -2,10 +2,11 @@ pub mod token;
 
 use crate::frontend::lexer::token::{Token, TokenType};
 use crate::reports::{CompileError, Error, Reports};
-use crate::ast::source::SourceLocation;
+use crate::ast::source::{FileId, SourceLocation};
 
 pub struct Lexer {
-    input: Vec<u8>,
+    /// The source split into characters, columns count characters and not bytes.
+    input: Vec<char>,
     read_position: usize,
     location: SourceLocation,
     reports: Reports,
@@ -21,11 +22,12 @@ enum ReadMode {
 }
 
 impl Lexer {
-    pub fn new(content: String, path: String) -> Lexer {
+    pub fn new(content: &str, file: FileId) -> Lexer {
         Lexer {
-            input: content.chars().map(|c| c as u8).collect(),
+            // a byte order mark is not part of the source
+            input: content.strip_prefix('\u{feff}').unwrap_or(content).chars().collect(),
             read_position: 0,
-            location: SourceLocation::new(path, 1, 0, 0),
+            location: SourceLocation::new(file, 1, 0, 0),
             reports: Reports::new(),
             tokens: Vec::new(),
         }
@@ -139,7 +141,10 @@ impl Lexer {
             '[' => self.consume(TokenType::OpenBracket, 0),
             ']' => self.consume(TokenType::CloseBracket, 0),
             ';' => self.consume(TokenType::Semicolon, 0),
-            ':' => self.consume(TokenType::Colon, 0),
+            ':' => match self.get_char(1) {
+                ':' => self.consume(TokenType::DoubleColon, 2),
+                _ => self.consume(TokenType::Colon, 0),
+            },
             ',' => self.consume(TokenType::Comma, 0),
             '.' => self.consume(TokenType::Dot, 0),
             '@' => self.consume(TokenType::At, 0),
@@ -149,21 +154,25 @@ impl Lexer {
             '"' => self.lex_string(),
             '\'' => self.lex_char(),
             _ => {
-                if self.get_char(0).is_digit(10) {
+                if self.get_char(0).is_ascii_digit() {
                     self.lex_number();
                     return;
-                } else if self.get_char(0).is_alphabetic() || self.get_char(0) == '_' {
+                } else if Self::is_identifier_start(self.get_char(0)) {
                     self.lex_identifier();
                     return;
+                } else if self.get_char(0).is_whitespace() {
+                    self.next_char(1);
+                    return;
                 }
                 self.report_error(Error::UnexpectedChar(self.get_char(0)));
+                self.next_char(1);
             }
         }
     }
 
     fn lex_char(&mut self) {
-        self.next_char(1);
         let loc = self.location.clone();
+        self.next_char(1);
         let mut chr = self.get_char(0);
         if chr == '\\' {
             self.next_char(1);
@@ -188,9 +197,9 @@ impl Lexer {
         if self.get_char(0) != '\'' {
             self.report_error(Error::UnexpectedChar(self.get_char(0)));
         }
-        self.append_token(TokenType::Char(chr as u8), 3);
         self.next_char(1);
-        self.tokens.last_mut().unwrap().set_location(loc.with_width(3));
+        let width = self.location.column - loc.column;
+        self.tokens.push(Token::new(TokenType::Char(chr), loc.with_width(width)));
     }
 
     fn lex_string(&mut self) {
@@ -198,7 +207,6 @@ impl Lexer {
         self.next_char(1);
         let mut string = String::new();
         let col = self.location.column;
-        let line = self.location.line;
         while self.get_char(0) != '"' {
             if self.get_char(0) == '\n' || self.get_char(0) == '\0' {
                 self.report_error(Error::UnexpectedEOF);
@@ -238,9 +246,7 @@ impl Lexer {
         let mut id = String::from(self.get_char(0));
         let loc = self.location.clone();
         self.next_char(1);
-        while (self.get_char(0).is_alphabetic() || self.get_char(0) == '_')
-            || self.get_char(0).is_digit(10)
-        {
+        while Self::is_identifier_continue(self.get_char(0)) {
             id.push(self.get_char(0));
             self.next_char(1);
         }
@@ -258,13 +264,16 @@ impl Lexer {
             "new" => self.append_token(TokenType::New, 3),
             "super" => self.append_token(TokenType::Super, 5),
             "do" => self.append_token(TokenType::Do, 2),
+            "match" => self.append_token(TokenType::Match, 5),
             "import" => self.append_token(TokenType::Import, 6),
+            "as" => self.append_token(TokenType::As, 2),
             "let" => self.append_token(TokenType::Let, 3),
             "mut" => self.append_token(TokenType::Mut, 3),
             "struct" => self.append_token(TokenType::Struct, 6),
             "enum" => self.append_token(TokenType::Enum, 4),
             "class" => self.append_token(TokenType::Class, 5),
             "interface" => self.append_token(TokenType::Interface, 9),
+            "namespace" => self.append_token(TokenType::Namespace, 9),
             "public" => self.append_token(TokenType::Public, 6),
             "private" => self.append_token(TokenType::Private, 7),
             "const" => self.append_token(TokenType::Const, 5),
@@ -276,7 +285,17 @@ impl Lexer {
             "override" => self.append_token(TokenType::Override, 8),
             _ => self.append_token(TokenType::Identifier(id.clone()), id.len()),
         }
-        self.tokens.last_mut().unwrap().set_location(loc.with_width(id.len()));
+        self.tokens.last_mut().unwrap().set_location(loc.with_width(id.chars().count()));
+    }
+
+    /// Identifiers follow UAX #31 (`XID_Start XID_Continue*`), with `_` allowed
+    /// as the first character.
+    fn is_identifier_start(c: char) -> bool {
+        unicode_ident::is_xid_start(c) || c == '_'
+    }
+
+    fn is_identifier_continue(c: char) -> bool {
+        unicode_ident::is_xid_continue(c)
     }
 
     fn lex_number(&mut self) {
@@ -296,16 +315,13 @@ impl Lexer {
         self.next_char(1);
         match read_mode {
             ReadMode::Integer => {
-                while self.get_char(0).is_digit(10) || self.get_char(0) == '.' {
+                while self.get_char(0).is_ascii_digit() || self.get_char(0) == '.' {
                     if self.get_char(0) == '.' {
-                        match read_mode {
-                            ReadMode::Float => {
-                                read_mode = ReadMode::Integer;
-                                num.pop();
-                                is_range = true;
-                                break;
-                            }
-                            _ => {}
+                        if let ReadMode::Float = read_mode {
+                            read_mode = ReadMode::Integer;
+                            num.pop();
+                            is_range = true;
+                            break;
                         }
                         read_mode = ReadMode::Float;
                     }
@@ -332,7 +348,7 @@ impl Lexer {
             ReadMode::Hex => {
                 num.push(self.get_char(0));
                 self.next_char(1); // skip 'x'
-                while self.get_char(0).is_digit(16) {
+                while self.get_char(0).is_ascii_hexdigit() {
                     num.push(self.get_char(0));
                     self.next_char(1);
                 }
@@ -340,13 +356,21 @@ impl Lexer {
             _ => {}
         }
         let mut append_dot = false;
-        if num.len() > 1 && num.chars().last().unwrap() == '.' {
+        if num.len() > 1 && num.ends_with('.') {
             num.pop();
             append_dot = true;
             read_mode = ReadMode::Float;
         }
         match self.get_char(0) {
-            'U' | 'u' | 'L' | 'l' | 'F' | 'f' | 'D' | 'd' => {
+            'U' | 'u' | 'L' | 'l' => {
+                num.push(self.get_char(0));
+                self.next_char(1);
+            }
+            'F' | 'f' | 'D' | 'd' => {
+                // `2f` is a float written without a fraction
+                if let ReadMode::Integer = read_mode {
+                    read_mode = ReadMode::Float;
+                }
                 num.push(self.get_char(0));
                 self.next_char(1);
             }
@@ -382,7 +406,7 @@ impl Lexer {
         if self.read_position + offset >= self.input.len() {
             '\0'
         } else {
-            self.input[self.read_position + offset] as char
+            self.input[self.read_position + offset]
         }
     }
 
@@ -4,7 +4,7 @@ pub enum TokenType {
     Integer(String),
     Float(String),
     String(String),
-    Char(u8),
+    Char(char),
     Continue,
     Break,
     Return,
@@ -18,10 +18,13 @@ pub enum TokenType {
     Enum,
     Class,
     Do,
+    Match,
     Interface,
+    Namespace,
     Public,
     Private,
     Import,
+    As,
     Const,
     Static,
     Inline,
@@ -122,7 +125,7 @@ impl Token {
             TokenType::Integer(value) => value.clone(),
             TokenType::Float(value) => value.clone(),
             TokenType::String(value) => format!("\"{}\"", value),
-            TokenType::Char(value) => format!("'{}'", (*value) as char),
+            TokenType::Char(value) => format!("'{}'", value),
             TokenType::Continue => String::from("continue"),
             TokenType::Break => String::from("break"),
             TokenType::Return => String::from("return"),
@@ -135,10 +138,13 @@ impl Token {
             TokenType::Struct => String::from("struct"),
             TokenType::Enum => String::from("enum"),
             TokenType::Class => String::from("class"),
+            TokenType::Match => String::from("match"),
             TokenType::Interface => String::from("interface"),
+            TokenType::Namespace => String::from("namespace"),
             TokenType::Public => String::from("public"),
             TokenType::Private => String::from("private"),
             TokenType::Import => String::from("import"),
+            TokenType::As => String::from("as"),
             TokenType::Const => String::from("const"),
             TokenType::Static => String::from("static"),
             TokenType::Inline => String::from("inline"),
@@ -202,14 +208,14 @@ impl Token {
     }
 }
 
-impl ToString for Token {
-    fn to_string(&self) -> String {
-        format!("Token({})", self.value())
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Token({})", self.value())
     }
 }
 
 impl std::fmt::Debug for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
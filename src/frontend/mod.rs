@@ -1,4 +1,6 @@
+pub mod cfg;
 pub mod lexer;
+pub mod monomorphize;
 pub mod parser;
 pub mod module;
 pub mod type_checker;
\ No newline at end of file
@@ -17,7 +17,7 @@ impl NamespacePath {
     pub fn display(&self) -> String {
         let mut output = String::new();
         for segment in &*self.segments {
-            if output.len() != 0 { output.push_str("::"); }
+            if !output.is_empty() { output.push_str("::"); }
             output.push_str(segment.as_str());
         }
         output
@@ -42,9 +42,9 @@ impl NamespacePath {
     }
 }
 
-impl ToString for NamespacePath {
-    fn to_string(&self) -> String {
-        self.display()
+impl std::fmt::Display for NamespacePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display())
     }
 }
 
@@ -70,7 +70,7 @@ impl<T: Clone + std::fmt::Debug> Module<T> {
     }
 
     pub fn get_top(&self) -> &AST<T> {
-        &(self.top.as_ref().unwrap())
+        self.top.as_ref().unwrap()
     }
 
     pub fn get_top_mut(&mut self) -> &mut AST<T> {
@@ -0,0 +1,685 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::ast::nodes::{ClassMember, EnumVariant, GenericDecl, MatchArm, Param, Pattern, PatternKind, AST};
+use crate::ast::typed::TypedNode;
+use crate::frontend::module::Module;
+use crate::frontend::type_checker::{Type, Typechecker, UnificationType};
+use crate::reports::{CompileError, Error, ErrorInfo, Reports};
+
+/// Instantiations that need more nested instantiations than this are
+/// reported, e.g. `f<T>` calling `f<Box<T>>` would never stop.
+const MAX_INSTANTIATION_DEPTH: usize = 64;
+
+/// A generic function or method that gets instantiated on demand.
+struct FunctionTemplate {
+    node: TypedNode,
+    /// The generic arguments of the class the method was specialized for.
+    class_generics: HashMap<String, UnificationType>,
+    /// The class the instantiations are methods of.
+    owner: Option<usize>,
+    module: usize,
+}
+
+struct ObjectTemplate {
+    node: TypedNode,
+    module: usize,
+}
+
+/// An instantiation that was asked for and still has to be generated.
+enum Instance {
+    Function(/* template */ String, /* name */ String, Vec<UnificationType>),
+    Object(/* template */ usize, /* id */ usize, Vec<UnificationType>),
+}
+
+/// Replaces every generic function and class with one copy per set of generic
+/// arguments it is used with, so the backends only ever see concrete types.
+/// Instantiations are named after the template and their arguments
+/// (e.g. `app::max<i32>` or `app::Box<app::Pair<i32, String>>`).
+pub struct Monomorphizer {
+    functions: HashMap<String, FunctionTemplate>,
+    objects: HashMap<usize, ObjectTemplate>,
+    /// The static methods of the generic classes, by their path.
+    class_methods: HashMap<String, usize>,
+    /// The path and the parent class of every concrete class, enum and interface.
+    paths: HashMap<usize, String>,
+    parents: HashMap<usize, usize>,
+    function_instances: HashSet<String>,
+    object_instances: HashMap<String, usize>,
+    next_id: usize,
+    queue: VecDeque<(Instance, usize)>,
+    /// How many instantiations led to the one being generated.
+    depth: usize,
+    output: Vec<Vec<TypedNode>>,
+    /// Instantiated generic methods, added to their class at the end.
+    methods: HashMap<usize, Vec<TypedNode>>,
+    reports: Reports,
+}
+
+macro_rules! report {
+    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
+        return Err(());
+    }}
+}
+
+impl Monomorphizer {
+    pub fn new() -> Monomorphizer {
+        Monomorphizer {
+            functions: HashMap::new(),
+            objects: HashMap::new(),
+            class_methods: HashMap::new(),
+            paths: HashMap::new(),
+            parents: HashMap::new(),
+            function_instances: HashSet::new(),
+            object_instances: HashMap::new(),
+            next_id: 0,
+            queue: VecDeque::new(),
+            depth: 0,
+            output: Vec::new(),
+            methods: HashMap::new(),
+            reports: Reports::new(),
+        }
+    }
+
+    pub fn get_reports(&self) -> &Reports {
+        &self.reports
+    }
+
+    pub fn monomorphize(&mut self, modules: Vec<Module<TypedNode>>) -> Result<Vec<Module<TypedNode>>, ()> {
+        let tops = modules.iter().map(|module| match module.get_top() {
+            AST::TopLevel( nodes ) => nodes.clone(),
+            _ => panic!("Expected TopLevel node"),
+        }).collect::<Vec<_>>();
+        for (module, nodes) in tops.iter().enumerate() {
+            for node in nodes {
+                self.index(node, module);
+            }
+        }
+        // the parents are needed to find the methods of a class before its declaration is reached
+        for node in tops.iter().flatten() {
+            if let AST::ClassDef( _, Some(parent), _, _, _, generics, Some(id) ) = node.get_kind() {
+                if generics.is_empty() {
+                    if let UnificationType::Known(Type::Object { id: parent, .. }) = self.concrete(parent, node)? {
+                        self.parents.insert(*id, parent);
+                    }
+                }
+            }
+        }
+        for nodes in &tops {
+            let mut concrete = Vec::new();
+            for node in nodes {
+                match node.get_kind() {
+                    AST::FuncDef( path, .. ) if !Self::is_generic(node) => concrete.push(self.function(node, path.clone(), &HashMap::new())?),
+                    AST::ClassDef( path, .., generics, Some(id) ) | AST::InterfaceDef( path, _, generics, Some(id) ) | AST::EnumDef( path, _, generics, Some(id) )
+                        if generics.is_empty() => concrete.push(self.object(node, path.clone(), *id, &HashMap::new())?),
+                    _ => {}
+                }
+            }
+            self.output.push(concrete);
+        }
+        while let Some((instance, depth)) = self.queue.pop_front() {
+            self.depth = depth;
+            match instance {
+                Instance::Function( template, name, args ) => {
+                    let template = &self.functions[&template];
+                    let (node, owner, module) = (template.node.clone(), template.owner, template.module);
+                    let AST::FuncDef( .., Some(generics), _ ) = node.get_kind() else {
+                        panic!("Expected a generic FuncDef");
+                    };
+                    let mut map = template.class_generics.clone();
+                    map.extend(generics.iter().map(|g| g.get_name().clone()).zip(args));
+                    let function = self.function(&node, name, &map)?;
+                    match owner {
+                        Some(owner) => self.methods.entry(owner).or_default().push(function),
+                        None => self.output[module].push(function),
+                    }
+                }
+                Instance::Object( template, id, args ) => {
+                    let ObjectTemplate { node, module } = &self.objects[&template];
+                    let (node, module) = (node.clone(), *module);
+                    let map = Self::object_generics(&node).iter().map(|g| g.get_name().clone()).zip(args).collect();
+                    let object = self.object(&node, self.paths[&id].clone(), id, &map)?;
+                    self.output[module].push(object);
+                }
+            }
+        }
+        let mut output = std::mem::take(&mut self.output);
+        for node in output.iter_mut().flatten() {
+            if let AST::ClassDef( .., methods, _, Some(id) ) | AST::InterfaceDef( _, methods, _, Some(id) ) = &mut **node.get_kind_mut() {
+                methods.extend(self.methods.remove(id).unwrap_or_default());
+            }
+        }
+        Ok(modules.into_iter().zip(output).map(|(module, nodes)| {
+            let mut new_module = Module::new(module.get_path().clone(), module.get_file_name().clone());
+            new_module.set_top(AST::TopLevel(nodes));
+            new_module
+        }).collect())
+    }
+
+    /// Remembers the templates and the paths of the objects declared by a global node.
+    fn index(&mut self, node: &TypedNode, module: usize) {
+        match node.get_kind() {
+            AST::FuncDef( path, .. ) if Self::is_generic(node) => {
+                self.functions.insert(path.clone(), FunctionTemplate { node: node.clone(), class_generics: HashMap::new(), owner: None, module });
+            }
+            AST::ClassDef( path, .., generics, Some(id) ) | AST::InterfaceDef( path, _, generics, Some(id) ) | AST::EnumDef( path, _, generics, Some(id) ) => {
+                self.next_id = self.next_id.max(id + 1);
+                if generics.is_empty() {
+                    self.paths.insert(*id, path.clone());
+                    self.add_method_templates(node, path, *id, &HashMap::new(), module);
+                } else {
+                    self.objects.insert(*id, ObjectTemplate { node: node.clone(), module });
+                    for method in Self::object_methods(node) {
+                        let AST::FuncDef( method_path, .. ) = method.get_kind() else {
+                            panic!("Expected FuncDef");
+                        };
+                        self.class_methods.insert(method_path.clone(), *id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Makes the generic methods of a concrete class available under its path.
+    fn add_method_templates(&mut self, node: &TypedNode, path: &str, id: usize, class_generics: &HashMap<String, UnificationType>, module: usize) {
+        for method in Self::object_methods(node) {
+            if Self::is_generic(method) {
+                self.functions.insert(format!("{}::{}", path, Self::method_name(method)), FunctionTemplate {
+                    node: method.clone(), class_generics: class_generics.clone(), owner: Some(id), module
+                });
+            }
+        }
+    }
+
+    fn is_generic(node: &TypedNode) -> bool {
+        matches!(node.get_kind(), AST::FuncDef( .., Some(generics), _ ) if !generics.is_empty())
+    }
+
+    fn object_methods(node: &TypedNode) -> &[TypedNode] {
+        match node.get_kind() {
+            AST::ClassDef( .., methods, _, _ ) | AST::InterfaceDef( _, methods, _, _ ) => methods,
+            _ => &[],
+        }
+    }
+
+    fn object_generics(node: &TypedNode) -> &Vec<GenericDecl<UnificationType>> {
+        match node.get_kind() {
+            AST::ClassDef( .., generics, _ ) | AST::InterfaceDef( _, _, generics, _ ) | AST::EnumDef( _, _, generics, _ ) => generics,
+            _ => panic!("Expected ClassDef, EnumDef or InterfaceDef"),
+        }
+    }
+
+    /// The name of a method without the path of its class.
+    fn method_name(method: &TypedNode) -> &str {
+        let AST::FuncDef( path, .. ) = method.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        path.rsplit("::").next().unwrap()
+    }
+
+    /// The name of an instantiation, e.g. `max<i32>`.
+    fn instance_name(&self, template: &str, args: &[UnificationType]) -> String {
+        format!("{}<{}>", template, args.iter().map(|arg| self.display(arg)).collect::<Vec<_>>().join(", "))
+    }
+
+    fn display(&self, ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(ty) => match ty {
+                Type::Integer { size, signed } => format!("{}{}", if *signed { "i" } else { "u" }, size),
+                Type::Float { size } => format!("f{}", size),
+                Type::Object { id, .. } => self.paths[id].clone(),
+                Type::Function { args, ret } => {
+                    format!("func({}) {}", args.iter().map(|a| self.display(a)).collect::<Vec<_>>().join(", "), self.display(ret))
+                }
+                Type::Pointer { ty } => format!("*{}", self.display(ty)),
+                Type::Reference { ty } => format!("&{}", self.display(ty)),
+                Type::Bool => "bool".to_string(),
+                Type::String => "String".to_string(),
+                Type::Void => "void".to_string(),
+            },
+            UnificationType::TypeVariable( _ ) => "_".to_string(),
+            UnificationType::Generic( name ) => name.clone(),
+        }
+    }
+
+    fn is_concrete(ty: &UnificationType) -> bool {
+        match ty {
+            UnificationType::Known(Type::Object { generics, .. }) => generics.iter().all(Self::is_concrete),
+            UnificationType::Known(Type::Function { args, ret }) => args.iter().all(Self::is_concrete) && Self::is_concrete(ret),
+            UnificationType::Known(Type::Pointer { ty }) | UnificationType::Known(Type::Reference { ty }) => Self::is_concrete(ty),
+            UnificationType::Known(_) => true,
+            UnificationType::TypeVariable( _ ) | UnificationType::Generic( _ ) => false,
+        }
+    }
+
+    /// Counts one more instantiation of `template` for the one being generated,
+    /// `at` is where it is asked for.
+    fn enter_instance(&mut self, template: &str, at: &TypedNode) -> Result<usize, ()> {
+        if self.depth >= MAX_INSTANTIATION_DEPTH {
+            report!(self, Error::InstantiationLimit(template.to_string()), at, ErrorInfo {
+                info: Some(format!("This needs more than {} nested instantiations.", MAX_INSTANTIATION_DEPTH)),
+                help: Some("Make sure generic functions do not call themselves with bigger generic arguments.".to_string()),
+                ..Default::default()
+            });
+        }
+        Ok(self.depth + 1)
+    }
+
+    /// Returns the name of the instantiation of a generic function, generating it if needed.
+    fn function_instance(&mut self, template: &str, args: Vec<UnificationType>, at: &TypedNode) -> Result<String, ()> {
+        let name = self.instance_name(template, &args);
+        if !self.function_instances.contains(&name) {
+            let depth = self.enter_instance(template, at)?;
+            self.function_instances.insert(name.clone());
+            self.queue.push_back((Instance::Function(template.to_string(), name.clone(), args), depth));
+        }
+        Ok(name)
+    }
+
+    /// Returns the id of the instantiation of a generic class, enum or interface, generating it if needed.
+    fn object_instance(&mut self, template: usize, args: Vec<UnificationType>, at: &TypedNode) -> Result<usize, ()> {
+        let node = self.objects[&template].node.clone();
+        let (AST::ClassDef( path, .. ) | AST::InterfaceDef( path, .. ) | AST::EnumDef( path, .. )) = node.get_kind() else {
+            panic!("Expected ClassDef, EnumDef or InterfaceDef");
+        };
+        let template_path = path;
+        let path = self.instance_name(path, &args);
+        if let Some(id) = self.object_instances.get(&path) {
+            return Ok(*id);
+        }
+        let depth = self.enter_instance(template_path, at)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.object_instances.insert(path.clone(), id);
+        self.paths.insert(id, path.clone());
+        let map = Self::object_generics(&node).iter().map(|g| g.get_name().clone()).zip(args.iter().cloned()).collect();
+        self.add_method_templates(&node, &path, id, &map, self.objects[&template].module);
+        if let AST::ClassDef( _, Some(parent), .. ) = node.get_kind() {
+            if let UnificationType::Known(Type::Object { id: parent, .. }) = self.ty(parent, &map, at)? {
+                self.parents.insert(id, parent);
+            }
+        }
+        self.queue.push_back((Instance::Object(template, id, args), depth));
+        Ok(id)
+    }
+
+    /// Replaces the generic parameters of a type and every generic object in it by its instantiation.
+    fn ty(&mut self, ty: &UnificationType, map: &HashMap<String, UnificationType>, at: &TypedNode) -> Result<UnificationType, ()> {
+        self.concrete(&Typechecker::substitute(ty, map), at)
+    }
+
+    fn concrete(&mut self, ty: &UnificationType, at: &TypedNode) -> Result<UnificationType, ()> {
+        Ok(match ty {
+            UnificationType::Known(Type::Object { id, generics }) if !generics.is_empty() => {
+                let mut args = Vec::new();
+                for generic in generics {
+                    args.push(self.concrete(generic, at)?);
+                }
+                UnificationType::Known(Type::Object { id: self.object_instance(*id, args, at)?, generics: Vec::new() })
+            }
+            UnificationType::Known(Type::Function { args, ret }) => {
+                let mut concrete = Vec::new();
+                for arg in args {
+                    concrete.push(self.concrete(arg, at)?);
+                }
+                UnificationType::Known(Type::Function { args: concrete, ret: Box::new(self.concrete(ret, at)?) })
+            }
+            UnificationType::Known(Type::Pointer { ty }) => UnificationType::Known(Type::Pointer { ty: Box::new(self.concrete(ty, at)?) }),
+            UnificationType::Known(Type::Reference { ty }) => UnificationType::Known(Type::Reference { ty: Box::new(self.concrete(ty, at)?) }),
+            ty => ty.clone(),
+        })
+    }
+
+    fn object(&mut self, node: &TypedNode, path: String, id: usize, map: &HashMap<String, UnificationType>) -> Result<TypedNode, ()> {
+        let typed = match node.get_kind() {
+            AST::ClassDef( _, parent, interfaces, members, methods, _, _ ) => {
+                let parent = match parent {
+                    Some(parent) => Some(self.ty(parent, map, node)?),
+                    None => None,
+                };
+                let mut concrete_interfaces = Vec::new();
+                for interface in interfaces {
+                    concrete_interfaces.push(self.ty(interface, map, node)?);
+                }
+                let mut concrete_members = Vec::new();
+                for member in members {
+                    concrete_members.push(ClassMember::new(member.get_name().clone(), self.ty(member.get_ty(), map, node)?, member.get_attrs().clone()));
+                }
+                AST::ClassDef(path.clone(), parent, concrete_interfaces, concrete_members, self.methods(methods, &path, map)?, Vec::new(), Some(id))
+            }
+            AST::InterfaceDef( _, methods, _, _ ) => AST::InterfaceDef(path.clone(), self.methods(methods, &path, map)?, Vec::new(), Some(id)),
+            AST::EnumDef( _, variants, _, _ ) => {
+                let mut concrete = Vec::new();
+                for variant in variants {
+                    let mut fields = Vec::new();
+                    for (name, ty) in variant.get_fields() {
+                        fields.push((name.clone(), self.ty(ty, map, node)?));
+                    }
+                    concrete.push(EnumVariant::new(variant.get_name().clone(), variant.get_kind(), fields, variant.get_location().clone()));
+                }
+                AST::EnumDef(path, concrete, Vec::new(), Some(id))
+            }
+            _ => panic!("Expected ClassDef, EnumDef or InterfaceDef"),
+        };
+        Ok(TypedNode::new(typed, node.get_attrs().clone())
+            .with_type(UnificationType::Known(Type::Object { id, generics: Vec::new() }))
+            .with_location(node.get_location().cloned()))
+    }
+
+    /// The methods of a concrete class, the generic ones are added once they are instantiated.
+    fn methods(&mut self, methods: &[TypedNode], path: &str, map: &HashMap<String, UnificationType>) -> Result<Vec<TypedNode>, ()> {
+        let mut concrete = Vec::new();
+        for method in methods {
+            if !Self::is_generic(method) {
+                concrete.push(self.function(method, format!("{}::{}", path, Self::method_name(method)), map)?);
+            }
+        }
+        Ok(concrete)
+    }
+
+    fn function(&mut self, node: &TypedNode, name: String, map: &HashMap<String, UnificationType>) -> Result<TypedNode, ()> {
+        let AST::FuncDef( _, params, ret, body, _, id ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let mut concrete = Vec::new();
+        for param in params {
+            let default = match param.get_default() {
+                Some(default) => Some(self.node(default, map)?),
+                None => None,
+            };
+            concrete.push(Param::new(param.get_name().clone(), self.ty(param.get_ty(), map, node)?, default, param.is_mutable(), param.get_location().clone()));
+        }
+        let body = match body {
+            Some(body) => Some(self.node(body, map)?),
+            None => None,
+        };
+        let typed = AST::FuncDef(name, concrete, self.ty(ret, map, node)?, body, None, *id);
+        Ok(TypedNode::new(typed, node.get_attrs().clone()).with_type(self.ty(node.get_type(), map, node)?).with_location(node.get_location().cloned()))
+    }
+
+    fn nodes(&mut self, nodes: &[TypedNode], map: &HashMap<String, UnificationType>) -> Result<Vec<TypedNode>, ()> {
+        let mut concrete = Vec::new();
+        for node in nodes {
+            concrete.push(self.node(node, map)?);
+        }
+        Ok(concrete)
+    }
+
+    fn node(&mut self, node: &TypedNode, map: &HashMap<String, UnificationType>) -> Result<TypedNode, ()> {
+        let typed = match node.get_kind() {
+            AST::Block( nodes ) => AST::Block(self.nodes(nodes, map)?),
+            AST::Return( value ) => AST::Return(match value {
+                Some(value) => Some(self.node(value, map)?),
+                None => None,
+            }),
+            AST::If( cond, then, otherwise ) => AST::If(self.node(cond, map)?, self.node(then, map)?, self.nodes(otherwise, map)?),
+            AST::While( cond, body, is_do_while ) => AST::While(self.node(cond, map)?, self.node(body, map)?, *is_do_while),
+            AST::For( init, cond, step, body ) => AST::For(self.node(init, map)?, self.node(cond, map)?, self.node(step, map)?, self.nodes(body, map)?),
+            AST::VarDef( name, ty, value ) => {
+                let ty = match ty {
+                    Some(ty) => Some(self.ty(ty, map, node)?),
+                    None => None,
+                };
+                let value = match value {
+                    Some(value) => Some(self.node(value, map)?),
+                    None => None,
+                };
+                AST::VarDef(name.clone(), ty, value)
+            }
+            AST::Ident( name, Some(generics) ) => {
+                let args = generics.iter().map(|g| Typechecker::substitute(g, map)).collect::<Vec<_>>();
+                if !args.iter().all(Self::is_concrete) {
+                    report!(self, Error::TypeAnnotationsNeeded, node, ErrorInfo {
+                        info: Some(format!("The generic arguments of '{}' cannot be inferred.", name)),
+                        help: Some("Pass the generic arguments explicitly.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let mut concrete = Vec::new();
+                for arg in &args {
+                    concrete.push(self.concrete(arg, node)?);
+                }
+                AST::Ident(self.function_path(name, concrete, node)?, None)
+            }
+            AST::ClassAccess( base, name ) => {
+                let base = self.node(base, map)?;
+                let name = match node.get_type() {
+                    UnificationType::Known(Type::Function { .. }) => self.method_member(base.get_type(), name, &Typechecker::substitute(node.get_type(), map), node)?,
+                    _ => name.clone(),
+                };
+                AST::ClassAccess(base, name)
+            }
+            AST::NamespaceAccess( base, name, _ ) => AST::NamespaceAccess(self.node(base, map)?, name.clone(), None),
+            AST::Call( callee, args ) => AST::Call(self.node(callee, map)?, self.nodes(args, map)?),
+            AST::ClassInit( ty, args ) => AST::ClassInit(self.ty(ty, map, node)?, self.nodes(args, map)?),
+            AST::Cast( value, ty ) => AST::Cast(self.node(value, map)?, self.ty(ty, map, node)?),
+            AST::BinaryOp( op, lhs, rhs, is_unary ) => AST::BinaryOp(op.clone(), self.node(lhs, map)?, self.node(rhs, map)?, *is_unary),
+            AST::Assign( target, value, op ) => AST::Assign(self.node(target, map)?, self.node(value, map)?, op.clone()),
+            AST::Match( scrutinee, arms ) => {
+                let scrutinee = self.node(scrutinee, map)?;
+                let mut concrete = Vec::new();
+                for arm in arms {
+                    let guard = match arm.get_guard() {
+                        Some(guard) => Some(self.node(guard, map)?),
+                        None => None,
+                    };
+                    concrete.push(MatchArm::new(self.pattern(arm.get_pattern(), map, node)?, guard, self.node(arm.get_body(), map)?));
+                }
+                AST::Match(scrutinee, concrete)
+            }
+            AST::VariantInit( ty, variant, fields ) => {
+                let mut concrete = Vec::new();
+                for (name, value) in fields {
+                    concrete.push((name.clone(), self.node(value, map)?));
+                }
+                AST::VariantInit(self.ty(ty, map, node)?, variant.clone(), concrete)
+            }
+            kind => kind.clone(),
+        };
+        Ok(TypedNode::new(typed, node.get_attrs().clone()).with_type(self.ty(node.get_type(), map, node)?).with_location(node.get_location().cloned()))
+    }
+
+    fn pattern(&mut self, pattern: &Pattern<UnificationType>, map: &HashMap<String, UnificationType>, at: &TypedNode) -> Result<Pattern<UnificationType>, ()> {
+        let kind = match pattern.get_kind() {
+            PatternKind::Variant( ty, name, kind, fields, has_rest ) => {
+                let ty = match ty {
+                    Some(ty) => Some(self.ty(ty, map, at)?),
+                    None => None,
+                };
+                let mut concrete = Vec::new();
+                for (field, pattern) in fields {
+                    concrete.push((field.clone(), self.pattern(pattern, map, at)?));
+                }
+                PatternKind::Variant(ty, name.clone(), *kind, concrete, *has_rest)
+            }
+            kind => kind.clone(),
+        };
+        Ok(Pattern::new(kind, pattern.get_location().unwrap().clone()))
+    }
+
+    /// The path of the instantiation a reference to a generic function or
+    /// to a static method of a generic class points to.
+    fn function_path(&mut self, path: &str, mut args: Vec<UnificationType>, at: &TypedNode) -> Result<String, ()> {
+        if self.functions.contains_key(path) {
+            return self.function_instance(path, args, at);
+        }
+        let Some(class) = self.class_methods.get(path).copied() else {
+            return Ok(path.to_string());
+        };
+        // the generic arguments of the class come before the ones of the method
+        let method_args = args.split_off(Self::object_generics(&self.objects[&class].node).len());
+        let id = self.object_instance(class, args, at)?;
+        let method = format!("{}::{}", self.paths[&id], path.rsplit("::").next().unwrap());
+        if method_args.is_empty() {
+            Ok(method)
+        } else {
+            self.function_instance(&method, method_args, at)
+        }
+    }
+
+    /// The name a method is called by on a value of type `base`. Generic methods
+    /// are named after their instantiation, the arguments are recovered from `ty`,
+    /// the type the method was used as.
+    fn method_member(&mut self, base: &UnificationType, name: &str, ty: &UnificationType, at: &TypedNode) -> Result<String, ()> {
+        let UnificationType::Known(Type::Object { id, .. }) = base else {
+            return Ok(name.to_string());
+        };
+        let mut current = Some(*id);
+        while let Some(class) = current {
+            let path = format!("{}::{}", self.paths[&class], name);
+            if let Some(template) = self.functions.get(&path) {
+                let AST::FuncDef( _, params, ret, _, Some(generics), _ ) = template.node.get_kind() else {
+                    panic!("Expected a generic FuncDef");
+                };
+                let declared = UnificationType::Known(Type::Function {
+                    args: params.iter().filter(|param| param.get_name() != "self").map(|param| param.get_ty().clone()).collect(),
+                    ret: Box::new(ret.clone()),
+                });
+                let mut inferred = template.class_generics.clone();
+                Self::bind(&Typechecker::substitute(&declared, &template.class_generics), ty, &mut inferred);
+                let generics = generics.clone();
+                let mut args = Vec::new();
+                for generic in &generics {
+                    let arg = match (inferred.get(generic.get_name()), generic.get_default()) {
+                        (Some(arg), _) => arg.clone(),
+                        (None, Some(default)) => Typechecker::substitute(default, &inferred),
+                        (None, None) => report!(self, Error::TypeAnnotationsNeeded, at, ErrorInfo {
+                            info: Some(format!("The generic argument '{}' of '{}' cannot be inferred.", generic.get_name(), name)),
+                            help: Some("Use the generic parameter in the parameters of the method.".to_string()),
+                            ..Default::default()
+                        }),
+                    };
+                    inferred.insert(generic.get_name().clone(), arg.clone());
+                    args.push(self.concrete(&arg, at)?);
+                }
+                let member = self.instance_name(name, &args);
+                self.function_instance(&path, args, at)?;
+                return Ok(member);
+            }
+            current = self.parents.get(&class).copied();
+        }
+        Ok(name.to_string())
+    }
+
+    /// Matches the declared type of a generic method with the type it was used
+    /// as, recording what its generic parameters stand for.
+    fn bind(declared: &UnificationType, found: &UnificationType, map: &mut HashMap<String, UnificationType>) {
+        match (declared, found) {
+            (UnificationType::Generic( name ), found) => {
+                map.entry(name.clone()).or_insert(found.clone());
+            }
+            (UnificationType::Known(Type::Object { id: a, generics: declared }), UnificationType::Known(Type::Object { id: b, generics: found })) if a == b => {
+                for (declared, found) in declared.iter().zip(found) {
+                    Self::bind(declared, found, map);
+                }
+            }
+            (UnificationType::Known(Type::Function { args: declared, ret: declared_ret }), UnificationType::Known(Type::Function { args: found, ret: found_ret })) => {
+                for (declared, found) in declared.iter().zip(found) {
+                    Self::bind(declared, found, map);
+                }
+                Self::bind(declared_ret, found_ret, map);
+            }
+            (UnificationType::Known(Type::Pointer { ty: declared }), UnificationType::Known(Type::Pointer { ty: found }))
+            | (UnificationType::Known(Type::Reference { ty: declared }), UnificationType::Known(Type::Reference { ty: found })) => Self::bind(declared, found, map),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{mangle, top_nodes};
+    use crate::compiler::Compiler;
+
+    const GENERICS: &str = "
+func pick<T>(a: T, b: T) T { return a; }
+
+class Box<T> {
+    public let value: T;
+    public func constructor(self, value: T) { self.value = value; }
+    public func get(self) T { return self.value; }
+}
+";
+
+    /// The paths of the functions, methods and classes generated for `main`.
+    fn items(main: &str) -> Vec<String> {
+        let (_, result) = Compiler::compile_source(&format!("{}\nfunc main() i32 {{\n{}\nreturn 0;\n}}\n", GENERICS, main));
+        let Ok(modules) = result else {
+            panic!("the source should compile");
+        };
+        let mut items = Vec::new();
+        for node in top_nodes(&modules) {
+            match node.get_kind() {
+                AST::FuncDef( path, .. ) => items.push(path.clone()),
+                AST::ClassDef( path, _, _, _, methods, .. ) => {
+                    items.push(path.clone());
+                    items.extend(methods.iter().map(|method| match method.get_kind() {
+                        AST::FuncDef( path, .. ) => path.clone(),
+                        _ => panic!("Expected FuncDef"),
+                    }));
+                }
+                _ => {}
+            }
+        }
+        items
+    }
+
+    fn count(items: &[String], path: &str) -> usize {
+        items.iter().filter(|item| *item == path).count()
+    }
+
+    #[test]
+    fn templates_are_not_generated() {
+        let items = items("");
+        assert!(!items.iter().any(|item| item.starts_with("main::pick") || item.starts_with("main::Box")), "{:?}", items);
+    }
+
+    #[test]
+    fn same_instantiation_is_generated_once() {
+        let items = items("
+            pick(1, 2);
+            pick<i32>(3, 4);
+            pick(pick(5, 6), 7);
+            let a = new Box<i32>(1);
+            let b: Box<i32> = new Box(2);
+            a.get();
+            b.get();
+        ");
+        assert_eq!(count(&items, "main::pick<i32>"), 1, "{:?}", items);
+        assert_eq!(count(&items, "main::Box<i32>"), 1, "{:?}", items);
+        assert_eq!(count(&items, "main::Box<i32>::get"), 1, "{:?}", items);
+        assert_eq!(count(&items, "main::Box<i32>::constructor"), 1, "{:?}", items);
+    }
+
+    #[test]
+    fn different_type_arguments_get_distinct_names() {
+        let items = items("
+            pick(1, 2);
+            pick(true, false);
+            pick(\"a\", \"b\");
+            pick(new Box(1), new Box(2));
+            new Box<i32>(1).get();
+            new Box<String>(\"a\").get();
+            new Box<Box<i32>>(new Box(1)).get();
+        ");
+        let expected = [
+            "main::pick<i32>",
+            "main::pick<bool>",
+            "main::pick<String>",
+            "main::pick<main::Box<i32>>",
+            "main::Box<i32>",
+            "main::Box<String>",
+            "main::Box<main::Box<i32>>",
+            "main::Box<i32>::get",
+            "main::Box<String>::get",
+            "main::Box<main::Box<i32>>::get",
+        ];
+        for path in expected {
+            assert_eq!(count(&items, path), 1, "'{}' in {:?}", path, items);
+        }
+        let symbols = expected.iter().map(|path| mangle(path)).collect::<HashSet<_>>();
+        assert_eq!(symbols.len(), expected.len(), "{:?}", symbols);
+    }
+}
@@ -1,8 +1,6 @@
-use std::collections::HashMap;
-
-use crate::ast::nodes::{AstType, GenericDecl, Node, AST};
+use crate::ast::nodes::{AstType, BinaryOp, ClassMember, EnumVariant, GenericDecl, MatchArm, Node, Param, Pattern, PatternKind, VariantKind, AST};
 use crate::frontend::lexer::token::{Token, TokenType};
-use crate::ast::attrs::{AstAttrs, AttrHandler, ExternalLinkage};
+use crate::ast::attrs::{AstAttrs, AttrHandler, ExternalLinkage, Linkage};
 use crate::reports::{CompileError, Error, ErrorInfo, Reports};
 use crate::ast::source::SourceLocation;
 use crate::frontend::module::Module;
@@ -15,7 +13,40 @@ pub struct Parser {
     token_index: usize,
     token: Token,
     reports: Reports,
-    module: Module<Node>
+    module: Module<Node>,
+    /// Attributes (`@[...]`) waiting for the item they are written before.
+    attributes: Vec<(String, AstAttrs, SourceLocation)>,
+    /// Whether `Path { field: value }` is a variant literal. It is turned off
+    /// where a `{` starts a block instead, e.g. after the condition of an `if`.
+    struct_literals: bool,
+    /// How many `namespace` blocks the parser is inside of.
+    namespace_depth: usize,
+}
+
+/// The items an attribute can be written before.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttrTarget {
+    Function,
+    Class,
+    Enum,
+    Interface,
+    Namespace,
+    Method,
+    Member,
+}
+
+impl std::fmt::Display for AttrTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            AttrTarget::Function => "functions",
+            AttrTarget::Class => "classes",
+            AttrTarget::Enum => "enums",
+            AttrTarget::Interface => "interfaces",
+            AttrTarget::Namespace => "namespaces",
+            AttrTarget::Method => "methods",
+            AttrTarget::Member => "class members",
+        })
+    }
 }
 
 macro_rules! report {
@@ -59,7 +90,10 @@ impl Parser {
             token_index: 0,
             token: l.get_tokens()[0].clone(),
             reports: Reports::new(),
-            module: Module::<Node>::new(path, Some(file_name))
+            module: Module::<Node>::new(path, Some(file_name)),
+            attributes: Vec::new(),
+            struct_literals: true,
+            namespace_depth: 0,
         }
     }
 
@@ -83,7 +117,9 @@ impl Parser {
             TokenType::External |
             TokenType::Abstract |
             TokenType::Final |
-            TokenType::Interface => Ok(()),
+            TokenType::At |
+            TokenType::Interface |
+            TokenType::Namespace => Ok(()),
             _ => report!(self, Error::ExpectedItem("global item".to_string(), after), ErrorInfo {
                 help: Some("There are only a few items that can be declared at the global scope".to_string()),
                 see: Some("https://snowball-lang.gitbook.io/docs/language-reference/global-scope".to_string()),
@@ -92,121 +128,710 @@ impl Parser {
         }
     }
 
+    /// Parses global items until `terminator`. An item that fails to parse is
+    /// replaced by an error node and the parser skips to the next item, so
+    /// every syntax error of the file is reported at once.
     pub fn parse_global(&mut self, terminator: TokenType) -> Result<Vec<Node>, ()> {
         let mut attrs = AttrHandler::new();
         let mut nodes = Vec::new();
         while *self.token.get_type() != terminator {
-            match self.token.get_type() {
-                TokenType::EOF => report!(self, Error::UnexpectedEOF),
-                TokenType::Public => {
-                    self.next();
-                    attrs.add_attr(AstAttrs::Privacy(true));
-                    self.assert_global_item_next("public".to_string())?;
-                } 
-                TokenType::Private => {
-                    self.next();
-                    attrs.add_attr(AstAttrs::Privacy(false));
-                    self.assert_global_item_next("private".to_string())?;
+            if *self.token.get_type() == TokenType::EOF {
+                report!(self, Error::UnexpectedEOF);
+            }
+            let start = self.token_index;
+            let pos = self.token.get_location();
+            if self.parse_global_item(&mut attrs, &mut nodes).is_err() {
+                attrs.clear();
+                self.attributes.clear();
+                nodes.push(Node::new(AST::Error).with_location(pos));
+                self.synchronize(start, &terminator, Self::starts_global_item);
+            }
+        }
+        if let Some((name, _, location)) = self.attributes.pop() {
+            self.attributes.clear();
+            self.reports.add_error(CompileError::new(Error::ExpectedItemAfter("item".to_string(), format!("@[{}]", name), self.token.value()), location));
+        }
+        Ok(nodes)
+    }
+
+    fn parse_global_item(&mut self, attrs: &mut AttrHandler, nodes: &mut Vec<Node>) -> Result<(), ()> {
+        match self.token.get_type() {
+            TokenType::Public => {
+                self.next();
+                attrs.add_attr(AstAttrs::Privacy(true));
+                self.assert_global_item_next("public".to_string())?;
+            } 
+            TokenType::Private => {
+                self.next();
+                attrs.add_attr(AstAttrs::Privacy(false));
+                self.assert_global_item_next("private".to_string())?;
+            }
+            TokenType::Static => {
+                self.next();
+                attrs.add_attr(AstAttrs::Static);
+                match self.token.get_type() {
+                    TokenType::Fn |
+                    TokenType::Const |
+                    TokenType::External => {}
+                    _ => report!(self, Error::ExpectedItem("function, constant, or external".to_string(), "static".to_string()), ErrorInfo {
+                        help: Some("The 'static' keyword can only be used with functions, constants, or externs".to_string()),
+                        note: Some("Static functions are used to specify that a function is a class method".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/static-functions".to_string()),
+                        ..Default::default()
+                    })
                 }
-                TokenType::Static => {
-                    self.next();
-                    attrs.add_attr(AstAttrs::Static);
-                    match self.token.get_type() {
-                        TokenType::Fn |
-                        TokenType::Const |
-                        TokenType::External => {}
-                        _ => report!(self, Error::ExpectedItem("function, constant, or external".to_string(), "static".to_string()), ErrorInfo {
-                            help: Some("The 'static' keyword can only be used with functions, constants, or externs".to_string()),
-                            note: Some("Static functions are used to specify that a function is a class method".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/static-functions".to_string()),
-                            ..Default::default()
-                        })
+            }
+            TokenType::Inline => {
+                self.next();
+                attrs.add_attr(AstAttrs::Inline);
+                match self.token.get_type() {
+                    TokenType::Fn => {}
+                    _ => report!(self, Error::ExpectedItem("function".to_string(), "inline".to_string()), ErrorInfo {
+                        help: Some("The 'inline' keyword can only be used with functions".to_string()),
+                        note: Some("Inline functions are used to specify that a function should be inlined by the compiler".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/inline-functions".to_string()),
+                        ..Default::default()
+                    }),
+                }
+            }
+            TokenType::External => {
+                self.next();
+                match self.token.get_type() {
+                    TokenType::String(data) => {
+                        match data.as_str() {
+                            "C" => attrs.add_attr(AstAttrs::External(ExternalLinkage::C)),
+                            "snowball" => attrs.add_attr(AstAttrs::External(ExternalLinkage::Snowball)),
+                            "system" => attrs.add_attr(AstAttrs::External(ExternalLinkage::System)),
+                            _ => report!(self, Error::InvalidExternalSpecifier(data.clone()), ErrorInfo {
+                                help: Some("The external specifier must be one of the following: 'C', 'snowball', 'system'".to_string()),
+                                info: Some("Not a valid external specifier!".to_string()),
+                                note: Some("External specifiers are used to specify the data that is being imported from an external source".to_string()),
+                                see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-specifier".to_string()),
+                                ..Default::default()
+                            }),
+                        }
+                        self.next();
                     }
+                    _ => report!(self, Error::ExpectedItem("external specifier".to_string(), "external".to_string()), ErrorInfo {
+                        help: Some("The external specifier must be a string literal".to_string()),
+                        note: Some("External specifiers are used to specify the data that is being imported from an external source".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-specifier".to_string()),
+                        ..Default::default()
+                    }),
+                }
+                match self.token.get_type() {
+                    TokenType::Fn => {}
+                    _ => report!(self, Error::ExpectedItem("function".to_string(), "external".to_string()), ErrorInfo {
+                        help: Some("The 'external' keyword can only be used with functions".to_string()),
+                        note: Some("External functions are used to specify that a function is being imported from an external source".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-functions".to_string()),
+                        ..Default::default()
+                    }),
+                }
+            }
+            TokenType::Abstract => {
+                self.next();
+                attrs.add_attr(AstAttrs::Abstract);
+                match self.token.get_type() {
+                    TokenType::Class => {}
+                    _ => report!(self, Error::ExpectedItem("class".to_string(), "abstract".to_string()), ErrorInfo {
+                        help: Some("The 'abstract' keyword can only be used with classes".to_string()),
+                        note: Some("Abstract classes are used to specify that a class cannot be instantiated".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/abstract-classes".to_string()),
+                        ..Default::default()
+                    }),
+                }
+            }
+            TokenType::Final => {
+                self.next();
+                attrs.add_attr(AstAttrs::Final);
+                match self.token.get_type() {
+                    TokenType::Class => {}
+                    _ => report!(self, Error::ExpectedItem("class".to_string(), "final".to_string()), ErrorInfo {
+                        help: Some("The 'final' keyword can only be used with classes".to_string()),
+                        note: Some("Final classes are used to specify that a class cannot be inherited from".to_string()),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/final-classes".to_string()),
+                        ..Default::default()
+                    }),
+                }
+            }
+            TokenType::At => self.parse_attributes()?,
+            TokenType::Fn => {
+                self.apply_attributes(attrs, AttrTarget::Function);
+                nodes.push(self.parse_function(attrs.clone(), None)?);
+                attrs.clear();
+            }
+            TokenType::Class => {
+                self.apply_attributes(attrs, AttrTarget::Class);
+                nodes.push(self.parse_class(attrs.clone())?);
+                attrs.clear();
+            }
+            TokenType::Enum => {
+                self.apply_attributes(attrs, AttrTarget::Enum);
+                nodes.push(self.parse_enum(attrs.clone())?);
+                attrs.clear();
+            }
+            TokenType::Interface => {
+                self.apply_attributes(attrs, AttrTarget::Interface);
+                nodes.push(self.parse_interface(attrs.clone())?);
+                attrs.clear();
+            }
+            TokenType::Namespace => {
+                if !attrs.get_attrs().is_empty() {
+                    report!(self, Error::ExpectedItem("global item".to_string(), "namespace".to_string()), ErrorInfo {
+                        help: Some("Namespaces cannot have modifiers, only the items declared in them can be public or private".to_string()),
+                        ..Default::default()
+                    });
+                }
+                self.apply_attributes(attrs, AttrTarget::Namespace);
+                nodes.push(self.parse_namespace(attrs.clone())?);
+                attrs.clear();
+            }
+            TokenType::Import => {
+                if let Some((name, _, location)) = self.attributes.first().cloned() {
+                    self.attributes.clear();
+                    self.reports.add_error(CompileError::new(Error::MisplacedAttribute(name, "imports".to_string()), location));
                 }
-                TokenType::Inline => {
+                if !attrs.get_attrs().is_empty() {
+                    report!(self, Error::ExpectedItem("global item".to_string(), "import".to_string()), ErrorInfo {
+                        help: Some("Imports cannot have modifiers, only the imported items can be public or private".to_string()),
+                        ..Default::default()
+                    });
+                }
+                if self.namespace_depth > 0 {
+                    report!(self, Error::UnexpectedItem("global item".to_string(), "import".to_string()), ErrorInfo {
+                        help: Some("Imports can only be written at the top level of a file, outside of any namespace".to_string()),
+                        ..Default::default()
+                    });
+                }
+                nodes.extend(self.parse_import()?);
+            }
+            _ => report!(self, Error::UnexpectedToken(self.token.value())),
+        }
+        Ok(())
+    }
+
+    /// Parses a list of attributes (`@[test, cfg(unix)]`). They are applied
+    /// to the item that follows by `apply_attributes`.
+    fn parse_attributes(&mut self) -> Result<(), ()> {
+        debug_assert!(*self.token.get_type() == TokenType::At);
+        self.next();
+        consume_token!(self, TokenType::OpenBracket, "@");
+        loop {
+            let location = self.token.get_location();
+            let name = match self.token.get_type() {
+                TokenType::Identifier(name) => name.clone(),
+                // keywords that are attributes too
+                TokenType::Inline => "inline".to_string(),
+                _ => report!(self, Error::ExpectedItemAfter("attribute name".to_string(), "@[".to_string(), self.token.value())),
+            };
+            self.next();
+            let args = match self.token.get_type() {
+                TokenType::OpenParen => Some(self.parse_attribute_arguments()?),
+                _ => None,
+            };
+            let location = location.span_to(&self.prev_location());
+            let attr = self.attribute(&name, args, &location)?;
+            self.attributes.push((name, attr, location));
+            match self.token.get_type() {
+                TokenType::Comma => self.next(),
+                TokenType::CloseBracket => {
                     self.next();
-                    attrs.add_attr(AstAttrs::Inline);
-                    match self.token.get_type() {
-                        TokenType::Fn => {}
-                        _ => report!(self, Error::ExpectedItem("function".to_string(), "inline".to_string()), ErrorInfo {
-                            help: Some("The 'inline' keyword can only be used with functions".to_string()),
-                            note: Some("Inline functions are used to specify that a function should be inlined by the compiler".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/inline-functions".to_string()),
+                    return Ok(());
+                }
+                _ => report!(self, Error::ExpectedItemAfter("]".to_string(), "attributes".to_string(), self.token.value())),
+            }
+        }
+    }
+
+    /// The tokens between the parentheses after the name of an attribute.
+    fn parse_attribute_arguments(&mut self) -> Result<Vec<Token>, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::OpenParen);
+        self.next();
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+        loop {
+            match self.token.get_type() {
+                TokenType::EOF => report!(self, Error::UnexpectedEOF),
+                TokenType::CloseParen if depth == 0 => break,
+                TokenType::OpenParen => depth += 1,
+                TokenType::CloseParen => depth -= 1,
+                _ => {}
+            }
+            tokens.push(self.token.clone());
+            self.next();
+        }
+        self.next();
+        Ok(tokens)
+    }
+
+    /// Turns an attribute into its `AstAttrs` variant, checking its arguments.
+    fn attribute(&mut self, name: &str, args: Option<Vec<Token>>, location: &SourceLocation) -> Result<AstAttrs, ()> {
+        let invalid = |this: &mut Self, expected: &str| {
+            this.reports.add_error(CompileError::new(Error::InvalidAttributeArguments(name.to_string(), expected.to_string()), location.clone()));
+            Err(())
+        };
+        // the single string argument of an attribute, if it has one
+        let string = match args.as_deref() {
+            Some([token]) => match token.get_type() {
+                TokenType::String(value) => Some(Some(value.clone())),
+                _ => None,
+            },
+            Some(_) => None,
+            None => Some(None),
+        };
+        let attr = match name {
+            "cfg" => match args {
+                Some(args) if !args.is_empty() => AstAttrs::Cfg(Self::tokens_to_string(&args)),
+                _ => return invalid(self, "it expects a condition, e.g. 'cfg(target_os = \"linux\")'"),
+            },
+            "deprecated" | "unstable" => match string {
+                Some(message) if name == "deprecated" => AstAttrs::Deprecated(message.unwrap_or_default()),
+                Some(message) => AstAttrs::Unstable(message.unwrap_or_default()),
+                None => return invalid(self, "it expects an optional message, e.g. 'deprecated(\"use 'x' instead\")'"),
+            },
+            "linkage" => match string.flatten().as_deref() {
+                Some("internal") => AstAttrs::Linkage(Linkage::Internal),
+                Some("external") => AstAttrs::Linkage(Linkage::External),
+                _ => return invalid(self, "it expects either \"internal\" or \"external\""),
+            },
+            _ => {
+                let attr = match name {
+                    "test" => AstAttrs::Test,
+                    "bench" => AstAttrs::Benchmark,
+                    "inline" => AstAttrs::Inline,
+                    "no_inline" => AstAttrs::NoInline,
+                    "no_optimize" => AstAttrs::NoOptimize,
+                    "no_mangle" => AstAttrs::NoMangle,
+                    "export" => AstAttrs::Export,
+                    "builtin" => AstAttrs::BuiltIn,
+                    "unsafe" => AstAttrs::Unsafe,
+                    "llvm_function" => AstAttrs::LLVMFunct,
+                    "not_implemented" => AstAttrs::NotImplemented,
+                    "no_constructors" => AstAttrs::NoConstructors,
+                    _ => {
+                        self.reports.add_error(CompileError::new(Error::UnknownAttribute(name.to_string()), location.clone()).with_info(ErrorInfo {
+                            help: Some("See 'snowball explain E0044' for the list of attributes.".to_string()),
                             ..Default::default()
-                        }),
+                        }));
+                        return Err(());
                     }
+                };
+                if args.is_some() {
+                    return invalid(self, "it does not take any arguments");
                 }
-                TokenType::External => {
+                attr
+            }
+        };
+        Ok(attr)
+    }
+
+    /// The items an attribute can be written before.
+    fn attribute_targets(attr: &AstAttrs) -> &'static [AttrTarget] {
+        match attr {
+            AstAttrs::Test | AstAttrs::Benchmark | AstAttrs::NoMangle | AstAttrs::Export | AstAttrs::Linkage(_) => &[AttrTarget::Function],
+            AstAttrs::Inline | AstAttrs::NoInline | AstAttrs::NoOptimize | AstAttrs::Unsafe |
+            AstAttrs::LLVMFunct | AstAttrs::NotImplemented => &[AttrTarget::Function, AttrTarget::Method],
+            AstAttrs::BuiltIn => &[AttrTarget::Function, AttrTarget::Class],
+            AstAttrs::NoConstructors => &[AttrTarget::Class],
+            AstAttrs::Cfg(_) => &[AttrTarget::Function, AttrTarget::Class, AttrTarget::Enum, AttrTarget::Interface, AttrTarget::Namespace, AttrTarget::Method, AttrTarget::Member],
+            _ => &[AttrTarget::Function, AttrTarget::Class, AttrTarget::Enum, AttrTarget::Interface, AttrTarget::Method, AttrTarget::Member],
+        }
+    }
+
+    /// Moves the pending attributes to the item that is being parsed, reporting
+    /// the ones that cannot be used on it.
+    fn apply_attributes(&mut self, attrs: &mut AttrHandler, target: AttrTarget) {
+        for (name, attr, location) in std::mem::take(&mut self.attributes) {
+            let targets = Self::attribute_targets(&attr);
+            if !targets.contains(&target) {
+                let allowed = targets.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" and ");
+                self.reports.add_error(CompileError::new(Error::MisplacedAttribute(name.clone(), target.to_string()), location).with_info(ErrorInfo {
+                    help: Some(format!("'{}' can only be used on {}", name, allowed)),
+                    ..Default::default()
+                }));
+                continue;
+            }
+            attrs.add_attr(attr);
+        }
+    }
+
+    /// Turns the arguments of an attribute back into source code (e.g. `target_os = "linux"`).
+    fn tokens_to_string(tokens: &[Token]) -> String {
+        let mut result = String::new();
+        for token in tokens {
+            match token.get_type() {
+                TokenType::Equal => result.push_str(" = "),
+                TokenType::Comma => result.push_str(", "),
+                _ => result.push_str(&token.value()),
+            }
+        }
+        result
+    }
+
+    /// Whether a token can only appear at the start of a global item.
+    fn starts_global_item(token: &TokenType) -> bool {
+        matches!(token, TokenType::Fn | TokenType::Class | TokenType::Struct | TokenType::Enum | TokenType::Interface |
+            TokenType::Namespace | TokenType::Import | TokenType::Public | TokenType::Private | TokenType::External | TokenType::Abstract | TokenType::Final | TokenType::At)
+    }
+
+    /// Whether a token starts a statement, or a global item after a block
+    /// that is missing its `}`.
+    fn starts_statement(token: &TokenType) -> bool {
+        matches!(token, TokenType::Let | TokenType::Return | TokenType::If | TokenType::While | TokenType::For |
+            TokenType::Do | TokenType::Match | TokenType::Break | TokenType::Continue) || Self::starts_global_item(token)
+    }
+
+    /// Skips the tokens of a construct that failed to parse. Stops after a `;`
+    /// or a closed block, or before `terminator` or a token accepted by
+    /// `is_start` outside of any nested braces. At least one token is skipped if the construct that
+    /// started at `start` consumed none, so the caller always makes progress.
+    fn synchronize(&mut self, start: usize, terminator: &TokenType, is_start: fn(&TokenType) -> bool) {
+        let mut depth = 0usize;
+        loop {
+            let token = self.token.get_type().clone();
+            if token == TokenType::EOF {
+                return;
+            }
+            if depth == 0 && self.token_index > start && (token == *terminator || is_start(&token)) {
+                return;
+            }
+            self.next();
+            match token {
+                TokenType::OpenBrace => depth += 1,
+                // a nested block was closed (e.g. the body of an `if`), which ends the construct
+                TokenType::CloseBrace if depth == 1 => return,
+                TokenType::CloseBrace => depth = depth.saturating_sub(1),
+                TokenType::Semicolon if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Parses an import statement. Selective imports (`import a::b::{c, d as e};`)
+    /// are turned into one import node for each of the imported items.
+    pub fn parse_import(&mut self) -> Result<Vec<Node>, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Import);
+        self.next();
+        let mut path = NamespacePath::new(Vec::new());
+        let mut imports = Vec::new();
+        let start = self.token.get_location();
+        loop {
+            assert_token!(self, TokenType::Identifier("module name".to_string()), "import");
+            path.push(self.token.value());
+            self.next();
+            if *self.token.get_type() != TokenType::DoubleColon {
+                let alias = self.parse_import_alias()?;
+                imports.push(Node::new(AST::Import(path, alias)).with_location(start.span_to(&self.prev_location())));
+                break;
+            }
+            self.next();
+            if *self.token.get_type() == TokenType::OpenBrace {
+                self.next();
+                while *self.token.get_type() != TokenType::CloseBrace {
+                    assert_token!(self, TokenType::Identifier("item name".to_string()), "import");
+                    let item_start = self.token.get_location();
+                    let mut item = path.clone();
+                    item.push(self.token.value());
                     self.next();
+                    let alias = self.parse_import_alias()?;
+                    imports.push(Node::new(AST::Import(item, alias)).with_location(item_start.span_to(&self.prev_location())));
                     match self.token.get_type() {
-                        TokenType::String(data) => {
-                            match data.as_str() {
-                                "C" => attrs.add_attr(AstAttrs::External(ExternalLinkage::C)),
-                                "snowball" => attrs.add_attr(AstAttrs::External(ExternalLinkage::Snowball)),
-                                "system" => attrs.add_attr(AstAttrs::External(ExternalLinkage::System)),
-                                _ => report!(self, Error::InvalidExternalSpecifier(data.clone()), ErrorInfo {
-                                    help: Some("The external specifier must be one of the following: 'C', 'snowball', 'system'".to_string()),
-                                    info: Some("Not a valid external specifier!".to_string()),
-                                    note: Some("External specifiers are used to specify the data that is being imported from an external source".to_string()),
-                                    see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-specifier".to_string()),
-                                    ..Default::default()
-                                }),
-                            }
-                            self.next();
-                        }
-                        _ => report!(self, Error::ExpectedItem("external specifier".to_string(), "external".to_string()), ErrorInfo {
-                            help: Some("The external specifier must be a string literal".to_string()),
-                            note: Some("External specifiers are used to specify the data that is being imported from an external source".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-specifier".to_string()),
-                            ..Default::default()
-                        }),
+                        TokenType::Comma => self.next(),
+                        TokenType::CloseBrace => {}
+                        _ => report!(self, Error::ExpectedItemAfter("}".to_string(), "imported items".to_string(), self.token.value())),
                     }
-                    match self.token.get_type() {
-                        TokenType::Fn => {}
-                        _ => report!(self, Error::ExpectedItem("function".to_string(), "external".to_string()), ErrorInfo {
-                            help: Some("The 'external' keyword can only be used with functions".to_string()),
-                            note: Some("External functions are used to specify that a function is being imported from an external source".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/external-functions".to_string()),
-                            ..Default::default()
-                        }),
+                }
+                self.next();
+                break;
+            }
+        }
+        consume_token!(self, TokenType::Semicolon, "import");
+        Ok(imports)
+    }
+
+    fn parse_import_alias(&mut self) -> Result<Option<String>, ()> {
+        if *self.token.get_type() != TokenType::As {
+            return Ok(None);
+        }
+        self.next();
+        assert_token!(self, TokenType::Identifier("alias".to_string()), "as");
+        let alias = self.token.value();
+        self.next();
+        Ok(Some(alias))
+    }
+
+    /// Parses a namespace block, e.g. `namespace a::b { ... }`. The name is
+    /// kept as the path it is written as (an identifier or a `::` access).
+    pub fn parse_namespace(&mut self, attrs: AttrHandler) -> Result<Node, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Namespace);
+        self.next();
+        let start = self.token.get_location();
+        let mut name: Option<Node> = None;
+        loop {
+            assert_token!(self, TokenType::Identifier("namespace name".to_string()), "namespace");
+            let segment = self.token.value();
+            let location = start.span_to(&self.token.get_location());
+            self.next();
+            name = Some(Node::new(match name {
+                Some(base) => AST::NamespaceAccess(base, segment, None),
+                None => AST::Ident(segment, None),
+            }).with_location(location));
+            if *self.token.get_type() != TokenType::DoubleColon {
+                break;
+            }
+            self.next();
+        }
+        consume_token!(self, TokenType::OpenBrace, "namespace name");
+        self.namespace_depth += 1;
+        let nodes = self.parse_global(TokenType::CloseBrace);
+        self.namespace_depth -= 1;
+        let nodes = nodes?;
+        self.next();
+        let name = name.unwrap();
+        let pos = name.get_location().unwrap().clone();
+        Ok(Node::new(AST::NamespaceDef(Some(name), nodes)).with_attrs(attrs).clone().with_location(pos))
+    }
+
+    pub fn parse_class(&mut self, attrs: AttrHandler) -> Result<Node, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Class);
+        self.next();
+        assert_token!(self, TokenType::Identifier("class name".to_string()), "class");
+        let pos = self.token.get_location();
+        let name = self.token.value();
+        self.next();
+        let generics = self.parse_generic_args_if_present()?.unwrap_or_default();
+        // `class A: Parent, Interface, ...`, the type checker finds out whether
+        // the first type is the parent class or an interface too.
+        let mut parent = None;
+        let mut interfaces = Vec::new();
+        if *self.token.get_type() == TokenType::Colon {
+            self.next();
+            parent = Some(self.parse_type()?);
+            while *self.token.get_type() == TokenType::Comma {
+                self.next();
+                interfaces.push(self.parse_type()?);
+            }
+        }
+        consume_token!(self, TokenType::OpenBrace, "class declaration");
+        // the type of 'self' inside of the methods, generic classes refer to themselves
+        // with their own generic parameters (e.g. 'self' is 'Vec<T>' inside of 'Vec<T>').
+        let self_generics = generics.iter()
+            .map(|g| AstType::new(Node::new(AST::Ident(g.get_name().clone(), None)).with_location(pos.clone())))
+            .collect::<Vec<_>>();
+        let self_ty = AstType::new(Node::new(AST::Ident(name.clone(), if self_generics.is_empty() { None } else { Some(self_generics) })).with_location(pos.clone()));
+        let mut members = Vec::new();
+        let mut methods = Vec::new();
+        let mut member_attrs = AttrHandler::new();
+        let mut failed = false;
+        // a member that fails to parse is skipped, so the rest of the class is
+        // still declared and its methods aren't mistaken for global functions.
+        while !matches!(self.token.get_type(), TokenType::CloseBrace | TokenType::EOF) {
+            let start = self.token_index;
+            if self.parse_class_item(&self_ty, &mut member_attrs, &mut members, &mut methods).is_err() {
+                failed = true;
+                member_attrs.clear();
+                self.attributes.clear();
+                // a global item right away means the class is missing its `}`
+                if self.token_index == start && !Self::starts_class_item(self.token.get_type()) && Self::starts_global_item(self.token.get_type()) {
+                    break;
+                }
+                self.synchronize(start, &TokenType::CloseBrace, |token| Self::starts_class_item(token) || Self::starts_global_item(token));
+            }
+        }
+        match self.token.get_type() {
+            TokenType::CloseBrace => self.next(),
+            TokenType::EOF if !failed => report!(self, Error::UnexpectedEOF),
+            _ => {}
+        }
+        if let Some((name, _, location)) = self.attributes.pop() {
+            self.attributes.clear();
+            self.reports.add_error(CompileError::new(Error::ExpectedItemAfter("member or method".to_string(), format!("@[{}]", name), "}".to_string()), location));
+        }
+        Ok(Node::new(AST::ClassDef(name, parent, interfaces, members, methods, generics, None)).with_attrs(attrs).clone().with_location(pos))
+    }
+
+    /// Parses a member, a method or the attributes and keywords in front of them.
+    fn parse_class_item(&mut self, self_ty: &AstType, member_attrs: &mut AttrHandler, members: &mut Vec<ClassMember>, methods: &mut Vec<Node>) -> Result<(), ()> {
+        match self.token.get_type() {
+            TokenType::Public => {
+                self.next();
+                member_attrs.add_attr(AstAttrs::Privacy(true));
+            }
+            TokenType::Private => {
+                self.next();
+                member_attrs.add_attr(AstAttrs::Privacy(false));
+            }
+            TokenType::Static |
+            TokenType::Override |
+            TokenType::Abstract |
+            TokenType::Inline => {
+                let keyword = self.token.value();
+                member_attrs.add_attr(match self.token.get_type() {
+                    TokenType::Static => AstAttrs::Static,
+                    TokenType::Override => AstAttrs::Override,
+                    TokenType::Abstract => AstAttrs::Abstract,
+                    _ => AstAttrs::Inline,
+                });
+                self.next();
+                if !matches!(self.token.get_type(), TokenType::Fn | TokenType::Static | TokenType::Override | TokenType::Abstract | TokenType::Inline | TokenType::At) {
+                    report!(self, Error::ExpectedItem("method".to_string(), keyword.clone()), ErrorInfo {
+                        help: Some(format!("The '{}' keyword can only be used with methods", keyword)),
+                        see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes".to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+            TokenType::At => self.parse_attributes()?,
+            TokenType::Let => {
+                self.apply_attributes(member_attrs, AttrTarget::Member);
+                self.next();
+                assert_token!(self, TokenType::Identifier("member name".to_string()), "let");
+                let member = self.token.value();
+                self.next();
+                consume_token!(self, TokenType::Colon, "class member");
+                let ty = self.parse_type()?;
+                consume_token!(self, TokenType::Semicolon, "class member");
+                members.push(ClassMember::new(member, ty, member_attrs.clone()));
+                member_attrs.clear();
+            }
+            TokenType::Fn => {
+                self.apply_attributes(member_attrs, AttrTarget::Method);
+                methods.push(self.parse_function(member_attrs.clone(), Some(self_ty))?);
+                member_attrs.clear();
+            }
+            _ => report!(self, Error::UnexpectedToken(self.token.value()), ErrorInfo {
+                help: Some("Classes can only contain members ('let') and methods ('func')".to_string()),
+                see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes".to_string()),
+                ..Default::default()
+            }),
+        }
+        Ok(())
+    }
+
+    /// Whether a token starts a member or a method of a class.
+    fn starts_class_item(token: &TokenType) -> bool {
+        matches!(token, TokenType::Let | TokenType::Fn | TokenType::Public | TokenType::Private | TokenType::Static |
+            TokenType::Override | TokenType::Abstract | TokenType::Inline | TokenType::At)
+    }
+
+    /// Parses an interface declaration, e.g. `interface Show<T> { func show(self) T; }`.
+    /// Its methods are public signatures without a body.
+    pub fn parse_interface(&mut self, attrs: AttrHandler) -> Result<Node, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Interface);
+        self.next();
+        assert_token!(self, TokenType::Identifier("interface name".to_string()), "interface");
+        let pos = self.token.get_location();
+        let name = self.token.value();
+        self.next();
+        let generics = self.parse_generic_args_if_present()?.unwrap_or_default();
+        consume_token!(self, TokenType::OpenBrace, "interface declaration");
+        let self_generics = generics.iter()
+            .map(|g| AstType::new(Node::new(AST::Ident(g.get_name().clone(), None)).with_location(pos.clone())))
+            .collect::<Vec<_>>();
+        let self_ty = AstType::new(Node::new(AST::Ident(name.clone(), if self_generics.is_empty() { None } else { Some(self_generics) })).with_location(pos.clone()));
+        let mut methods = Vec::new();
+        let mut method_attrs = AttrHandler::new();
+        while *self.token.get_type() != TokenType::CloseBrace {
+            match self.token.get_type() {
+                TokenType::EOF => report!(self, Error::UnexpectedEOF),
+                TokenType::At => self.parse_attributes()?,
+                TokenType::Fn => {
+                    self.apply_attributes(&mut method_attrs, AttrTarget::Method);
+                    method_attrs.add_attr(AstAttrs::Privacy(true));
+                    method_attrs.add_attr(AstAttrs::Abstract);
+                    let method = self.parse_function(method_attrs.clone(), Some(&self_ty))?;
+                    if let AST::FuncDef( _, _, _, Some(body), .. ) = method.get_kind() {
+                        self.reports.add_error(CompileError::new(Error::UnexpectedItem(";".to_string(), "method body".to_string()), body.get_location().unwrap().clone())
+                            .with_info(ErrorInfo {
+                                help: Some("Interfaces only declare the signatures of their methods, the classes implementing them give the bodies".to_string()),
+                                ..Default::default()
+                            }));
+                        return Err(());
                     }
+                    methods.push(method);
+                    method_attrs.clear();
                 }
-                TokenType::Abstract => {
+                _ => report!(self, Error::UnexpectedToken(self.token.value()), ErrorInfo {
+                    help: Some("Interfaces can only contain method signatures ('func name(self) type;')".to_string()),
+                    ..Default::default()
+                }),
+            }
+        }
+        if let Some((name, _, location)) = self.attributes.pop() {
+            self.attributes.clear();
+            self.reports.add_error(CompileError::new(Error::ExpectedItemAfter("method".to_string(), format!("@[{}]", name), "}".to_string()), location));
+        }
+        self.next();
+        Ok(Node::new(AST::InterfaceDef(name, methods, generics, None)).with_attrs(attrs).clone().with_location(pos))
+    }
+
+    /// Parses an enum declaration, e.g. `enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }`.
+    pub fn parse_enum(&mut self, attrs: AttrHandler) -> Result<Node, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Enum);
+        self.next();
+        assert_token!(self, TokenType::Identifier("enum name".to_string()), "enum");
+        let pos = self.token.get_location();
+        let name = self.token.value();
+        self.next();
+        let generics = self.parse_generic_args_if_present()?.unwrap_or_default();
+        consume_token!(self, TokenType::OpenBrace, "enum declaration");
+        let mut variants = Vec::new();
+        while *self.token.get_type() != TokenType::CloseBrace {
+            assert_token!(self, TokenType::Identifier("variant name".to_string()), "enum declaration");
+            let location = self.token.get_location();
+            let variant = self.token.value();
+            self.next();
+            let (kind, fields) = match self.token.get_type() {
+                TokenType::OpenParen => {
                     self.next();
-                    attrs.add_attr(AstAttrs::Abstract);
-                    match self.token.get_type() {
-                        TokenType::Class => {}
-                        _ => report!(self, Error::ExpectedItem("class".to_string(), "abstract".to_string()), ErrorInfo {
-                            help: Some("The 'abstract' keyword can only be used with classes".to_string()),
-                            note: Some("Abstract classes are used to specify that a class cannot be instantiated".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/abstract-classes".to_string()),
-                            ..Default::default()
-                        }),
+                    let mut fields = Vec::new();
+                    while *self.token.get_type() != TokenType::CloseParen {
+                        fields.push((fields.len().to_string(), self.parse_type()?));
+                        if *self.token.get_type() != TokenType::Comma {
+                            break;
+                        }
+                        self.next();
                     }
+                    consume_token!(self, TokenType::CloseParen, "variant fields");
+                    (VariantKind::Tuple, fields)
                 }
-                TokenType::Final => {
+                TokenType::OpenBrace => {
                     self.next();
-                    attrs.add_attr(AstAttrs::Final);
-                    match self.token.get_type() {
-                        TokenType::Class => {}
-                        _ => report!(self, Error::ExpectedItem("class".to_string(), "final".to_string()), ErrorInfo {
-                            help: Some("The 'final' keyword can only be used with classes".to_string()),
-                            note: Some("Final classes are used to specify that a class cannot be inherited from".to_string()),
-                            see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/final-classes".to_string()),
-                            ..Default::default()
-                        }),
+                    let mut fields = Vec::new();
+                    while *self.token.get_type() != TokenType::CloseBrace {
+                        assert_token!(self, TokenType::Identifier("field name".to_string()), "variant fields");
+                        let field = self.token.value();
+                        self.next();
+                        consume_token!(self, TokenType::Colon, "field name");
+                        fields.push((field, self.parse_type()?));
+                        if *self.token.get_type() != TokenType::Comma {
+                            break;
+                        }
+                        self.next();
                     }
+                    consume_token!(self, TokenType::CloseBrace, "variant fields");
+                    (VariantKind::Struct, fields)
                 }
-                TokenType::Fn => {
-                    nodes.push(self.parse_function(attrs.clone())?);
-                }
-                _ => report!(self, Error::UnexpectedToken(self.token.value())),
+                _ => (VariantKind::Unit, Vec::new()),
+            };
+            variants.push(EnumVariant::new(variant, kind, fields, location));
+            match self.token.get_type() {
+                TokenType::Comma => self.next(),
+                TokenType::CloseBrace => {}
+                _ => report!(self, Error::ExpectedItemAfter("}".to_string(), "enum variants".to_string(), self.token.value()), ErrorInfo {
+                    help: Some("Variants are separated by commas".to_string()),
+                    ..Default::default()
+                }),
             }
         }
-        Ok(nodes)
+        self.next();
+        Ok(Node::new(AST::EnumDef(name, variants, generics, None)).with_attrs(attrs).clone().with_location(pos))
     }
 
-    pub fn parse_function(&mut self, attrs: AttrHandler) -> Result<Node, ()> {
+    /// Parses a function definition. Methods receive the type of their class as
+    /// `self_ty`, which allows them to take a `self` parameter without a type.
+    pub fn parse_function(&mut self, attrs: AttrHandler, self_ty: Option<&AstType>) -> Result<Node, ()> {
         debug_assert!(*self.token.get_type() == TokenType::Fn);
         self.next();
         assert_token!(self, TokenType::Identifier("function name".to_string()), "function name");
@@ -215,22 +840,46 @@ impl Parser {
         self.next();
         let generics = self.parse_generic_args_if_present()?;
         consume_token!(self, TokenType::OpenParen, "function parameters");
-        let mut params = HashMap::new();
+        let mut params: Vec<Param> = Vec::new();
         while *self.token.get_type() != TokenType::CloseParen {
+            let mutable = *self.token.get_type() == TokenType::Mut;
+            if mutable {
+                self.next();
+            }
             match self.token.get_type() {
                 TokenType::Identifier(_) => {
                     let param = self.token.value();
-                    if params.contains_key(&param) {
+                    let location = self.token.get_location();
+                    if params.iter().any(|p| *p.get_name() == param) {
                         report!(self, Error::RepeatedParameter(param.clone()), ErrorInfo {
                             info: Some(format!("The parameter '{}' has already been declared", param)),
                             help: Some("Function parameters must have unique names".to_string()),
                             ..Default::default()
                         });
                     }
+                    if param == "self" && (self_ty.is_none() || !params.is_empty()) {
+                        report!(self, Error::InvalidSelfParameter, ErrorInfo {
+                            info: Some("This is not the receiver of a method".to_string()),
+                            help: Some("Rename the parameter, or make the function a method of a class".to_string()),
+                            ..Default::default()
+                        });
+                    }
                     self.next();
-                    consume_token!(self, TokenType::Colon, "parameter separator");
-                    let ty = self.parse_type()?;
-                    params.insert(param, ty);
+                    let ty = match self_ty {
+                        Some(self_ty) if param == "self" => self_ty.clone(),
+                        _ => {
+                            consume_token!(self, TokenType::Colon, "parameter separator");
+                            self.parse_type()?
+                        }
+                    };
+                    let default = match self.token.get_type() {
+                        TokenType::Equal => {
+                            self.next();
+                            Some(self.parse_binary_expression(0)?)
+                        }
+                        _ => None,
+                    };
+                    params.push(Param::new(param, ty, default, mutable, location));
                     if *self.token.get_type() == TokenType::Comma {
                         self.next();
                     }
@@ -249,7 +898,17 @@ impl Parser {
             TokenType::Semicolon => AstType::new(Node::new(AST::Ident("void".to_string(), None)).with_location(self.token.get_location())),
             _ => self.parse_type()?,
         };
-        let body = Some(self.parse_block()?);
+        let body = match self.token.get_type() {
+            TokenType::Semicolon if attrs.get_attrs().iter().any(|a| matches!(a, AstAttrs::Abstract | AstAttrs::External(_))) => {
+                self.next();
+                None
+            }
+            TokenType::Semicolon => report!(self, Error::ExpectedItem("function body".to_string(), name), ErrorInfo {
+                help: Some("Only abstract and external functions can be declared without a body".to_string()),
+                ..Default::default()
+            }),
+            _ => Some(self.parse_block()?),
+        };
         Ok(Node::new(AST::FuncDef(name, params, ret_ty, body, generics, None)).with_attrs(attrs).clone().with_location(pos))
     }
 
@@ -258,13 +917,38 @@ impl Parser {
         let mut nodes = Vec::new();
         let pos = self.token.get_location();
         while *self.token.get_type() != TokenType::CloseBrace {
-            nodes.push(self.parse_statement()?);
+            match self.token.get_type() {
+                TokenType::EOF => report!(self, Error::UnexpectedEOF),
+                // the block is missing its '}', the enclosing item recovers from here
+                token if Self::starts_global_item(token) && *token != TokenType::At => {
+                    report!(self, Error::ExpectedItemAfter("}".to_string(), "block".to_string(), self.token.value()))
+                }
+                _ => nodes.push(self.parse_statement()?),
+            }
         }
         self.next();
         Ok(Node::new(AST::Block(nodes)).with_location(pos))
     }
 
+    /// Parses a statement. A statement that fails to parse is replaced by an
+    /// error node once the parser skipped to the next statement, only the end
+    /// of the file stops the parsing.
     pub fn parse_statement(&mut self) -> Result<Node, ()> {
+        let start = self.token_index;
+        let pos = self.token.get_location();
+        match self.parse_statement_kind() {
+            Ok(node) => Ok(node),
+            Err(()) => {
+                self.synchronize(start, &TokenType::CloseBrace, Self::starts_statement);
+                if *self.token.get_type() == TokenType::EOF {
+                    return Err(());
+                }
+                Ok(Node::new(AST::Error).with_location(pos))
+            }
+        }
+    }
+
+    fn parse_statement_kind(&mut self) -> Result<Node, ()> {
         let pos = self.token.get_location();
         match self.token.get_type() {
             TokenType::Return => {
@@ -288,14 +972,14 @@ impl Parser {
             }
             TokenType::If => {
                 self.next();
-                let cond = self.parse_expression()?;
+                let cond = self.parse_condition()?;
                 let then = self.parse_statement()?;
                 let mut els = Vec::new();
                 while *self.token.get_type() == TokenType::Else {
                     self.next();
                     if *self.token.get_type() == TokenType::If {
                         self.next();
-                        let cond = self.parse_expression()?;
+                        let cond = self.parse_condition()?;
                         let then = self.parse_statement()?;
                         els.push(Node::new(AST::If(cond, then, Vec::new())).with_location(pos.clone()));
                     } else {
@@ -303,11 +987,11 @@ impl Parser {
                         els.push(stmt);
                     }
                 }
-                Ok(Node::new(AST::If(cond, then, els)))
+                Ok(Node::new(AST::If(cond, then, els)).with_location(pos))
             }
             TokenType::While => {
                 self.next();
-                let cond = self.parse_expression()?;
+                let cond = self.parse_condition()?;
                 let body = self.parse_statement()?;
                 Ok(Node::new(AST::While(cond, body, false)).with_location(pos))
             }
@@ -321,6 +1005,8 @@ impl Parser {
             }
             TokenType::Let => {
                 self.next();
+                // like other definitions, a variable is located at its name
+                let pos = self.token.get_location();
                 let name = self.token.value();
                 self.next();
                 let ty = match self.token.get_type() {
@@ -341,6 +1027,21 @@ impl Parser {
                 Ok(Node::new(AST::VarDef(name, ty, value)).with_location(pos))
             }
             TokenType::OpenBrace => self.parse_block(),
+            // a match used as a statement does not need a ';' after its arms
+            TokenType::Match => {
+                let expr = self.parse_expression()?;
+                if *self.token.get_type() == TokenType::Semicolon {
+                    self.next();
+                }
+                Ok(expr)
+            }
+            TokenType::At => {
+                self.parse_attributes()?;
+                let (name, _, location) = self.attributes.first().cloned().unwrap();
+                self.attributes.clear();
+                self.reports.add_error(CompileError::new(Error::MisplacedAttribute(name, "statements".to_string()), location));
+                Err(())
+            }
             TokenType::Semicolon => {
                 warn!(self, Error::ExcessiveSemicolon, ErrorInfo {
                     help: Some("Excessive semicolons are unnecessary and should be removed".to_string()),
@@ -348,7 +1049,7 @@ impl Parser {
                     ..Default::default()
                 });
                 self.next();
-                Ok(Node::new(AST::Empty))
+                Ok(Node::new(AST::Empty).with_location(pos))
             }
             _ => {
                 let expr = self.parse_expression()?;
@@ -358,64 +1059,451 @@ impl Parser {
         }
     }
 
+    /// Parses the condition of an `if` or `while`, where a `{` starts the body.
+    fn parse_condition(&mut self) -> Result<Node, ()> {
+        self.with_struct_literals(false, Self::parse_expression)
+    }
+
+    fn with_struct_literals(&mut self, allowed: bool, parse: fn(&mut Self) -> Result<Node, ()>) -> Result<Node, ()> {
+        let previous = std::mem::replace(&mut self.struct_literals, allowed);
+        let result = parse(self);
+        self.struct_literals = previous;
+        result
+    }
+
     pub fn parse_expression(&mut self) -> Result<Node, ()> {
+        let lhs = self.parse_binary_expression(0)?;
+        let op = match self.token.get_type() {
+            TokenType::Equal => None,
+            TokenType::PlusEqual => Some(BinaryOp::Add),
+            TokenType::MinusEqual => Some(BinaryOp::Sub),
+            TokenType::StarEqual => Some(BinaryOp::Mul),
+            TokenType::SlashEqual => Some(BinaryOp::Div),
+            TokenType::PercentEqual => Some(BinaryOp::Mod),
+            _ => return Ok(lhs),
+        };
+        self.next();
+        // assignments are right associative: `a = b = c` is `a = (b = c)`
+        let rhs = self.parse_expression()?;
+        let location = Self::span(&lhs, &rhs);
+        Ok(Node::new(AST::Assign(lhs, rhs, op)).with_location(location))
+    }
+
+    fn parse_binary_expression(&mut self, min_precedence: u8) -> Result<Node, ()> {
+        let mut lhs = self.parse_unary_expression()?;
+        while let Some((op, precedence)) = Self::get_binary_op(self.token.get_type()) {
+            if precedence < min_precedence {
+                break;
+            }
+            self.next();
+            // every binary operator is left associative, so the right hand side
+            // may only bind operators that are strictly tighter than this one.
+            let rhs = self.parse_binary_expression(precedence + 1)?;
+            let location = Self::span(&lhs, &rhs);
+            lhs = Node::new(AST::BinaryOp(op, lhs, rhs, false)).with_location(location);
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary_expression(&mut self) -> Result<Node, ()> {
+        let op = match self.token.get_type() {
+            TokenType::Minus => BinaryOp::Sub,
+            TokenType::Exclamation => BinaryOp::Not,
+            TokenType::Tilde => BinaryOp::BitNot,
+            _ => return self.parse_postfix_expression(),
+        };
+        let pos = self.token.get_location();
+        self.next();
+        // a minus sign directly before a number is part of it, so the smallest i64 can be written
+        if let (BinaryOp::Sub, TokenType::Integer(literal)) = (&op, self.token.get_type()) {
+            if !matches!(self.tokens[self.token_index + 1].get_type(), TokenType::Dot | TokenType::OpenParen | TokenType::OpenBracket | TokenType::DoubleColon) {
+                let location = pos.span_to(&self.token.get_location());
+                let value = self.integer_value(&literal.clone(), true, &location);
+                self.next();
+                return Ok(Node::new(AST::Int(value)).with_location(location));
+            }
+        }
+        let operand = self.parse_unary_expression()?;
+        let location = pos.span_to(operand.get_location().unwrap());
+        Ok(Node::new(AST::BinaryOp(op, operand, Node::new(AST::Empty), true)).with_location(location))
+    }
+
+    fn parse_postfix_expression(&mut self) -> Result<Node, ()> {
+        let mut expr = self.parse_primary_expression()?;
+        let start = expr.get_location().unwrap().clone();
+        loop {
+            match self.token.get_type() {
+                TokenType::OpenParen => {
+                    self.next();
+                    let mut args = Vec::new();
+                    while *self.token.get_type() != TokenType::CloseParen {
+                        args.push(self.with_struct_literals(true, Self::parse_expression)?);
+                        if *self.token.get_type() != TokenType::Comma {
+                            break;
+                        }
+                        self.next();
+                    }
+                    consume_token!(self, TokenType::CloseParen, "call arguments");
+                    expr = Node::new(AST::Call(expr, args)).with_location(start.span_to(&self.prev_location()));
+                }
+                TokenType::Dot => {
+                    self.next();
+                    assert_token!(self, TokenType::Identifier("member name".to_string()), "member access");
+                    let name = self.token.value();
+                    self.next();
+                    expr = Node::new(AST::ClassAccess(expr, name)).with_location(start.span_to(&self.prev_location()));
+                }
+                TokenType::DoubleColon => {
+                    self.next();
+                    assert_token!(self, TokenType::Identifier("name".to_string()), "namespace access");
+                    let name = self.token.value();
+                    self.next();
+                    let generics = if self.is_generic_expr() { self.parse_generic_expr()? } else { None };
+                    expr = Node::new(AST::NamespaceAccess(expr, name, generics)).with_location(start.span_to(&self.prev_location()));
+                }
+                TokenType::OpenBrace if self.struct_literals && self.is_variant_literal(&expr) => {
+                    let AST::NamespaceAccess( base, variant, _ ) = expr.get_kind().clone() else {
+                        unreachable!();
+                    };
+                    self.next();
+                    let mut fields = Vec::new();
+                    while *self.token.get_type() != TokenType::CloseBrace {
+                        let field = self.token.value();
+                        self.next();
+                        consume_token!(self, TokenType::Colon, "field name");
+                        fields.push((field, self.with_struct_literals(true, Self::parse_expression)?));
+                        if *self.token.get_type() != TokenType::Comma {
+                            break;
+                        }
+                        self.next();
+                    }
+                    consume_token!(self, TokenType::CloseBrace, "variant fields");
+                    expr = Node::new(AST::VariantInit(AstType::new(base), variant, fields)).with_location(start.span_to(&self.prev_location()));
+                }
+                TokenType::OpenBracket => {
+                    self.next();
+                    let index = self.parse_expression()?;
+                    consume_token!(self, TokenType::CloseBracket, "index expression");
+                    expr = Node::new(AST::BinaryOp(BinaryOp::Index, expr, index, false)).with_location(start.span_to(&self.prev_location()));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_primary_expression(&mut self) -> Result<Node, ()> {
+        let pos = self.token.get_location();
         match self.token.get_type() {
             TokenType::Identifier(_) => {
                 let name = self.token.value();
-                let pos = self.token.get_location();
                 self.next();
-                let generics = self.parse_generic_expr()?;
+                let generics = if self.is_generic_expr() { self.parse_generic_expr()? } else { None };
                 Ok(Node::new(AST::Ident(name, generics)).with_location(pos))
             }
-            TokenType::Integer(_) => {
-                let value = self.token.value();
+            TokenType::New => {
+                self.next();
+                let ty = self.parse_type()?;
+                consume_token!(self, TokenType::OpenParen, "class initialization");
+                let mut args = Vec::new();
+                while *self.token.get_type() != TokenType::CloseParen {
+                    args.push(self.parse_expression()?);
+                    if *self.token.get_type() != TokenType::Comma {
+                        break;
+                    }
+                    self.next();
+                }
+                consume_token!(self, TokenType::CloseParen, "class initialization");
+                Ok(Node::new(AST::ClassInit(ty, args)).with_location(pos.span_to(&self.prev_location())))
+            }
+            TokenType::Integer(literal) => {
+                let value = self.integer_value(&literal.clone(), false, &pos);
+                self.next();
+                Ok(Node::new(AST::Int(value)).with_location(pos))
+            }
+            TokenType::Float(literal) => {
+                let value = self.float_value(&literal.clone(), &pos);
                 self.next();
-                // parse the number to an i64
-                let value = value.parse::<i64>().unwrap();
-                Ok(Node::new(AST::Int(value)).with_location(self.token.get_location()))
+                Ok(Node::new(AST::Float(value)).with_location(pos))
             }
-            TokenType::Float(_) => {
-                let value = self.token.value();
+            TokenType::String(value) => {
+                let value = value.clone();
                 self.next();
-                // parse the number to a f64
-                let value = value.parse::<f64>().unwrap();
-                Ok(Node::new(AST::Float(value)).with_location(self.token.get_location()))
+                Ok(Node::new(AST::String(value)).with_location(pos))
             }
-            TokenType::String(_) => {
-                let value = self.token.value();
+            TokenType::True | TokenType::False => {
+                let value = *self.token.get_type() == TokenType::True;
                 self.next();
-                Ok(Node::new(AST::String(value)).with_location(self.token.get_location()))
+                Ok(Node::new(AST::Bool(value)).with_location(pos))
             }
             TokenType::OpenParen => {
                 self.next();
-                let expr = self.parse_expression()?;
+                let expr = self.with_struct_literals(true, Self::parse_expression)?;
                 consume_token!(self, TokenType::CloseParen, "parenthesized expression");
                 Ok(expr)
             }
-
+            TokenType::Match => self.parse_match(),
             _ => report!(self, Error::UnexpectedToken(self.token.value())),
         }
     }
 
+    /// Whether the `{` after a path starts the fields of a variant
+    /// (`Shape::Rect { w: 1.0 }`), which are either empty or start with `name:`.
+    fn is_variant_literal(&self, expr: &Node) -> bool {
+        if !matches!(expr.get_kind(), AST::NamespaceAccess( .. )) {
+            return false;
+        }
+        match self.tokens[self.token_index + 1].get_type() {
+            TokenType::CloseBrace => true,
+            TokenType::Identifier(_) => *self.tokens[self.token_index + 2].get_type() == TokenType::Colon,
+            _ => false,
+        }
+    }
+
+    /// Parses `match value { pattern if guard => body, ... }`. The body of an
+    /// arm is either an expression followed by a `,` or a block.
+    fn parse_match(&mut self) -> Result<Node, ()> {
+        debug_assert!(*self.token.get_type() == TokenType::Match);
+        let pos = self.token.get_location();
+        self.next();
+        let scrutinee = self.parse_condition()?;
+        consume_token!(self, TokenType::OpenBrace, "match value");
+        let mut arms = Vec::new();
+        while *self.token.get_type() != TokenType::CloseBrace {
+            if *self.token.get_type() == TokenType::EOF {
+                report!(self, Error::UnexpectedEOF);
+            }
+            let pattern = self.parse_pattern()?;
+            let guard = match self.token.get_type() {
+                TokenType::If => {
+                    self.next();
+                    Some(self.parse_expression()?)
+                }
+                _ => None,
+            };
+            consume_token!(self, TokenType::Arrow, "match pattern");
+            let body = match self.token.get_type() {
+                TokenType::OpenBrace => {
+                    let body = self.parse_block()?;
+                    if *self.token.get_type() == TokenType::Comma {
+                        self.next();
+                    }
+                    body
+                }
+                _ => {
+                    let body = self.parse_expression()?;
+                    match self.token.get_type() {
+                        TokenType::Comma => self.next(),
+                        TokenType::CloseBrace => {}
+                        _ => report!(self, Error::ExpectedItemAfter(",".to_string(), "match arm".to_string(), self.token.value()), ErrorInfo {
+                            help: Some("Arms whose body is not a block are separated by commas".to_string()),
+                            ..Default::default()
+                        }),
+                    }
+                    body
+                }
+            };
+            arms.push(MatchArm::new(pattern, guard, body));
+        }
+        self.next();
+        Ok(Node::new(AST::Match(scrutinee, arms)).with_location(pos.span_to(&self.prev_location())))
+    }
+
+    /// Parses a pattern of a match arm: `_`, a binding, a literal or a
+    /// variant with patterns for its fields.
+    fn parse_pattern(&mut self) -> Result<Pattern, ()> {
+        let pos = self.token.get_location();
+        let kind = match self.token.get_type() {
+            TokenType::Identifier(name) if name == "_" => {
+                self.next();
+                PatternKind::Wildcard
+            }
+            TokenType::Integer(literal) => {
+                let value = self.integer_value(&literal.clone(), false, &pos);
+                self.next();
+                PatternKind::Int(value)
+            }
+            TokenType::Minus if matches!(self.tokens[self.token_index + 1].get_type(), TokenType::Integer(_)) => {
+                self.next();
+                let value = self.integer_value(&self.token.value(), true, &pos.span_to(&self.token.get_location()));
+                self.next();
+                PatternKind::Int(value)
+            }
+            TokenType::String(value) => {
+                let value = value.clone();
+                self.next();
+                PatternKind::String(value)
+            }
+            TokenType::True | TokenType::False => {
+                let value = *self.token.get_type() == TokenType::True;
+                self.next();
+                PatternKind::Bool(value)
+            }
+            TokenType::Identifier(_) => return self.parse_variant_pattern(),
+            _ => report!(self, Error::ExpectedItem("pattern".to_string(), "match".to_string()), ErrorInfo {
+                help: Some("Patterns can be '_', a name, an integer, a string, a boolean or an enum variant".to_string()),
+                ..Default::default()
+            }),
+        };
+        Ok(Pattern::new(kind, pos.span_to(&self.prev_location())))
+    }
+
+    /// Parses a pattern that starts with a name. A single name without fields
+    /// is a binding, unless the type checker finds a variant with that name.
+    fn parse_variant_pattern(&mut self) -> Result<Pattern, ()> {
+        let pos = self.token.get_location();
+        let mut path = Node::new(AST::Ident(self.token.value(), None)).with_location(pos.clone());
+        let mut variant = self.token.value();
+        self.next();
+        let mut enum_path = None;
+        while *self.token.get_type() == TokenType::DoubleColon {
+            self.next();
+            assert_token!(self, TokenType::Identifier("variant name".to_string()), "pattern");
+            enum_path = Some(path.clone());
+            variant = self.token.value();
+            path = Node::new(AST::NamespaceAccess(path, variant.clone(), None)).with_location(pos.span_to(&self.token.get_location()));
+            self.next();
+        }
+        let enum_path = enum_path.map(AstType::new);
+        let mut fields = Vec::new();
+        let mut has_rest = false;
+        let kind = match self.token.get_type() {
+            TokenType::OpenParen => {
+                self.next();
+                while *self.token.get_type() != TokenType::CloseParen {
+                    fields.push((fields.len().to_string(), self.parse_pattern()?));
+                    if *self.token.get_type() != TokenType::Comma {
+                        break;
+                    }
+                    self.next();
+                }
+                consume_token!(self, TokenType::CloseParen, "variant pattern");
+                VariantKind::Tuple
+            }
+            TokenType::OpenBrace => {
+                self.next();
+                while *self.token.get_type() != TokenType::CloseBrace {
+                    if *self.token.get_type() == TokenType::Dot {
+                        self.next();
+                        consume_token!(self, TokenType::Dot, ".");
+                        has_rest = true;
+                        break;
+                    }
+                    assert_token!(self, TokenType::Identifier("field name".to_string()), "variant pattern");
+                    let field_pos = self.token.get_location();
+                    let field = self.token.value();
+                    self.next();
+                    // `{ w }` is short for `{ w: w }`
+                    let pattern = match self.token.get_type() {
+                        TokenType::Colon => {
+                            self.next();
+                            self.parse_pattern()?
+                        }
+                        _ => Pattern::new(PatternKind::Binding(field.clone()), field_pos),
+                    };
+                    fields.push((field, pattern));
+                    if *self.token.get_type() != TokenType::Comma {
+                        break;
+                    }
+                    self.next();
+                }
+                consume_token!(self, TokenType::CloseBrace, "variant pattern");
+                VariantKind::Struct
+            }
+            _ if enum_path.is_none() => return Ok(Pattern::new(PatternKind::Binding(variant), pos)),
+            _ => VariantKind::Unit,
+        };
+        Ok(Pattern::new(PatternKind::Variant(enum_path, variant, kind, fields, has_rest), pos.span_to(&self.prev_location())))
+    }
+
+    fn get_binary_op(token: &TokenType) -> Option<(BinaryOp, u8)> {
+        Some(match token {
+            TokenType::DoublePipe => (BinaryOp::Or, 1),
+            TokenType::DoubleAmpersand => (BinaryOp::And, 2),
+            TokenType::DoubleEqual => (BinaryOp::Eq, 3),
+            TokenType::NotEqual => (BinaryOp::Ne, 3),
+            TokenType::LessThan => (BinaryOp::Lt, 4),
+            TokenType::LessThanEqual => (BinaryOp::Le, 4),
+            TokenType::GreaterThan => (BinaryOp::Gt, 4),
+            TokenType::GreaterThanEqual => (BinaryOp::Ge, 4),
+            TokenType::Plus => (BinaryOp::Add, 5),
+            TokenType::Minus => (BinaryOp::Sub, 5),
+            TokenType::Star => (BinaryOp::Mul, 6),
+            TokenType::Slash => (BinaryOp::Div, 6),
+            TokenType::Percent => (BinaryOp::Mod, 6),
+            _ => return None,
+        })
+    }
+
+    /// Decides whether the `<` after an identifier opens a generic argument list
+    /// or is a comparison. The arguments are scanned without being parsed and
+    /// only count as generics if the closing `>` is followed by a token that
+    /// cannot start an expression (e.g. `foo<i32>(x)` but not `a < b > c`).
+    fn is_generic_expr(&self) -> bool {
+        if *self.token.get_type() != TokenType::LessThan {
+            return false;
+        }
+        let mut depth = 0;
+        let mut index = self.token_index;
+        loop {
+            match self.tokens[index].get_type() {
+                TokenType::LessThan => depth += 1,
+                TokenType::GreaterThan => depth -= 1,
+                TokenType::DoubleGreaterThan if depth >= 2 => depth -= 2,
+                TokenType::Identifier(_) |
+                TokenType::Comma |
+                TokenType::DoubleColon => {}
+                _ => return false,
+            }
+            index += 1;
+            if depth == 0 {
+                break;
+            }
+        }
+        matches!(self.tokens[index].get_type(),
+            TokenType::OpenParen |
+            TokenType::CloseParen |
+            TokenType::CloseBracket |
+            TokenType::Semicolon |
+            TokenType::Comma |
+            TokenType::Dot |
+            TokenType::DoubleColon)
+    }
+
+    fn span(start: &Node, end: &Node) -> SourceLocation {
+        start.get_location().unwrap().span_to(end.get_location().unwrap())
+    }
+
     pub fn parse_type(&mut self) -> Result<AstType, ()> {
         let location = self.token.get_location();
         match self.token.get_type() {
             TokenType::Identifier(_) => {
-                let name = self.token.value();
+                // qualified types (`a::b::Type<T>`) keep their generics on the last segment
+                let mut path = Node::new(AST::Ident(self.token.value(), None)).with_location(location.clone());
                 self.next();
+                while *self.token.get_type() == TokenType::DoubleColon {
+                    self.next();
+                    assert_token!(self, TokenType::Identifier("type name".to_string()), "::");
+                    path = Node::new(AST::NamespaceAccess(path, self.token.value(), None)).with_location(location.span_to(&self.token.get_location()));
+                    self.next();
+                }
                 let mut generics = None;
                 while *self.token.get_type() == TokenType::LessThan {
                     generics = Some(Vec::new());
                     self.next();
-                    while *self.token.get_type() != TokenType::GreaterThan {
+                    while !self.at_generic_close() {
                         generics.as_mut().unwrap().push(self.parse_type()?);
                         if *self.token.get_type() == TokenType::Comma {
                             self.next();
                         }
                     }
-                    self.next();
+                    self.consume_generic_close();
                 }
-                Ok(AstType::new(Node::new(AST::Ident(name, generics)).with_location(location)))
+                let kind = match path.get_kind().clone() {
+                    AST::NamespaceAccess( base, name, _ ) => AST::NamespaceAccess(base, name, generics),
+                    AST::Ident( name, _ ) => AST::Ident(name, generics),
+                    _ => unreachable!(),
+                };
+                Ok(AstType::new(Node::new(kind).with_location(path.get_location().unwrap().clone())))
             }
             _ => report!(self, Error::ExpectedItem("type".to_string(), "type".to_string()), ErrorInfo {
                 help: Some("Types can be identifiers, tuples, or arrays".to_string()),
@@ -429,9 +1517,8 @@ impl Parser {
     pub fn parse_generic_args_if_present(&mut self) -> Result<Option<Vec<GenericDecl>>, ()> {
         if let TokenType::LessThan = self.token.get_type() {
             self.next();
-            let mut generics = None;
-            while *self.token.get_type() != TokenType::GreaterThan {
-                generics = Some(Vec::new());
+            let mut generics = Vec::new();
+            while !self.at_generic_close() {
                 match self.token.get_type() {
                     TokenType::Identifier(_) => {
                         let name = self.token.value();
@@ -450,7 +1537,10 @@ impl Parser {
                             self.next();
                             default = Some(self.parse_type()?);
                         }
-                        generics.as_mut().unwrap().push(GenericDecl::new(name, impls, default));
+                        generics.push(GenericDecl::new(name, impls, default));
+                        if *self.token.get_type() == TokenType::Comma {
+                            self.next();
+                        }
                     }
                     _ => report!(self, Error::ExpectedItem("generic argument".to_string(), "generic argument".to_string()), ErrorInfo {
                         help: Some("Generic arguments must be identifiers".to_string()),
@@ -460,8 +1550,8 @@ impl Parser {
                     }),
                 }
             }
-            self.next();
-            return Ok(generics.clone());
+            self.consume_generic_close();
+            return Ok(if generics.is_empty() { None } else { Some(generics) });
         } 
         Ok(None)
     }
@@ -470,18 +1560,86 @@ impl Parser {
         if let TokenType::LessThan = self.token.get_type() {
             self.next();
             let mut generics = Vec::new();
-            while *self.token.get_type() != TokenType::GreaterThan {
+            while !self.at_generic_close() {
                 generics.push(self.parse_type()?);
                 if *self.token.get_type() == TokenType::Comma {
                     self.next();
                 }
             }
-            self.next();
+            self.consume_generic_close();
             return Ok(Some(generics));
         }
         Ok(None)
     }
     
+    /// The value of an integer literal (`42`, `0x2a`, `0b101`, `0o52`, `42u`).
+    /// Literals after a minus sign are `negative`, the sign is applied before
+    /// the value is narrowed to an i64 so `-9223372036854775808` fits. Invalid
+    /// literals are reported and read as 0 so the parser can keep going.
+    fn integer_value(&mut self, literal: &str, negative: bool, location: &SourceLocation) -> i64 {
+        let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+        let (radix, digits) = match digits.get(..2) {
+            Some("0x") => (16, &digits[2..]),
+            Some("0b") => (2, &digits[2..]),
+            Some("0o") => (8, &digits[2..]),
+            _ => (10, digits),
+        };
+        let info = match u64::from_str_radix(digits, radix) {
+            Ok(value) => {
+                let value = if negative { -(value as i128) } else { value as i128 };
+                if let Ok(value) = i64::try_from(value) {
+                    return value;
+                }
+                "Integers must be between -9223372036854775808 and 9223372036854775807."
+            }
+            Err(error) if *error.kind() == std::num::IntErrorKind::PosOverflow => "Integers must be between -9223372036854775808 and 9223372036854775807.",
+            Err(_) => "This is not a valid number.",
+        };
+        let literal = if negative { format!("-{}", literal) } else { literal.to_string() };
+        self.reports.add_error(CompileError::new(Error::InvalidNumber(literal), location.clone()).with_info(ErrorInfo {
+            info: Some(info.to_string()),
+            ..Default::default()
+        }));
+        0
+    }
+
+    /// The value of a floating point literal (`1.5`, `2f`, `0.5d`).
+    fn float_value(&mut self, literal: &str, location: &SourceLocation) -> f64 {
+        match literal.trim_end_matches(['f', 'F', 'd', 'D']).parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => {
+                self.reports.add_error(CompileError::new(Error::InvalidNumber(literal.to_string()), location.clone()).with_info(ErrorInfo {
+                    info: Some("This is not a valid number.".to_string()),
+                    ..Default::default()
+                }));
+                0.0
+            }
+        }
+    }
+
+    fn prev_location(&self) -> SourceLocation {
+        self.tokens[self.token_index - 1].get_location()
+    }
+
+    fn at_generic_close(&self) -> bool {
+        matches!(self.token.get_type(), TokenType::GreaterThan | TokenType::DoubleGreaterThan)
+    }
+
+    /// Consumes the `>` that closes a generic list. The lexer reads the end of
+    /// nested lists (`Vec<Map<K, V>>`) as a single `>>`, which is split so its
+    /// second half closes the outer list.
+    fn consume_generic_close(&mut self) {
+        if *self.token.get_type() != TokenType::DoubleGreaterThan {
+            self.next();
+            return;
+        }
+        let mut location = self.token.get_location();
+        location.column += 1;
+        location.width = 1;
+        self.token = Token::new(TokenType::GreaterThan, location);
+        self.tokens[self.token_index] = self.token.clone();
+    }
+
     pub fn next(&mut self) {
         self.token_index += 1;
         self.token = self.tokens[self.token_index].clone();
@@ -492,3 +1650,115 @@ impl Parser {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::source::FileId;
+
+    /// Parses `source` as an expression and writes it back with every
+    /// operation in parentheses, so the tree can be compared as text.
+    fn parse(source: &str) -> String {
+        let mut lexer = Lexer::new(source, FileId::DUMMY);
+        lexer.lex();
+        let mut parser = Parser::new(NamespacePath::new(Vec::new()), "test.sn".to_string(), &lexer);
+        let Ok(node) = parser.parse_expression() else {
+            let errors: Vec<String> = parser.get_reports().get_errors().iter().map(|e| e.get_message().to_string()).collect();
+            panic!("'{}' failed to parse: {:?}", source, errors);
+        };
+        assert_eq!(*parser.token.get_type(), TokenType::EOF, "'{}' was not parsed to the end", source);
+        render(&node)
+    }
+
+    /// Parses `source` as a module, returning the global items and the codes of the errors.
+    fn parse_module(source: &str) -> (Vec<Node>, Vec<&'static str>) {
+        let mut lexer = Lexer::new(source, FileId::DUMMY);
+        lexer.lex();
+        let mut parser = Parser::new(NamespacePath::new(Vec::new()), "test.sn".to_string(), &lexer);
+        let nodes = match parser.parse().map(|module| module.get_top().clone()) {
+            Ok(AST::TopLevel( nodes )) => nodes,
+            _ => Vec::new(),
+        };
+        (nodes, parser.get_reports().get_errors().iter().map(|e| e.get_message().code()).collect())
+    }
+
+    fn render(node: &Node) -> String {
+        match node.get_kind() {
+            AST::BinaryOp(op, operand, _, true) => format!("({}{})", op, render(operand)),
+            AST::BinaryOp(op, lhs, rhs, false) => format!("({} {} {})", render(lhs), op, render(rhs)),
+            AST::Ident(name, _) => name.clone(),
+            AST::Int(value) => value.to_string(),
+            AST::Call(callee, args) => format!("{}({})", render(callee), args.iter().map(render).collect::<Vec<_>>().join(", ")),
+            AST::ClassAccess(base, member) => format!("{}.{}", render(base), member),
+            kind => panic!("unexpected node in an expression: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(parse("a + b * c"), "(a + (b * c))");
+        assert_eq!(parse("a * b + c"), "((a * b) + c)");
+        assert_eq!(parse("a % b - c / d"), "((a % b) - (c / d))");
+    }
+
+    #[test]
+    fn comparisons_bind_tighter_than_logical_operators() {
+        assert_eq!(parse("a < b && c >= d"), "((a < b) && (c >= d))");
+        assert_eq!(parse("a == b || c != d && e"), "((a == b) || ((c != d) && e))");
+        assert_eq!(parse("a + 1 > b * 2"), "((a + 1) > (b * 2))");
+        assert_eq!(parse("a < b == c > d"), "((a < b) == (c > d))");
+    }
+
+    #[test]
+    fn binary_operators_are_left_associative() {
+        assert_eq!(parse("a - b - c"), "((a - b) - c)");
+        assert_eq!(parse("a / b / c"), "((a / b) / c)");
+        assert_eq!(parse("a - b + c"), "((a - b) + c)");
+        assert_eq!(parse("a && b && c"), "((a && b) && c)");
+        assert_eq!(parse("a || b || c"), "((a || b) || c)");
+    }
+
+    #[test]
+    fn unary_operators_bind_tighter_than_binary_operators() {
+        assert_eq!(parse("-a * b"), "((-a) * b)");
+        assert_eq!(parse("a - -b"), "(a - (-b))");
+        assert_eq!(parse("!a && b"), "((!a) && b)");
+        assert_eq!(parse("!!a"), "(!(!a))");
+        assert_eq!(parse("~a + b"), "((~a) + b)");
+    }
+
+    #[test]
+    fn unary_operators_apply_to_the_whole_postfix_expression() {
+        assert_eq!(parse("-a.b"), "(-a.b)");
+        assert_eq!(parse("!f(a)"), "(!f(a))");
+        assert_eq!(parse("-1 - 2"), "(-1 - 2)");
+    }
+
+    #[test]
+    fn errors_in_a_class_body_only_skip_the_member() {
+        let (nodes, errors) = parse_module("class Counter {\n    let count i32;\n    func get(self) i32 { return self.count; }\n    func bump(self {}\n    func reset(self) { self.count = 0; }\n}\n\nfunc main() i32 { return 0; }\n");
+        assert_eq!(errors, ["E0006", "E0005"]);
+        let [class, main] = nodes.as_slice() else {
+            panic!("expected the class and 'main', found {:?}", nodes);
+        };
+        let AST::ClassDef( name, _, _, members, methods, .. ) = class.get_kind() else {
+            panic!("expected the class, found {:?}", class);
+        };
+        assert_eq!(name, "Counter");
+        assert!(members.is_empty());
+        let methods = methods.iter().map(|m| match m.get_kind() {
+            AST::FuncDef( name, .. ) => name.as_str(),
+            kind => panic!("unexpected method: {:?}", kind),
+        }).collect::<Vec<_>>();
+        assert_eq!(methods, ["get", "reset"]);
+        assert!(matches!(main.get_kind(), AST::FuncDef( name, .. ) if name == "main"));
+    }
+
+    #[test]
+    fn a_class_without_its_closing_brace_ends_at_the_next_item() {
+        let (nodes, errors) = parse_module("class A {\n    func f(self) {}\n\nclass B {}\n");
+        assert_eq!(errors, ["E0002"]);
+        assert!(matches!(nodes.as_slice(), [a, b] if matches!(a.get_kind(), AST::ClassDef( name, .. ) if name == "A")
+            && matches!(b.get_kind(), AST::ClassDef( name, .. ) if name == "B")));
+    }
+}
@@ -9,7 +9,14 @@ use crate::reports::{CompileError, ErrorInfo, Error, Reports};
 use crate::ast::nodes::AstType;
 use crate::ast::nodes::GenericDecl;
 use crate::ast::nodes::ClassMember;
-use std::ops::Deref;
+use crate::ast::nodes::Param;
+use crate::ast::nodes::EnumVariant;
+use crate::ast::nodes::MatchArm;
+use crate::ast::nodes::{Pattern, PatternKind};
+use crate::ast::nodes::VariantKind;
+use crate::ast::attrs::{AstAttrs, AttrHandler};
+use crate::ast::nodes::BinaryOp;
+use crate::ast::source::SourceLocation;
 
 #[derive(Clone, Debug)]
 pub enum UnificationType {
@@ -28,203 +35,2657 @@ pub enum Type {
         size: usize
     },
     Object {
-        id: usize
+        id: usize,
+        generics: Vec<UnificationType>
     },
     Function {
         args: Vec<UnificationType>,
         ret: Box<UnificationType>
     },
+    // there is no syntax for pointer and reference types yet
+    #[allow(dead_code)]
     Pointer {
         ty: Box<UnificationType>
     },
+    #[allow(dead_code)]
     Reference {
         ty: Box<UnificationType>
     },
+    Bool,
+    String,
     Void
 }
 
+/// The parameters, return type and generic arguments of a function after
+/// its generic parameters have been replaced.
+type InstantiatedFunction = (Vec<(String, UnificationType)>, UnificationType, Option<Vec<UnificationType>>);
+
+/// A pending requirement that `found` is the same type as `expected`.
+pub struct Constraint {
+    expected: UnificationType,
+    found: UnificationType,
+    expected_location: Option<SourceLocation>,
+    found_location: SourceLocation
+}
+
+/// Restricts what a type variable can be unified with. Integer and float
+/// literals create variables that only accept integer or float types.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariableKind {
+    Any,
+    Integer,
+    Float
+}
+
 #[derive(Debug, Clone)]
 pub struct FunctionSymbol {
     ast: TypedNode
 }
 
-impl FunctionSymbol {
-    pub fn new(ast: TypedNode) -> FunctionSymbol {
-        FunctionSymbol {
-            ast
+impl FunctionSymbol {
+    pub fn new(ast: TypedNode) -> FunctionSymbol {
+        FunctionSymbol {
+            ast
+        }
+    }
+
+    pub fn get_ast(&self) -> &TypedNode {
+        &self.ast
+    }
+
+    pub fn get_id(&self) -> usize {
+        match self.ast.get_kind() {
+            AST::FuncDef( .., id ) => id.unwrap(),
+            _ => panic!("Expected FuncDef")
+        }
+    }
+
+    /// The signature of the function, without the `self` parameter.
+    pub fn get_type(&self) -> UnificationType {
+        match self.ast.get_kind() {
+            AST::FuncDef( _, args, ret, .. ) => UnificationType::Known(Type::Function {
+                args: args.iter().filter(|param| param.get_name() != "self").map(|param| param.get_ty().clone()).collect(),
+                ret: Box::new(ret.clone())
+            }),
+            _ => panic!("Expected FuncDef")
+        }
+    }
+}
+
+pub struct Object {
+    id: usize,
+    name: String,
+    path: NamespacePath,
+    parent: Option<UnificationType>,
+    /// The interfaces a class says it implements.
+    interfaces: Vec<UnificationType>,
+    is_interface: bool,
+    members: Vec<ClassMember<UnificationType>>,
+    methods: HashMap<String, usize>,
+    generics: Vec<GenericDecl<UnificationType>>,
+    /// Only enums have variants.
+    variants: Option<Vec<EnumVariant<UnificationType>>>,
+    attrs: AttrHandler,
+    location: Option<SourceLocation>
+}
+
+impl Object {
+    pub fn new(id: usize, name: String, path: NamespacePath, generics: Vec<GenericDecl<UnificationType>>, attrs: AttrHandler, location: Option<SourceLocation>) -> Object {
+        Object {
+            id,
+            name,
+            path,
+            parent: None,
+            interfaces: Vec::new(),
+            is_interface: false,
+            members: Vec::new(),
+            methods: HashMap::new(),
+            generics,
+            variants: None,
+            attrs,
+            location
+        }
+    }
+
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_path(&self) -> &NamespacePath {
+        &self.path
+    }
+
+    pub fn get_parent(&self) -> Option<&UnificationType> {
+        self.parent.as_ref()
+    }
+
+    pub fn get_parent_id(&self) -> Option<usize> {
+        match self.parent {
+            Some(UnificationType::Known(Type::Object { id, .. })) => Some(id),
+            _ => None
+        }
+    }
+
+    pub fn get_interfaces(&self) -> &Vec<UnificationType> {
+        &self.interfaces
+    }
+
+    pub fn is_interface(&self) -> bool {
+        self.is_interface
+    }
+
+    pub fn get_members(&self) -> &Vec<ClassMember<UnificationType>> {
+        &self.members
+    }
+
+    /// Maps every method declared by this class (not its parents) to its function id.
+    pub fn get_methods(&self) -> &HashMap<String, usize> {
+        &self.methods
+    }
+
+    pub fn get_generics(&self) -> &Vec<GenericDecl<UnificationType>> {
+        &self.generics
+    }
+
+    pub fn get_variants(&self) -> Option<&Vec<EnumVariant<UnificationType>>> {
+        self.variants.as_ref()
+    }
+
+    pub fn get_attrs(&self) -> &AttrHandler {
+        &self.attrs
+    }
+}
+
+pub struct Typechecker {
+    types: HashMap<String, UnificationType>, // TODO: Figure out changing it to a vec
+    /// The classes and functions declared at the top level of each module.
+    globals: HashMap<NamespacePath, HashMap<String, Symbol>>,
+    /// The names each module brings into scope with `import`.
+    imports: HashMap<NamespacePath, HashMap<String, Import>>,
+    /// The module of the file each namespace is declared in.
+    namespaces: HashMap<NamespacePath, NamespacePath>,
+    /// Every namespace, by its name and the module or namespace it is declared in.
+    namespace_decls: Vec<(NamespacePath, String)>,
+    current_module: NamespacePath,
+    constraints: Vec<Constraint>,
+    substitutions: Vec<(Option<UnificationType>, VariableKind)>,
+    functions: Vec<(NamespacePath, FunctionSymbol)>,
+    scope: Vec<HashMap<String, Symbol>>,
+    /// The interfaces the generic parameters of each scope are bound by.
+    generic_bounds: Vec<HashMap<String, Vec<UnificationType>>>,
+    /// Bounds of generic arguments that are checked once their types are known,
+    /// with the generic parameter and the item it belongs to.
+    pending_bounds: Vec<(UnificationType, UnificationType, SourceLocation, String, String)>,
+    /// Where the variables of each scope were declared.
+    declarations: Vec<HashMap<String, SourceLocation>>,
+    /// Every name that was resolved, from where it is used to where it is declared.
+    references: Vec<(SourceLocation, SourceLocation)>,
+    infer_ctx: Option<UnificationType>,
+    reports: Reports,
+    objects: Vec<Object>,
+    loop_depth: usize,
+    current_class: Option<usize>,
+    return_location: Option<SourceLocation>
+}
+
+macro_rules! report {
+    ($self:ident, $error_type:expr, $node:expr) => { {
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()));
+        return Err(());
+    }
+    };
+    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
+        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
+        return Err(());
+    }
+    }
+}
+
+#[derive(Clone)]
+pub enum Symbol {
+    Variable(UnificationType),
+    Function(FunctionSymbol),
+    Type(UnificationType),
+    Module(NamespacePath)
+}
+
+/// What an imported name refers to. Items are looked up when they are used
+/// since imports are resolved before anything is declared.
+#[derive(Clone, PartialEq)]
+pub enum Import {
+    Module(NamespacePath),
+    Item(NamespacePath, String),
+}
+
+/// A pattern reduced to what exhaustiveness checking looks at. Bindings are
+/// wildcards, variants and literals are constructors with their fields.
+#[derive(Debug, Clone)]
+enum Space {
+    Wildcard,
+    Constructor(String, Vec<Space>),
+}
+
+impl Space {
+    fn from_pattern(pattern: &Pattern<UnificationType>) -> Space {
+        match pattern.get_kind() {
+            PatternKind::Wildcard | PatternKind::Binding( _ ) => Space::Wildcard,
+            PatternKind::Int( value ) => Space::Constructor(value.to_string(), Vec::new()),
+            PatternKind::String( value ) => Space::Constructor(format!("{:?}", value), Vec::new()),
+            PatternKind::Bool( value ) => Space::Constructor(value.to_string(), Vec::new()),
+            PatternKind::Variant( _, name, _, fields, _ ) => Space::Constructor(name.clone(), fields.iter().map(|(_, field)| Space::from_pattern(field)).collect()),
+        }
+    }
+}
+
+/// At most this many missing patterns are listed when a match is not exhaustive.
+const MAX_MISSING_PATTERNS: usize = 4;
+
+/// The name calls to the built-in `assert(cond)` get in the typed tree, no
+/// item or variable of a program can have it.
+pub const BUILTIN_ASSERT: &str = "@assert";
+
+impl Typechecker {
+    pub fn new() -> Typechecker {
+        Typechecker {
+            types: HashMap::new(),
+            globals: HashMap::new(),
+            imports: HashMap::new(),
+            namespaces: HashMap::new(),
+            namespace_decls: Vec::new(),
+            current_module: NamespacePath::new(Vec::new()),
+            scope: vec![HashMap::new()],
+            generic_bounds: vec![HashMap::new()],
+            pending_bounds: Vec::new(),
+            declarations: vec![HashMap::new()],
+            references: Vec::new(),
+            reports: Reports::new(),
+            constraints: Vec::new(),
+            substitutions: Vec::new(),
+            infer_ctx: None,
+            functions: Vec::new(),
+            objects: Vec::new(),
+            loop_depth: 0,
+            current_class: None,
+            return_location: None
+        }
+    }
+
+    /// Type checks every module of a project together. Returns the typed
+    /// modules, which are only complete if no error was reported.
+    pub fn typecheck(&mut self, modules: &mut Vec<Module<Node>>) -> Vec<Module<TypedNode>> {
+        self.initialize_builtin_types();
+        if self.split_namespaces(modules).is_err() || self.declare_modules(modules).is_err() {
+            return Vec::new();
+        }
+        modules.iter().map(|module| self.check_module(module)).collect()
+    }
+
+    pub fn initialize_builtin_types(&mut self) {
+        self.types.insert("i32".to_string(), UnificationType::Known(Type::Integer { size: 32, signed: true }));
+        self.types.insert("i64".to_string(), UnificationType::Known(Type::Integer { size: 64, signed: true }));
+        self.types.insert("f32".to_string(), UnificationType::Known(Type::Float { size: 32 }));
+        self.types.insert("f64".to_string(), UnificationType::Known(Type::Float { size: 64 }));
+        self.types.insert("u32".to_string(), UnificationType::Known(Type::Integer { size: 32, signed: false }));
+        self.types.insert("u64".to_string(), UnificationType::Known(Type::Integer { size: 64, signed: false }));
+        self.types.insert("bool".to_string(), UnificationType::Known(Type::Bool));
+        self.types.insert("String".to_string(), UnificationType::Known(Type::String));
+        self.types.insert("void".to_string(), UnificationType::Known(Type::Void));
+    }
+
+    fn top_nodes_mut(module: &mut Module<Node>) -> &mut Vec<Node> {
+        match module.get_top_mut() {
+            AST::TopLevel( nodes ) => nodes,
+            _ => panic!("Expected TopLevel node"),
+        }
+    }
+
+    /// Moves the items of every `namespace` block into a module of its own,
+    /// placed after the module of its file. The path of a namespace starts with
+    /// the path of its file (`namespace a::b` in `app` is `app::a::b`), and
+    /// blocks with the same path share one module.
+    fn split_namespaces(&mut self, modules: &mut Vec<Module<Node>>) -> Result<(), ()> {
+        let files = modules.iter().map(|module| module.get_path().clone()).collect::<Vec<_>>();
+        let mut split = Vec::new();
+        let mut failed = false;
+        for mut module in std::mem::take(modules) {
+            let path = module.get_path().clone();
+            let mut namespaces = Vec::new();
+            let nodes = std::mem::take(Self::top_nodes_mut(&mut module));
+            match self.collect_namespaces(&path, &path, nodes, &files, &mut namespaces, module.get_file_name()) {
+                Ok(nodes) => *Self::top_nodes_mut(&mut module) = nodes,
+                Err(()) => failed = true,
+            }
+            split.push(module);
+            split.extend(namespaces);
+        }
+        *modules = split;
+        if failed {
+            return Err(());
+        }
+        Ok(())
+    }
+
+    /// Moves the items of the namespaces among `nodes` into `namespaces` and
+    /// returns the other nodes.
+    fn collect_namespaces(&mut self, file: &NamespacePath, parent: &NamespacePath, nodes: Vec<Node>, files: &[NamespacePath],
+                          namespaces: &mut Vec<Module<Node>>, file_name: &Option<String>) -> Result<Vec<Node>, ()> {
+        let mut rest = Vec::new();
+        for node in nodes {
+            let AST::NamespaceDef( name, items ) = node.get_kind() else {
+                rest.push(node);
+                continue;
+            };
+            // every segment of `a::b` is a namespace of its own
+            let mut path = parent.clone();
+            for segment in name.iter().flat_map(|name| Self::node_to_path(name).split("::").map(String::from).collect::<Vec<_>>()) {
+                let outer = path.clone();
+                path.push(segment.clone());
+                if files.contains(&path) {
+                    report!(self, Error::VariableAlreadyDeclared(segment.clone()), node, ErrorInfo {
+                        info: Some(format!("'{}' is already the path of a module.", path)),
+                        help: Some("Rename the namespace or the file of the module.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                if self.namespaces.insert(path.clone(), file.clone()).is_none() {
+                    self.namespace_decls.push((outer, segment));
+                    let mut module = Module::new(path.clone(), file_name.clone());
+                    module.set_top(AST::TopLevel(Vec::new()));
+                    namespaces.push(module);
+                }
+            }
+            let items = self.collect_namespaces(file, &path, items.clone(), files, namespaces, file_name)?;
+            let module = namespaces.iter_mut().find(|module| *module.get_path() == path).unwrap();
+            Self::top_nodes_mut(module).extend(items);
+        }
+        Ok(rest)
+    }
+
+    /// The module of the file a module or namespace is declared in.
+    fn file_module<'a>(&'a self, path: &'a NamespacePath) -> &'a NamespacePath {
+        self.namespaces.get(path).unwrap_or(path)
+    }
+
+    /// Declares the classes and functions of every module. Each phase runs over
+    /// all of the modules before the next one starts so the declarations of a
+    /// module are known while the others are being declared.
+    fn declare_modules(&mut self, modules: &mut [Module<Node>]) -> Result<(), ()> {
+        self.resolve_imports(modules)?;
+        // namespaces are bound first so every signature can refer to their items
+        for (parent, name) in self.namespace_decls.clone() {
+            let mut path = parent.clone();
+            path.push(name.clone());
+            self.enter_module(&parent);
+            self.insert_global(name, Symbol::Module(path));
+        }
+        // classes are declared first so every signature can refer to them,
+        // then their bodies and after that the rest of the functions.
+        for module in modules.iter_mut() {
+            self.enter_module(module.get_path());
+            let x = module.clone();
+            for node in Self::top_nodes_mut(module) {
+                if let AST::ClassDef( .. ) | AST::EnumDef( .. ) | AST::InterfaceDef( .. ) = node.get_kind() {
+                    self.declare_class(&x, node)?;
+                }
+            }
+        }
+        for module in modules.iter_mut() {
+            self.enter_module(module.get_path());
+            let x = module.clone();
+            for node in Self::top_nodes_mut(module) {
+                match node.get_kind() {
+                    AST::ClassDef( .. ) => self.define_class(&x, node)?,
+                    AST::EnumDef( .. ) => self.define_enum(node)?,
+                    AST::InterfaceDef( .. ) => self.define_interface(&x, node)?,
+                    _ => {}
+                }
+            }
+        }
+        for module in modules.iter_mut() {
+            self.enter_module(module.get_path());
+            let x = module.clone();
+            for node in Self::top_nodes_mut(module) {
+                if let AST::FuncDef( name, .. ) = node.get_kind().clone() {
+                    if self.get_globals().contains_key(&name) {
+                        report!(self, Error::VariableAlreadyDeclared(name.clone()), node, ErrorInfo {
+                            info: Some(format!("Function '{}' already declared in this scope.", name)),
+                            help: Some("Make sure the function is not declared twice in the same scope.".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                    let id = self.declare_function(&x, node, None)?;
+                    let symbol = self.functions[id].1.clone();
+                    self.insert_global(name, Symbol::Function(symbol));
+                }
+            }
+        }
+        for module in modules.iter_mut() {
+            self.enter_module(module.get_path());
+            for node in Self::top_nodes_mut(module) {
+                if let AST::ClassDef( .. ) = node.get_kind() {
+                    self.check_class_hierarchy(node)?;
+                    self.check_interfaces(node)?;
+                }
+            }
+        }
+        // the classes used as generic arguments in signatures know their interfaces by now
+        self.check_pending_bounds()
+    }
+
+    /// Binds the names brought into scope by the imports of every module and
+    /// makes sure the modules don't import each other in a cycle.
+    fn resolve_imports(&mut self, modules: &[Module<Node>]) -> Result<(), ()> {
+        // the items of each module and whether they are public
+        let mut items: HashMap<NamespacePath, Vec<(String, bool)>> = HashMap::new();
+        for module in modules {
+            let AST::TopLevel( nodes ) = module.get_top() else {
+                panic!("Expected TopLevel node");
+            };
+            let module_items = items.entry(module.get_path().clone()).or_default();
+            for node in nodes {
+                if let AST::FuncDef( name, .. ) | AST::ClassDef( name, .. ) | AST::EnumDef( name, .. ) | AST::InterfaceDef( name, .. ) = node.get_kind() {
+                    module_items.push((name.clone(), node.get_attrs().is_some_and(|a| a.is_pub())));
+                }
+            }
+        }
+        // namespaces are always public
+        for (parent, name) in &self.namespace_decls {
+            items.entry(parent.clone()).or_default().push((name.clone(), true));
+        }
+        let mut dependencies: HashMap<NamespacePath, Vec<(NamespacePath, Node)>> = HashMap::new();
+        let mut failed = false;
+        for module in modules {
+            self.enter_module(module.get_path());
+            self.imports.entry(module.get_path().clone()).or_default();
+            let AST::TopLevel( nodes ) = module.get_top() else {
+                panic!("Expected TopLevel node");
+            };
+            // every import gets resolved so all of the broken ones are reported at once
+            for node in nodes {
+                if let AST::Import( path, alias ) = node.get_kind() {
+                    match self.resolve_import(&items, path, alias, node) {
+                        Ok(dependency) => dependencies.entry(module.get_path().clone()).or_default().push((dependency, node.clone())),
+                        Err(()) => failed = true,
+                    }
+                }
+            }
+        }
+        if failed {
+            return Err(());
+        }
+        let mut finished = Vec::new();
+        for module in modules {
+            self.check_import_cycles(module.get_path(), &dependencies, &mut Vec::new(), &mut finished)?;
+        }
+        Ok(())
+    }
+
+    /// Resolves a single import against the modules of the project and
+    /// returns the module it depends on.
+    fn resolve_import(&mut self, items: &HashMap<NamespacePath, Vec<(String, bool)>>, path: &NamespacePath, alias: &Option<String>, node: &Node) -> Result<NamespacePath, ()> {
+        if let Some(module_items) = items.get(path) {
+            match alias {
+                Some(alias) => self.bind_import(alias.clone(), Import::Module(path.clone()), node)?,
+                None => {
+                    // importing a module brings all of its public items into scope
+                    for (name, _) in module_items.iter().filter(|(_, is_pub)| *is_pub) {
+                        self.bind_import(name.clone(), Import::Item(path.clone(), name.clone()), node)?;
+                    }
+                }
+            }
+            return Ok(path.clone());
+        }
+        let (name, module) = path.get_segments().split_last().unwrap();
+        let module = NamespacePath::new(module.to_vec());
+        let Some(module_items) = items.get(&module) else {
+            report!(self, Error::UnresolvedImport(path.display()), node, ErrorInfo {
+                info: Some("There is no module with this path.".to_string()),
+                help: Some("Module paths are relative to the project root (e.g. 'util/math.sn' is 'util::math').".to_string()),
+                ..Default::default()
+            });
+        };
+        match module_items.iter().find(|(item, _)| item == name) {
+            Some((_, true)) => self.bind_import(alias.clone().unwrap_or(name.clone()), Import::Item(module.clone(), name.clone()), node)?,
+            Some((_, false)) => report!(self, Error::PrivateImport(name.clone(), module.display()), node, ErrorInfo {
+                info: Some("This item is private.".to_string()),
+                help: Some(format!("Mark '{}' as 'public' to import it from other modules.", name)),
+                ..Default::default()
+            }),
+            None => report!(self, Error::UnresolvedImport(path.display()), node, ErrorInfo {
+                info: Some(format!("Module '{}' has no item named '{}'.", module, name)),
+                ..Default::default()
+            }),
+        }
+        Ok(module)
+    }
+
+    fn bind_import(&mut self, name: String, import: Import, node: &Node) -> Result<(), ()> {
+        let imports = self.imports.get_mut(&self.current_module).unwrap();
+        match imports.get(&name) {
+            Some(existing) if *existing != import => report!(self, Error::VariableAlreadyDeclared(name.clone()), node, ErrorInfo {
+                info: Some(format!("'{}' is already imported by another import.", name)),
+                help: Some("Use 'as' to import it under a different name.".to_string()),
+                ..Default::default()
+            }),
+            Some(_) => {}
+            None => {
+                imports.insert(name, import);
+            }
+        }
+        Ok(())
+    }
+
+    /// Walks the imports depth first, `stack` holds the modules that are
+    /// currently being visited so reaching one of them again means there is a cycle.
+    fn check_import_cycles(&mut self, module: &NamespacePath, dependencies: &HashMap<NamespacePath, Vec<(NamespacePath, Node)>>,
+                           stack: &mut Vec<NamespacePath>, finished: &mut Vec<NamespacePath>) -> Result<(), ()> {
+        if finished.contains(module) {
+            return Ok(());
+        }
+        stack.push(module.clone());
+        for (dependency, node) in dependencies.get(module).into_iter().flatten() {
+            if let Some(start) = stack.iter().position(|m| m == dependency) {
+                let cycle = stack[start..].iter().chain(std::iter::once(dependency)).map(|m| m.display()).collect::<Vec<_>>().join(" -> ");
+                self.enter_module(module);
+                report!(self, Error::ImportCycle(cycle), node, ErrorInfo {
+                    info: Some("This import closes the cycle.".to_string()),
+                    help: Some("Move the items these modules share into a separate module.".to_string()),
+                    ..Default::default()
+                });
+            }
+            self.check_import_cycles(dependency, dependencies, stack, finished)?;
+        }
+        stack.pop();
+        finished.push(module.clone());
+        Ok(())
+    }
+
+    /// Looks up an item of another module through a module import (e.g. `math::max`).
+    fn lookup_module_item(&mut self, module: &NamespacePath, name: &str, node: &Node) -> Result<Symbol, ()> {
+        let Some(symbol) = self.globals.get(module).and_then(|globals| globals.get(name)).cloned() else {
+            report!(self, Error::UnknownVariable(format!("{}::{}", module, name)), node, ErrorInfo {
+                info: Some(format!("Module '{}' has no item named '{}'.", module, name)),
+                ..Default::default()
+            });
+        };
+        let is_pub = match &symbol {
+            Symbol::Function( function ) => function.get_ast().get_attrs().as_ref().is_some_and(|a| a.is_pub()),
+            Symbol::Type(UnificationType::Known(Type::Object { id, .. })) => self.objects[*id].get_attrs().is_pub(),
+            _ => true,
+        };
+        // private items can be used anywhere in the file they are declared in
+        if !is_pub && self.file_module(module) != self.file_module(&self.current_module) {
+            report!(self, Error::PrivateImport(name.to_string(), module.display()), node, ErrorInfo {
+                info: Some("This item is private.".to_string()),
+                help: Some(format!("Mark '{}' as 'public' to use it from other modules.", name)),
+                ..Default::default()
+            });
+        }
+        Ok(symbol)
+    }
+
+    fn check_module(&mut self, module: &Module<Node>) -> Module<TypedNode> {
+        self.enter_module(module.get_path());
+        let mut new_module = Module::<TypedNode>::new(module.get_path().clone(), module.get_file_name().clone());
+        let mut new_top = Vec::new();
+        if let AST::TopLevel (nodes) = module.get_top() {
+            // a failing declaration does not stop the rest from being checked
+            for node in nodes.iter().filter(|n| !matches!(n.get_kind(), AST::Import( .. ))) {
+                if let Ok(typed) = self.check_node(node.clone()) {
+                    new_top.push(typed);
+                }
+            }
+        }
+        new_module.set_top(AST::TopLevel(new_top));
+        new_module
+    }
+
+    fn enter_module(&mut self, path: &NamespacePath) {
+        self.current_module = path.clone();
+        self.globals.entry(path.clone()).or_default();
+    }
+
+    /// The symbols declared at the top level of the module being checked.
+    fn get_globals(&self) -> &HashMap<String, Symbol> {
+        &self.globals[&self.current_module]
+    }
+
+    fn insert_global(&mut self, name: String, symbol: Symbol) {
+        self.globals.get_mut(&self.current_module).unwrap().insert(name, symbol);
+    }
+
+    /// Declares the generic parameters in the current scope and resolves their bounds and defaults.
+    fn declare_generics(&mut self, generics: &[GenericDecl]) -> Result<Vec<GenericDecl<UnificationType>>, ()> {
+        let mut generic_types = Vec::new();
+        for generic in generics {
+            self.insert_symbol(generic.get_name().clone(), Symbol::Type(UnificationType::Generic(generic.get_name().clone())));
+        }
+        for generic in generics {
+            let mut impls_ty = Vec::new();
+            for impl_ty in generic.get_impls() {
+                impls_ty.push(self.get_type(impl_ty.clone())?);
+            }
+            let mut default_ty = None;
+            if let Some(default) = generic.get_default() {
+                default_ty = Some(self.get_type(default.clone())?);
+            }
+            generic_types.push(GenericDecl::new(generic.get_name().clone(), impls_ty, default_ty));
+        }
+        for (generic, typed) in generics.iter().zip(&generic_types) {
+            for (bound, ty) in generic.get_impls().iter().zip(typed.get_impls()) {
+                if !matches!(ty, UnificationType::Known(Type::Object { id, .. }) if self.objects[*id].is_interface()) {
+                    report!(self, Error::UnexpectedItem("interface".to_string(), self.display_type(ty)), bound.get_ast(), ErrorInfo {
+                        info: Some("Generic parameters can only be bound by interfaces.".to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+            self.declare_generic(typed);
+        }
+        Ok(generic_types)
+    }
+
+    /// Brings a generic parameter and its bounds into the current scope.
+    fn declare_generic(&mut self, generic: &GenericDecl<UnificationType>) {
+        self.insert_symbol(generic.get_name().clone(), Symbol::Type(UnificationType::Generic(generic.get_name().clone())));
+        self.generic_bounds.last_mut().unwrap().insert(generic.get_name().clone(), generic.get_impls().clone());
+    }
+
+    fn bounds_of(&self, generic: &str) -> Vec<UnificationType> {
+        self.generic_bounds.iter().rev().find_map(|bounds| bounds.get(generic)).cloned().unwrap_or_default()
+    }
+
+    /// The type itself followed by every class and interface it can be used as.
+    fn supertypes(&self, ty: &UnificationType) -> Vec<UnificationType> {
+        let mut supertypes = vec![self.resolve(ty)];
+        let mut i = 0;
+        while i < supertypes.len() {
+            let next = match &supertypes[i] {
+                UnificationType::Known(Type::Object { id, generics }) => {
+                    let map = self.class_generics_map(*id, generics);
+                    self.objects[*id].get_parent().into_iter().chain(self.objects[*id].get_interfaces()).map(|t| Self::substitute(t, &map)).collect()
+                }
+                UnificationType::Generic( name ) => self.bounds_of(name),
+                _ => Vec::new(),
+            };
+            for ty in next {
+                if !supertypes.iter().any(|t| self.same_type(t, &ty)) {
+                    supertypes.push(ty);
+                }
+            }
+            i += 1;
+        }
+        supertypes
+    }
+
+    fn implements(&self, ty: &UnificationType, interface: &UnificationType) -> bool {
+        self.supertypes(ty).iter().any(|t| self.same_type(t, interface))
+    }
+
+    /// Whether two types are the same without unifying them.
+    fn same_type(&self, a: &UnificationType, b: &UnificationType) -> bool {
+        match (self.resolve(a), self.resolve(b)) {
+            (UnificationType::Known(x), UnificationType::Known(y)) => match (x, y) {
+                (Type::Integer { size: a, signed: b }, Type::Integer { size: c, signed: d }) => a == c && b == d,
+                (Type::Float { size: a }, Type::Float { size: b }) => a == b,
+                (Type::Bool, Type::Bool) | (Type::String, Type::String) | (Type::Void, Type::Void) => true,
+                (Type::Object { id: a, generics: x }, Type::Object { id: b, generics: y }) => {
+                    a == b && x.len() == y.len() && x.iter().zip(&y).all(|(x, y)| self.same_type(x, y))
+                }
+                (Type::Function { args: a, ret: x }, Type::Function { args: b, ret: y }) => {
+                    a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| self.same_type(a, b)) && self.same_type(&x, &y)
+                }
+                (Type::Pointer { ty: x }, Type::Pointer { ty: y }) | (Type::Reference { ty: x }, Type::Reference { ty: y }) => self.same_type(&x, &y),
+                _ => false,
+            },
+            (UnificationType::TypeVariable(x), UnificationType::TypeVariable(y)) => x == y,
+            (UnificationType::Generic(x), UnificationType::Generic(y)) => x == y,
+            _ => false,
+        }
+    }
+
+    /// Makes sure the generic arguments of a type or a function implement the
+    /// interfaces their parameters are bound by. Arguments that are not known
+    /// yet are checked later by `check_pending_bounds`.
+    fn require_bounds(&mut self, generics: &[GenericDecl<UnificationType>], args: &[UnificationType], map: &HashMap<String, UnificationType>, owner: &str, node: &Node)
+        -> Result<(), ()> {
+        for (generic, arg) in generics.iter().zip(args) {
+            for bound in generic.get_impls() {
+                let bound = Self::substitute(bound, map);
+                match self.resolve(arg) {
+                    UnificationType::Generic( .. ) => if !self.implements(arg, &bound) {
+                        report!(self, Error::UnsatisfiedBound(self.display_type(arg), self.display_type(&bound)), node, ErrorInfo {
+                            info: Some(format!("Required by the generic parameter '{}' of '{}'.", generic.get_name(), owner)),
+                            help: Some(self.bound_help(arg, &bound)),
+                            ..Default::default()
+                        });
+                    },
+                    _ => self.pending_bounds.push((arg.clone(), bound, node.get_location().unwrap().clone(), generic.get_name().clone(), owner.to_string())),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_pending_bounds(&mut self) -> Result<(), ()> {
+        for (arg, bound, location, generic, owner) in std::mem::take(&mut self.pending_bounds) {
+            let arg = self.resolve_deep(&arg);
+            // arguments nothing is known about are reported by `finalize_types`
+            let unknown = matches!(arg, UnificationType::TypeVariable(var) if self.substitutions[var].1 == VariableKind::Any);
+            if unknown || self.implements(&arg, &bound) {
+                continue;
+            }
+            self.reports.add_error(CompileError::new(Error::UnsatisfiedBound(self.display_type(&arg), self.display_type(&bound)), location).with_info(ErrorInfo {
+                info: Some(format!("Required by the generic parameter '{}' of '{}'.", generic, owner)),
+                help: Some(self.bound_help(&arg, &bound)),
+                ..Default::default()
+            }));
+            return Err(());
+        }
+        Ok(())
+    }
+
+    fn bound_help(&self, arg: &UnificationType, bound: &UnificationType) -> String {
+        match arg {
+            UnificationType::Generic( name ) => format!("Add '{}' to the bounds of '{}' (e.g. '{}: {}').", self.display_type(bound), name, name, self.display_type(bound)),
+            UnificationType::Known(Type::Object { id, .. }) if !self.objects[*id].is_interface() && self.objects[*id].get_variants().is_none() => {
+                format!("List '{}' after the ':' of class '{}' and implement its methods.", self.display_type(bound), self.objects[*id].get_name())
+            }
+            _ => format!("Use a type that implements '{}'.", self.display_type(bound)),
+        }
+    }
+
+    fn declare_function(&mut self, module: &Module<Node>, node: &mut Node, class_path: Option<NamespacePath>) -> Result<usize, ()> {
+        let AST::FuncDef( name, args, ret, .., generics, id ) = node.get_kind().clone() else {
+            panic!("Expected FuncDef");
+        };
+        assert!(id.is_none());
+        self.add_scope();
+        let generic_types = match generics {
+            Some(generics) => Some(self.declare_generics(&generics)?),
+            None => None,
+        };
+        let mut typed_args = Vec::new();
+        for param in args {
+            let ty = self.get_type(param.get_ty().clone())?;
+            let default = self.check_default_value(&param, &ty, typed_args.last())?;
+            typed_args.push(Param::new(param.get_name().clone(), ty, default, param.is_mutable(), param.get_location().clone()));
+        }
+        let ret = self.get_type(ret.clone())?;
+        self.remove_scope();
+        let id = self.functions.len();
+        if let AST::FuncDef( .., id_ref ) = node.get_kind_mut() {
+            *id_ref = Some(id);
+        }
+        let typed_node = TypedNode::new(AST::FuncDef(name.clone(), typed_args, ret, None, generic_types, Some(id)), node.get_attrs().cloned())
+            .with_location(node.get_location().cloned());
+        self.functions.push((Self::get_path_for_name(module, name, class_path), FunctionSymbol::new(typed_node)));
+        Ok(id)
+    }
+
+    /// Default values are filled in at every call that leaves them out, so
+    /// they have to be literals that mean the same in every scope. Parameters
+    /// after one with a default need a default too.
+    fn check_default_value(&mut self, param: &Param, ty: &UnificationType, previous: Option<&Param<TypedNode, UnificationType>>) -> Result<Option<TypedNode>, ()> {
+        let Some(default) = param.get_default() else {
+            if previous.is_some_and(|p| p.get_default().is_some()) {
+                self.reports.add_error(CompileError::new(Error::InvalidDefaultParameter(param.get_name().clone(), "needs a default value".to_string()), param.get_location().clone())
+                    .with_info(ErrorInfo {
+                        info: Some(format!("The parameter before '{}' has a default value.", param.get_name())),
+                        help: Some("Move the parameters with default values to the end.".to_string()),
+                        ..Default::default()
+                    }));
+                return Err(());
+            }
+            return Ok(None);
+        };
+        if param.get_name() == "self" {
+            report!(self, Error::InvalidDefaultParameter("self".to_string(), "cannot have a default value".to_string()), default);
+        }
+        let is_literal = |node: &Node| matches!(node.get_kind(), AST::Int( _ ) | AST::Float( _ ) | AST::String( _ ) | AST::Bool( _ ));
+        let is_constant = match default.get_kind() {
+            AST::BinaryOp( BinaryOp::Sub | BinaryOp::Not, operand, _, true ) => is_literal(operand),
+            _ => is_literal(default),
+        };
+        if !is_constant {
+            report!(self, Error::InvalidDefaultParameter(param.get_name().clone(), "has a default value that is not a literal".to_string()), default, ErrorInfo {
+                info: Some("Default values are evaluated where the function is called.".to_string()),
+                help: Some("Use a number, a string or a boolean.".to_string()),
+                ..Default::default()
+            });
+        }
+        let mut typed = self.check_expr(default)?;
+        self.constrain(ty.clone(), typed.get_type().clone(), Some(param.get_location().clone()), &typed)?;
+        self.finalize_types(&mut typed)?;
+        Ok(Some(typed))
+    }
+
+    /// Declares the name of a class, an enum or an interface, they are all objects.
+    fn declare_class(&mut self, module: &Module<Node>, node: &mut Node) -> Result<(), ()> {
+        let (AST::ClassDef( name, .., generics, _ ) | AST::EnumDef( name, _, generics, _ ) | AST::InterfaceDef( name, _, generics, _ )) = node.get_kind().clone() else {
+            panic!("Expected ClassDef, EnumDef or InterfaceDef");
+        };
+        if self.types.contains_key(&name) || self.get_globals().contains_key(&name) {
+            report!(self, Error::VariableAlreadyDeclared(name.clone()), node, ErrorInfo {
+                info: Some(format!("Type '{}' already declared.", name)),
+                help: Some("Make sure the type is not declared twice.".to_string()),
+                ..Default::default()
+            });
+        }
+        let id = self.objects.len();
+        if let AST::ClassDef( .., id_ref ) | AST::EnumDef( .., id_ref ) | AST::InterfaceDef( .., id_ref ) = node.get_kind_mut() {
+            *id_ref = Some(id);
+        }
+        // only the generic names are known here, bounds and defaults are
+        // resolved by `define_class` once every class has been declared.
+        let generics = generics.iter().map(|g| GenericDecl::new(g.get_name().clone(), Vec::new(), None)).collect();
+        let attrs = node.get_attrs().cloned().unwrap_or_else(AttrHandler::new);
+        self.objects.push(Object::new(id, name.clone(), Self::get_path_for_name(module, name.clone(), None), generics, attrs, node.get_location().cloned()));
+        self.objects[id].is_interface = matches!(node.get_kind(), AST::InterfaceDef( .. ));
+        self.insert_global(name, Symbol::Type(UnificationType::Known(Type::Object { id, generics: Vec::new() })));
+        Ok(())
+    }
+
+    fn define_class(&mut self, module: &Module<Node>, node: &mut Node) -> Result<(), ()> {
+        let AST::ClassDef( name, parent, interfaces, members, _, generics, id ) = node.get_kind().clone() else {
+            panic!("Expected ClassDef");
+        };
+        let id = id.unwrap();
+        self.add_scope();
+        self.objects[id].generics = self.declare_generics(&generics)?;
+        let is_interface = |this: &Self, ty: &UnificationType| matches!(ty, UnificationType::Known(Type::Object { id, .. }) if this.objects[*id].is_interface());
+        if let Some(parent) = parent {
+            let parent_ty = self.get_type(parent.clone())?;
+            if is_interface(self, &parent_ty) {
+                self.objects[id].interfaces.push(parent_ty);
+            } else {
+                if !matches!(parent_ty, UnificationType::Known(Type::Object { .. })) {
+                    report!(self, Error::UnexpectedItem("class".to_string(), self.display_type(&parent_ty)), parent.get_ast(), ErrorInfo {
+                        info: Some("Only classes can be inherited from.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                self.objects[id].parent = Some(parent_ty);
+            }
+        }
+        for interface in interfaces {
+            let interface_ty = self.get_type(interface.clone())?;
+            if !is_interface(self, &interface_ty) {
+                report!(self, Error::UnexpectedItem("interface".to_string(), self.display_type(&interface_ty)), interface.get_ast(), ErrorInfo {
+                    info: Some("Only the first type after ':' can be the parent class, the rest are interfaces.".to_string()),
+                    ..Default::default()
+                });
+            }
+            self.objects[id].interfaces.push(interface_ty);
+        }
+        let mut typed_members: Vec<ClassMember<UnificationType>> = Vec::new();
+        for member in members {
+            if typed_members.iter().any(|m| m.get_name() == member.get_name()) {
+                report!(self, Error::VariableAlreadyDeclared(member.get_name().clone()), member.get_ty().get_ast(), ErrorInfo {
+                    info: Some(format!("Member '{}' already declared in class '{}'.", member.get_name(), name)),
+                    help: Some("Class members must have unique names.".to_string()),
+                    ..Default::default()
+                });
+            }
+            let ty = self.get_type(member.get_ty().clone())?;
+            typed_members.push(ClassMember::new(member.get_name().clone(), ty, member.get_attrs().clone()));
+        }
+        self.objects[id].members = typed_members;
+        let class_path = NamespacePath::new(vec![name.clone()]);
+        if let AST::ClassDef( _, _, _, _, methods, .. ) = node.get_kind_mut() {
+            for method in methods.iter_mut() {
+                let AST::FuncDef( method_name, .. ) = method.get_kind().clone() else {
+                    panic!("Expected FuncDef");
+                };
+                if self.objects[id].methods.contains_key(&method_name) || self.objects[id].members.iter().any(|m| *m.get_name() == method_name) {
+                    report!(self, Error::VariableAlreadyDeclared(method_name.clone()), method, ErrorInfo {
+                        info: Some(format!("'{}' is already declared in class '{}'.", method_name, name)),
+                        help: Some("Methods and members of a class must have unique names.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let function = self.declare_function(module, method, Some(class_path.clone()))?;
+                self.objects[id].methods.insert(method_name, function);
+            }
+        }
+        self.remove_scope();
+        Ok(())
+    }
+
+    fn define_interface(&mut self, module: &Module<Node>, node: &mut Node) -> Result<(), ()> {
+        let AST::InterfaceDef( name, _, generics, id ) = node.get_kind().clone() else {
+            panic!("Expected InterfaceDef");
+        };
+        let id = id.unwrap();
+        self.add_scope();
+        self.objects[id].generics = self.declare_generics(&generics)?;
+        let interface_path = NamespacePath::new(vec![name.clone()]);
+        if let AST::InterfaceDef( _, methods, .. ) = node.get_kind_mut() {
+            for method in methods.iter_mut() {
+                let AST::FuncDef( method_name, .. ) = method.get_kind().clone() else {
+                    panic!("Expected FuncDef");
+                };
+                if self.objects[id].methods.contains_key(&method_name) {
+                    report!(self, Error::VariableAlreadyDeclared(method_name.clone()), method, ErrorInfo {
+                        info: Some(format!("'{}' is already declared in interface '{}'.", method_name, name)),
+                        ..Default::default()
+                    });
+                }
+                let function = self.declare_function(module, method, Some(interface_path.clone()))?;
+                if !self.function_takes_self(function) {
+                    report!(self, Error::UnexpectedItem("instance method".to_string(), "static method".to_string()), method, ErrorInfo {
+                        info: Some("Interface methods are called on instances, so they need a 'self' parameter.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                self.objects[id].methods.insert(method_name, function);
+            }
+        }
+        self.remove_scope();
+        Ok(())
+    }
+
+    fn define_enum(&mut self, node: &Node) -> Result<(), ()> {
+        let AST::EnumDef( name, variants, generics, id ) = node.get_kind() else {
+            panic!("Expected EnumDef");
+        };
+        let id = id.unwrap();
+        self.add_scope();
+        self.objects[id].generics = self.declare_generics(generics)?;
+        let mut typed_variants: Vec<EnumVariant<UnificationType>> = Vec::new();
+        for variant in variants {
+            if typed_variants.iter().any(|v| v.get_name() == variant.get_name()) {
+                self.reports.add_error(CompileError::new(Error::VariableAlreadyDeclared(variant.get_name().clone()), variant.get_location().clone())
+                    .with_info(ErrorInfo {
+                        info: Some(format!("Variant '{}' already declared in enum '{}'.", variant.get_name(), name)),
+                        help: Some("Variants of an enum must have unique names.".to_string()),
+                        ..Default::default()
+                    }));
+                return Err(());
+            }
+            let mut fields: Vec<(String, UnificationType)> = Vec::new();
+            for (field, ty) in variant.get_fields() {
+                if fields.iter().any(|(f, _)| f == field) {
+                    report!(self, Error::VariableAlreadyDeclared(field.clone()), ty.get_ast(), ErrorInfo {
+                        info: Some(format!("Field '{}' already declared in variant '{}'.", field, variant.get_name())),
+                        ..Default::default()
+                    });
+                }
+                fields.push((field.clone(), self.get_type(ty.clone())?));
+            }
+            typed_variants.push(EnumVariant::new(variant.get_name().clone(), variant.get_kind(), fields, variant.get_location().clone()));
+        }
+        self.objects[id].variants = Some(typed_variants);
+        self.remove_scope();
+        Ok(())
+    }
+
+    /// Looks for a method in a class and all of its ancestors.
+    pub fn find_method(&self, object: usize, name: &str) -> Option<usize> {
+        let mut current = Some(object);
+        let mut visited = Vec::new();
+        while let Some(id) = current {
+            if visited.contains(&id) {
+                break;
+            }
+            visited.push(id);
+            if let Some(function) = self.objects[id].get_methods().get(name) {
+                return Some(*function);
+            }
+            current = self.objects[id].get_parent_id();
+        }
+        None
+    }
+
+    fn is_abstract_function(&self, function: usize) -> bool {
+        self.functions[function].1.get_ast().get_attrs().as_ref().is_some_and(|a| a.get_attrs().contains(&AstAttrs::Abstract))
+    }
+
+    fn check_class_hierarchy(&mut self, node: &Node) -> Result<(), ()> {
+        let AST::ClassDef( name, parent, _, _, methods, _, id ) = node.get_kind() else {
+            panic!("Expected ClassDef");
+        };
+        let id = id.unwrap();
+        let parent_id = self.objects[id].get_parent_id();
+        if let Some(parent_id) = parent_id {
+            let parent_node = parent.as_ref().unwrap().get_ast();
+            let mut ancestor = Some(parent_id);
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    report!(self, Error::CyclicInheritance(name.clone()), parent_node, ErrorInfo {
+                        info: Some("This class ends up inheriting from itself.".to_string()),
+                        help: Some("Make sure the class hierarchy does not contain cycles.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                ancestor = self.objects[ancestor_id].get_parent_id();
+            }
+            if self.objects[parent_id].get_attrs().get_attrs().contains(&AstAttrs::Final) {
+                report!(self, Error::InheritFromFinal(self.objects[parent_id].get_name().clone()), parent_node, ErrorInfo {
+                    info: Some("This class is marked as 'final'.".to_string()),
+                    note: Some("Final classes are used to specify that a class cannot be inherited from".to_string()),
+                    see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/final-classes".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        let is_abstract_class = self.objects[id].get_attrs().get_attrs().contains(&AstAttrs::Abstract);
+        for method in methods {
+            let AST::FuncDef( method_name, .. ) = method.get_kind() else {
+                panic!("Expected FuncDef");
+            };
+            let attrs = method.get_attrs().cloned().unwrap_or_else(AttrHandler::new);
+            let overridden = parent_id.and_then(|parent_id| self.find_method(parent_id, method_name));
+            if attrs.get_attrs().contains(&AstAttrs::Override) && overridden.is_none() {
+                report!(self, Error::NothingToOverride(method_name.clone()), method, ErrorInfo {
+                    info: Some("No parent class declares this method.".to_string()),
+                    help: Some("Remove the 'override' keyword or fix the method name.".to_string()),
+                    ..Default::default()
+                });
+            } else if !attrs.get_attrs().contains(&AstAttrs::Override) && overridden.is_some() {
+                report!(self, Error::MissingOverride(method_name.clone()), method, ErrorInfo {
+                    info: Some("A parent class already declares this method.".to_string()),
+                    help: Some("Mark the method as 'override' if it is meant to replace the parent one.".to_string()),
+                    ..Default::default()
+                });
+            }
+            if attrs.get_attrs().contains(&AstAttrs::Abstract) && !is_abstract_class {
+                report!(self, Error::AbstractMethodInConcreteClass(method_name.clone(), name.clone()), method, ErrorInfo {
+                    info: Some("Abstract methods can only be declared inside of abstract classes.".to_string()),
+                    help: Some(format!("Mark '{}' as 'abstract' or give the method a body.", name)),
+                    see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/abstract-classes".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        if !is_abstract_class {
+            // every abstract method of the ancestors must resolve to a concrete implementation
+            let mut ancestor = parent_id;
+            while let Some(ancestor_id) = ancestor {
+                let mut abstract_methods = self.objects[ancestor_id].get_methods().iter()
+                    .filter(|(_, function)| self.is_abstract_function(**function))
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>();
+                abstract_methods.sort();
+                for method in abstract_methods {
+                    let implementation = self.find_method(id, &method).unwrap();
+                    if self.is_abstract_function(implementation) {
+                        report!(self, Error::UnimplementedAbstractMethod(name.clone(), method.clone()), node, ErrorInfo {
+                            info: Some(format!("'{}' is declared as abstract in '{}'.", method, self.objects[ancestor_id].get_name())),
+                            help: Some(format!("Implement '{}' with the 'override' keyword or mark '{}' as 'abstract'.", method, name)),
+                            ..Default::default()
+                        });
+                    }
+                }
+                ancestor = self.objects[ancestor_id].get_parent_id();
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes sure a class has every method of the interfaces it implements,
+    /// with the signatures the interfaces declare.
+    fn check_interfaces(&mut self, node: &Node) -> Result<(), ()> {
+        let AST::ClassDef( name, .., id ) = node.get_kind() else {
+            panic!("Expected ClassDef");
+        };
+        let id = id.unwrap();
+        let class_generics = self.objects[id].get_generics().iter().map(|g| UnificationType::Generic(g.get_name().clone())).collect::<Vec<_>>();
+        for interface in self.objects[id].get_interfaces().clone() {
+            let UnificationType::Known(Type::Object { id: interface_id, generics }) = &interface else {
+                panic!("Expected an interface");
+            };
+            let interface_map = self.class_generics_map(*interface_id, generics);
+            let interface_name = self.display_type(&interface);
+            let mut methods = self.objects[*interface_id].get_methods().iter().map(|(name, function)| (name.clone(), *function)).collect::<Vec<_>>();
+            methods.sort();
+            for (method, declared) in methods {
+                let signature = self.display_signature(declared, &interface_map);
+                let Some((function, class_map)) = self.find_method_in_type(id, &class_generics, &method) else {
+                    report!(self, Error::MissingInterfaceMethod(name.clone(), method.clone(), interface_name.clone()), node, ErrorInfo {
+                        info: Some(format!("'{}' declares it as '{}'.", interface_name, signature)),
+                        help: Some(format!("Add a public method '{}' to '{}'.", method, name)),
+                        ..Default::default()
+                    });
+                };
+                if let Some(reason) = self.signature_mismatch(declared, &interface_map, function, &class_map) {
+                    report!(self, Error::InterfaceMethodMismatch(method, interface_name.clone(), reason), self.functions[function].1.get_ast(), ErrorInfo {
+                        info: Some(format!("'{}' declares it as '{}'.", interface_name, signature)),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// How a method differs from the one an interface declares, the generic
+    /// parameters of both methods are matched by their position.
+    fn signature_mismatch(&self, declared: usize, interface_map: &HashMap<String, UnificationType>, function: usize, class_map: &HashMap<String, UnificationType>) -> Option<String> {
+        let signature = |function: usize| match self.functions[function].1.get_ast().get_kind() {
+            AST::FuncDef( _, args, ret, _, generics, _ ) => (args.clone(), ret.clone(), generics.clone().unwrap_or_default()),
+            _ => panic!("Expected FuncDef"),
+        };
+        let (declared_args, declared_ret, declared_generics) = signature(declared);
+        let (args, ret, generics) = signature(function);
+        if !self.functions[function].1.get_ast().get_attrs().as_ref().is_some_and(|a| a.is_pub()) {
+            return Some("is not public".to_string());
+        }
+        if !self.function_takes_self(function) {
+            return Some("does not take 'self'".to_string());
+        }
+        if generics.len() != declared_generics.len() {
+            return Some(format!("takes {} generic parameter(s) instead of {}", generics.len(), declared_generics.len()));
+        }
+        let mut map = interface_map.clone();
+        map.extend(declared_generics.iter().zip(&generics).map(|(d, g)| (d.get_name().clone(), UnificationType::Generic(g.get_name().clone()))));
+        let declared_args = declared_args.iter().filter(|p| p.get_name() != "self").collect::<Vec<_>>();
+        let args = args.iter().filter(|p| p.get_name() != "self").collect::<Vec<_>>();
+        if args.len() != declared_args.len() {
+            return Some(format!("takes {} parameter(s) instead of {}", args.len(), declared_args.len()));
+        }
+        for (param, declared_param) in args.iter().zip(declared_args) {
+            let (expected, found) = (Self::substitute(declared_param.get_ty(), &map), Self::substitute(param.get_ty(), class_map));
+            if !self.same_type(&expected, &found) {
+                return Some(format!("takes '{}' instead of '{}' as parameter '{}'", self.display_type(&found), self.display_type(&expected), param.get_name()));
+            }
+        }
+        let (expected, found) = (Self::substitute(&declared_ret, &map), Self::substitute(&ret, class_map));
+        if !self.same_type(&expected, &found) {
+            return Some(format!("returns '{}' instead of '{}'", self.display_type(&found), self.display_type(&expected)));
+        }
+        None
+    }
+
+    /// Writes the signature of a function the way it is declared, e.g. `func show(self) String`.
+    fn display_signature(&self, function: usize, map: &HashMap<String, UnificationType>) -> String {
+        let AST::FuncDef( name, args, ret, .. ) = self.functions[function].1.get_ast().get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let name = name.rsplit("::").next().unwrap();
+        let args = args.iter().map(|param| match param.get_name().as_str() {
+            "self" => "self".to_string(),
+            _ => format!("{}: {}", param.get_name(), self.display_type(&Self::substitute(param.get_ty(), map))),
+        }).collect::<Vec<_>>();
+        format!("func {}({}) {}", name, args.join(", "), self.display_type(&Self::substitute(ret, map)))
+    }
+
+    pub fn check_node(&mut self, node: Node) -> Result<TypedNode, ()> {
+        match node.get_kind() {
+            AST::FuncDef( .., id ) => self.check_function(&node, id.unwrap(), None),
+            AST::ClassDef( _, parent, _, _, methods, _, id ) => {
+                let id = id.unwrap();
+                let typed_methods = self.check_methods(id, methods)?;
+                let object = &self.objects[id];
+                let typed_parent = parent.as_ref().and(object.get_parent().cloned());
+                let typed = AST::ClassDef(object.get_path().display(), typed_parent, object.get_interfaces().clone(), object.get_members().clone(), typed_methods,
+                    object.get_generics().clone(), Some(id));
+                Ok(TypedNode::new(typed, node.get_attrs().cloned())
+                    .with_type(UnificationType::Known(Type::Object { id, generics: object.get_generics().iter().map(|g| UnificationType::Generic(g.get_name().clone())).collect() }))
+                    .with_location(node.get_location().cloned()))
+            }
+            AST::InterfaceDef( _, methods, _, id ) => {
+                let id = id.unwrap();
+                let typed_methods = self.check_methods(id, methods)?;
+                let object = &self.objects[id];
+                let typed = AST::InterfaceDef(object.get_path().display(), typed_methods, object.get_generics().clone(), Some(id));
+                Ok(TypedNode::new(typed, node.get_attrs().cloned())
+                    .with_type(UnificationType::Known(Type::Object { id, generics: object.get_generics().iter().map(|g| UnificationType::Generic(g.get_name().clone())).collect() }))
+                    .with_location(node.get_location().cloned()))
+            }
+            AST::EnumDef( .., id ) => {
+                let object = &self.objects[id.unwrap()];
+                let typed = AST::EnumDef(object.get_path().display(), object.get_variants().unwrap().clone(), object.get_generics().clone(), *id);
+                Ok(TypedNode::new(typed, node.get_attrs().cloned())
+                    .with_type(UnificationType::Known(Type::Object { id: id.unwrap(), generics: object.get_generics().iter().map(|g| UnificationType::Generic(g.get_name().clone())).collect() }))
+                    .with_location(node.get_location().cloned()))
+            }
+            _ => panic!("Unexpected global node"),
+        }
+    }
+
+    /// Checks the methods of a class or an interface with its generic parameters in scope.
+    fn check_methods(&mut self, id: usize, methods: &[Node]) -> Result<Vec<TypedNode>, ()> {
+        self.add_scope();
+        for generic in self.objects[id].get_generics().clone() {
+            self.declare_generic(&generic);
+        }
+        let mut typed_methods = Vec::new();
+        let mut failed = false;
+        for method in methods {
+            let AST::FuncDef( .., function ) = method.get_kind() else {
+                panic!("Expected FuncDef");
+            };
+            // keep going so every method gets its errors reported
+            match self.check_function(method, function.unwrap(), Some(id)) {
+                Ok(typed) => typed_methods.push(typed),
+                Err(()) => failed = true,
+            }
+        }
+        self.remove_scope();
+        if failed {
+            return Err(());
+        }
+        Ok(typed_methods)
+    }
+
+    fn check_function(&mut self, node: &Node, id: usize, class: Option<usize>) -> Result<TypedNode, ()> {
+        let AST::FuncDef( name, _, ret_ast, body, .. ) = node.get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        let (path, symbol) = self.functions[id].clone();
+        let AST::FuncDef( _, args, ret, _, generics, _ ) = symbol.get_ast().get_kind().clone() else {
+            panic!("Expected FuncDef");
+        };
+        let Some(body) = body else {
+            let typed = AST::FuncDef(path.display(), args, ret, None, generics, Some(id));
+            return Ok(TypedNode::new(typed, node.get_attrs().cloned()).with_type(symbol.get_type()).with_location(node.get_location().cloned()));
+        };
+        self.add_scope();
+        for generic in generics.iter().flatten() {
+            self.declare_generic(generic);
+        }
+        for param in &args {
+            self.declare_variable(param.get_name().clone(), param.get_ty().clone(), Some(param.get_location()));
+        }
+        self.infer_ctx = Some(ret.clone());
+        self.return_location = ret_ast.get_ast().get_location().cloned();
+        self.current_class = class;
+        self.loop_depth = 0;
+        // the bounds are checked while the generic parameters are still in scope
+        let result = self.check_stmt(body).and_then(|body| self.check_pending_bounds().map(|_| body));
+        self.pending_bounds.clear();
+        self.remove_scope();
+        self.infer_ctx = None;
+        self.current_class = None;
+        let mut typed_body = result?;
+        self.finalize_types(&mut typed_body)?;
+        if !matches!(ret, UnificationType::Known(Type::Void)) && !Self::always_returns(&typed_body) {
+            report!(self, Error::MissingReturn(name.clone(), self.display_type(&ret)), node, ErrorInfo {
+                info: Some("Not every path of this function returns a value.".to_string()),
+                help: Some("Add a 'return' statement at the end of the function.".to_string()),
+                ..Default::default()
+            });
+        }
+        let typed = AST::FuncDef(path.display(), args, ret, Some(typed_body), generics, Some(id));
+        Ok(TypedNode::new(typed, node.get_attrs().cloned()).with_type(symbol.get_type()).with_location(node.get_location().cloned()))
+    }
+
+    /// Whether every path through a statement ends in a `return`.
+    fn always_returns(node: &TypedNode) -> bool {
+        match node.get_kind() {
+            AST::Return( .. ) => true,
+            AST::Block( nodes ) => nodes.iter().any(Self::always_returns),
+            AST::If( _, then, els ) => {
+                // the last 'else' branch must not be an 'else if' for the chain to be exhaustive
+                let has_else = els.last().is_some_and(|e| !matches!(e.get_kind(), AST::If( _, _, e ) if e.is_empty()));
+                has_else && Self::always_returns(then) && els.iter().all(|e| match e.get_kind() {
+                    AST::If( _, then, _ ) => Self::always_returns(then),
+                    _ => Self::always_returns(e),
+                })
+            }
+            // every value is matched by one of the arms
+            AST::Match( _, arms ) => arms.iter().all(|arm| Self::always_returns(arm.get_body())),
+            _ => false,
+        }
+    }
+
+    fn check_stmt(&mut self, node: &Node) -> Result<TypedNode, ()> {
+        let void = UnificationType::Known(Type::Void);
+        let typed = match node.get_kind() {
+            AST::Block( nodes ) => {
+                self.add_scope();
+                let mut typed_nodes = Vec::new();
+                let mut result = Ok(());
+                for node in nodes {
+                    match self.check_stmt(node) {
+                        Ok(typed) => typed_nodes.push(typed),
+                        Err(()) => {
+                            result = Err(());
+                            break;
+                        }
+                    }
+                }
+                self.remove_scope();
+                result?;
+                AST::Block(typed_nodes)
+            }
+            AST::VarDef( name, ty, value ) => {
+                if self.scope.last().unwrap().contains_key(name) {
+                    report!(self, Error::VariableAlreadyDeclared(name.clone()), node, ErrorInfo {
+                        info: Some(format!("Variable '{}' already declared in this scope.", name)),
+                        help: Some("Make sure the variable is not declared twice in the same scope.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let var_ty = match ty {
+                    Some(ty) => self.get_type(ty.clone())?,
+                    None => self.new_type_variable(VariableKind::Any),
+                };
+                let typed_value = match value {
+                    Some(value) => {
+                        let typed_value = self.check_expr(value)?;
+                        let expected_location = ty.as_ref().and_then(|ty| ty.get_ast().get_location().cloned());
+                        self.constrain(var_ty.clone(), typed_value.get_type().clone(), expected_location, &typed_value)?;
+                        Some(typed_value)
+                    }
+                    None => None,
+                };
+                self.declare_variable(name.clone(), var_ty.clone(), node.get_location());
+                AST::VarDef(name.clone(), Some(var_ty), typed_value)
+            }
+            AST::Return( value ) => {
+                let ret = self.infer_ctx.clone().unwrap();
+                let typed_value = match value {
+                    Some(value) => Some(self.check_expr(value)?),
+                    None => None,
+                };
+                let found = typed_value.as_ref().map(|v| v.get_type().clone()).unwrap_or(void.clone());
+                let expected_location = self.return_location.clone();
+                match &typed_value {
+                    Some(typed_value) => self.constrain(ret, found, expected_location, typed_value)?,
+                    None => {
+                        let location = TypedNode::new(AST::Empty, None).with_location(node.get_location().cloned());
+                        self.constrain(ret, found, expected_location, &location)?
+                    }
+                }
+                AST::Return(typed_value)
+            }
+            AST::Break | AST::Continue => {
+                if self.loop_depth == 0 {
+                    let keyword = if let AST::Break = node.get_kind() { "break" } else { "continue" };
+                    report!(self, Error::OutsideOfLoop(keyword.to_string()), node, ErrorInfo {
+                        info: Some(format!("'{}' can only be used inside of a loop.", keyword)),
+                        ..Default::default()
+                    });
+                }
+                if let AST::Break = node.get_kind() { AST::Break } else { AST::Continue }
+            }
+            AST::If( cond, then, els ) => {
+                let typed_cond = self.check_condition(cond)?;
+                let typed_then = self.check_stmt(then)?;
+                let mut typed_els = Vec::new();
+                for e in els {
+                    typed_els.push(self.check_stmt(e)?);
+                }
+                AST::If(typed_cond, typed_then, typed_els)
+            }
+            AST::While( cond, body, is_do_while ) => {
+                let typed_cond = self.check_condition(cond)?;
+                self.loop_depth += 1;
+                let typed_body = self.check_stmt(body);
+                self.loop_depth -= 1;
+                AST::While(typed_cond, typed_body?, *is_do_while)
+            }
+            AST::Empty => AST::Empty,
+            AST::Match( scrutinee, arms ) => self.check_match(node, scrutinee, arms, true)?.0,
+            _ => return self.check_expr(node),
+        };
+        Ok(TypedNode::new(typed, node.get_attrs().cloned()).with_type(void).with_location(node.get_location().cloned()))
+    }
+
+    fn check_condition(&mut self, cond: &Node) -> Result<TypedNode, ()> {
+        let typed_cond = self.check_expr(cond)?;
+        self.constrain(UnificationType::Known(Type::Bool), typed_cond.get_type().clone(), None, &typed_cond)?;
+        Ok(typed_cond)
+    }
+
+    fn check_expr(&mut self, node: &Node) -> Result<TypedNode, ()> {
+        let (typed, ty) = match node.get_kind() {
+            AST::Int( value ) => (AST::Int(*value), self.new_type_variable(VariableKind::Integer)),
+            AST::Float( value ) => (AST::Float(*value), self.new_type_variable(VariableKind::Float)),
+            AST::Bool( value ) => (AST::Bool(*value), UnificationType::Known(Type::Bool)),
+            AST::String( value ) => (AST::String(value.clone()), UnificationType::Known(Type::String)),
+            AST::Ident( name, generics ) => {
+                let Some(symbol) = self.lookup_variable(name) else {
+                    report!(self, Error::UnknownVariable(name.clone()), node, ErrorInfo {
+                        info: Some(format!("Variable '{}' not found!", name)),
+                        help: Some("Make sure the variable is declared in the current scope.".to_string()),
+                        ..Default::default()
+                    });
+                };
+                self.check_value(symbol, name, generics, node)?
+            }
+            AST::NamespaceAccess( base, variant, _ ) if self.enum_of(base).is_some() => {
+                self.check_variant_init(node, base, variant, VariantKind::Unit, Vec::new(), Some(node))?
+            }
+            AST::Call( callee, args ) if matches!(callee.get_kind(), AST::NamespaceAccess( base, .. ) if self.enum_of(base).is_some()) => {
+                let AST::NamespaceAccess( base, variant, _ ) = callee.get_kind() else {
+                    unreachable!();
+                };
+                let fields = args.iter().enumerate().map(|(i, arg)| (i.to_string(), arg)).collect();
+                self.check_variant_init(node, base, variant, VariantKind::Tuple, fields, Some(callee))?
+            }
+            AST::VariantInit( path, variant, fields ) => {
+                if self.enum_of(path.get_ast()).is_none() {
+                    report!(self, Error::UnexpectedItem("enum".to_string(), Self::node_to_path(path.get_ast())), path.get_ast(), ErrorInfo {
+                        info: Some("Only enum variants can be created with named fields.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let fields = fields.iter().map(|(name, value)| (name.clone(), value)).collect();
+                self.check_variant_init(node, path.get_ast(), variant, VariantKind::Struct, fields, None)?
+            }
+            AST::Match( scrutinee, arms ) => self.check_match(node, scrutinee, arms, false)?,
+            AST::NamespaceAccess( base, name, generics ) if self.get_module(base).is_some() => {
+                let module = self.get_module(base).unwrap();
+                let symbol = self.lookup_module_item(&module, name, node)?;
+                self.check_value(symbol, name, generics, node)?
+            }
+            AST::NamespaceAccess( base, name, explicit ) => {
+                // static methods are accessed through their class (e.g. 'Math::max')
+                let base_ty = match self.lookup_path(base) {
+                    Some(Symbol::Type(UnificationType::Known(Type::Object { .. }))) => Some(self.get_type(AstType::new(base.clone()))?),
+                    // reports the segment of the path that does not exist
+                    None => {
+                        self.get_symbol(base.clone())?;
+                        None
+                    }
+                    _ => None,
+                };
+                let Some(UnificationType::Known(Type::Object { id, generics })) = base_ty else {
+                    report!(self, Error::UnknownVariable(format!("{}::{}", Self::node_to_path(base), name)), node, ErrorInfo {
+                        info: Some("This path does not point to anything.".to_string()),
+                        ..Default::default()
+                    });
+                };
+                let Some((function, class_generics)) = self.find_method_in_type(id, &generics, name) else {
+                    report!(self, Error::UnknownMember(self.display_type(&UnificationType::Known(Type::Object { id, generics: generics.clone() })), name.clone()), node);
+                };
+                if self.function_takes_self(function) {
+                    report!(self, Error::UnexpectedItem("static method".to_string(), "instance method".to_string()), node, ErrorInfo {
+                        info: Some(format!("'{}' needs an instance to be called on.", name)),
+                        help: Some("Call the method on an instance of the class instead.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                self.check_method_privacy(function, node)?;
+                let declaration = self.functions[function].1.get_ast().get_location().cloned();
+                self.add_reference(Self::name_location(node, name).as_ref(), declaration.as_ref());
+                let (params, ret, generic_args) = self.instantiate_function(function, explicit.clone(), &class_generics, node)?;
+                let ty = UnificationType::Known(Type::Function { args: params.into_iter().map(|(_, ty)| ty).collect(), ret: Box::new(ret) });
+                // the generic arguments of the class come before the ones of the method
+                let owner = self.get_function_owner(function).unwrap();
+                let generic_args = self.objects[owner].get_generics().iter()
+                    .map(|g| class_generics[g.get_name()].clone())
+                    .chain(generic_args.into_iter().flatten())
+                    .collect::<Vec<_>>();
+                (AST::Ident(self.functions[function].0.display(), Some(generic_args).filter(|g| !g.is_empty())), ty)
+            }
+            AST::ClassAccess( base, name ) => {
+                let typed_base = self.check_expr(base)?;
+                // the methods of a generic value come from the interfaces it is bound by
+                let base_ty = match self.resolve(typed_base.get_type()) {
+                    UnificationType::Generic( generic ) => self.bound_with_method(&generic, name).unwrap_or(UnificationType::Generic(generic)),
+                    ty => ty,
+                };
+                let UnificationType::Known(Type::Object { id, generics }) = base_ty else {
+                    if let UnificationType::Generic( generic ) = &base_ty {
+                        report!(self, Error::UnknownMember(generic.clone(), name.clone()), node, ErrorInfo {
+                            info: Some(format!("None of the interfaces '{}' is bound by have a method named '{}'.", generic, name)),
+                            help: Some(format!("Bound '{}' by an interface with this method (e.g. '{}: Interface').", generic, generic)),
+                            ..Default::default()
+                        });
+                    }
+                    if let UnificationType::TypeVariable( .. ) = base_ty {
+                        report!(self, Error::TypeAnnotationsNeeded, base, ErrorInfo {
+                            info: Some("The type of this value must be known at this point.".to_string()),
+                            help: Some("Add a type annotation to the variable declaration.".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                    report!(self, Error::UnknownMember(self.display_type(&base_ty), name.clone()), node, ErrorInfo {
+                        info: Some("Only class instances have members.".to_string()),
+                        ..Default::default()
+                    });
+                };
+                if let Some((owner, member_ty, attrs)) = self.find_member(id, &generics, name) {
+                    self.check_member_privacy((owner, attrs.is_pub()), node)?;
+                    (AST::ClassAccess(typed_base, name.clone()), member_ty)
+                } else if let Some((function, class_generics)) = self.find_method_in_type(id, &generics, name) {
+                    if !self.function_takes_self(function) {
+                        report!(self, Error::UnexpectedItem("instance method".to_string(), "static method".to_string()), node, ErrorInfo {
+                            info: Some(format!("'{}' does not take 'self'.", name)),
+                            help: Some(format!("Call it through the class instead (e.g. '{}::{}').", self.objects[id].get_name(), name)),
+                            ..Default::default()
+                        });
+                    }
+                    self.check_method_privacy(function, node)?;
+                    let declaration = self.functions[function].1.get_ast().get_location().cloned();
+                    self.add_reference(Self::name_location(node, name).as_ref(), declaration.as_ref());
+                    let (params, ret, _) = self.instantiate_function(function, None, &class_generics, node)?;
+                    let ty = UnificationType::Known(Type::Function { args: params.into_iter().map(|(_, ty)| ty).collect(), ret: Box::new(ret) });
+                    (AST::ClassAccess(typed_base, name.clone()), ty)
+                } else {
+                    report!(self, Error::UnknownMember(self.display_type(&UnificationType::Known(Type::Object { id, generics })), name.clone()), node, ErrorInfo {
+                        info: Some(format!("No member or method named '{}'.", name)),
+                        ..Default::default()
+                    });
+                }
+            }
+            // `assert` is built in, unless the program declares something named 'assert' itself
+            AST::Call( callee, args ) if matches!(callee.get_kind(), AST::Ident( name, None ) if name == "assert" && self.lookup_variable(name).is_none()) => {
+                if args.len() != 1 {
+                    report!(self, Error::ArgumentCountMismatch(1, args.len()), node, ErrorInfo {
+                        info: Some("'assert' takes the condition that must hold.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let condition = self.check_expr(&args[0])?;
+                self.constrain(UnificationType::Known(Type::Bool), condition.get_type().clone(), None, &condition)?;
+                let ty = UnificationType::Known(Type::Function { args: vec![UnificationType::Known(Type::Bool)], ret: Box::new(UnificationType::Known(Type::Void)) });
+                let typed_callee = TypedNode::new(AST::Ident(BUILTIN_ASSERT.to_string(), None), None).with_type(ty).with_location(callee.get_location().cloned());
+                (AST::Call(typed_callee, vec![condition]), UnificationType::Known(Type::Void))
+            }
+            AST::Call( callee, args ) => {
+                let typed_callee = self.check_expr(callee)?;
+                let callee_ty = self.resolve(typed_callee.get_type());
+                let UnificationType::Known(Type::Function { args: params, ret }) = callee_ty else {
+                    report!(self, Error::NotCallable(self.display_type(&callee_ty)), callee, ErrorInfo {
+                        info: Some("This is not a function.".to_string()),
+                        ..Default::default()
+                    });
+                };
+                let defaults = match self.called_function(&typed_callee) {
+                    Some(function) => self.default_arguments(function, args.len()),
+                    None => Vec::new(),
+                };
+                if params.len() != args.len() + defaults.len() {
+                    report!(self, Error::ArgumentCountMismatch(params.len(), args.len()), node, ErrorInfo {
+                        info: Some(format!("This function takes {} argument(s).", params.len())),
+                        ..Default::default()
+                    });
+                }
+                let mut typed_args = Vec::new();
+                for (param, arg) in params.iter().zip(args) {
+                    let typed_arg = self.check_expr(arg)?;
+                    self.constrain(param.clone(), typed_arg.get_type().clone(), None, &typed_arg)?;
+                    typed_args.push(typed_arg);
+                }
+                typed_args.extend(defaults);
+                (AST::Call(typed_callee, typed_args), *ret)
+            }
+            AST::ClassInit( ty, args ) => self.check_class_init(node, ty, args)?,
+            AST::BinaryOp( op, lhs, rhs, is_unary ) => self.check_binary_op(node, op, lhs, rhs, *is_unary)?,
+            AST::Assign( target, value, op ) => {
+                if !matches!(target.get_kind(), AST::Ident( .. ) | AST::ClassAccess( .. ) | AST::BinaryOp( BinaryOp::Index, .. )) {
+                    report!(self, Error::InvalidAssignment, target, ErrorInfo {
+                        info: Some("Only variables, members and indexes can be assigned to.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let typed_target = self.check_expr(target)?;
+                if let AST::Ident( .. ) = typed_target.get_kind() {
+                    if let UnificationType::Known(Type::Function { .. }) = self.resolve(typed_target.get_type()) {
+                        report!(self, Error::InvalidAssignment, target, ErrorInfo {
+                            info: Some("Functions cannot be assigned to.".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                }
+                let typed_value = self.check_expr(value)?;
+                let ty = typed_target.get_type().clone();
+                // `a += b` assigns the result of `a + b`
+                let value_ty = match op {
+                    Some(op) => self.binary_op_type(node, op, &typed_target, &typed_value)?,
+                    None => typed_value.get_type().clone(),
+                };
+                self.constrain(ty.clone(), value_ty, typed_target.get_location().cloned(), &typed_value)?;
+                (AST::Assign(typed_target, typed_value, op.clone()), ty)
+            }
+            _ => report!(self, Error::UnexpectedItem("expression".to_string(), "statement".to_string()), node),
+        };
+        Ok(TypedNode::new(typed, node.get_attrs().cloned()).with_type(ty).with_location(node.get_location().cloned()))
+    }
+
+    /// Checks a name that is used as a value.
+    fn check_value(&mut self, symbol: Symbol, name: &String, generics: &Option<Vec<AstType>>, node: &Node) -> Result<(AST<TypedNode, UnificationType>, UnificationType), ()> {
+        match symbol {
+            Symbol::Variable( ty ) => {
+                if generics.is_some() {
+                    report!(self, Error::TooManyGenerics(0, generics.as_ref().unwrap().len()), node, ErrorInfo {
+                        info: Some("Variables cannot take generic arguments.".to_string()),
+                        ..Default::default()
+                    });
+                }
+                let declaration = self.lookup_declaration(name);
+                self.add_reference(node.get_location(), declaration.as_ref());
+                Ok((AST::Ident(name.clone(), None), ty))
+            }
+            Symbol::Function( function ) => {
+                let id = function.get_id();
+                self.add_reference(Self::name_location(node, name).as_ref(), function.get_ast().get_location());
+                let (params, ret, generic_args) = self.instantiate_function(id, generics.clone(), &HashMap::new(), node)?;
+                let ty = UnificationType::Known(Type::Function { args: params.into_iter().map(|(_, ty)| ty).collect(), ret: Box::new(ret) });
+                Ok((AST::Ident(self.functions[id].0.display(), generic_args), ty))
+            }
+            Symbol::Type( .. ) => report!(self, Error::UnexpectedItem("value".to_string(), "type".to_string()), node, ErrorInfo {
+                info: Some("This is a type, not a value.".to_string()),
+                help: Some("Use 'new' to create an instance of a class.".to_string()),
+                ..Default::default()
+            }),
+            Symbol::Module( .. ) => report!(self, Error::UnexpectedItem("value".to_string(), "module".to_string()), node, ErrorInfo {
+                info: Some("This is a module, not a value.".to_string()),
+                help: Some(format!("Use '{}::<item>' to refer to an item of the module.", name)),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// The module a path refers to when it names an imported module or a namespace.
+    fn get_module(&self, node: &Node) -> Option<NamespacePath> {
+        match node.get_kind() {
+            AST::Ident( _, None ) | AST::NamespaceAccess( _, _, None ) => match self.lookup_path(node) {
+                Some(Symbol::Module( module )) => Some(module),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// What a name or a path like `a::b::c` refers to, without reporting
+    /// anything when it does not exist.
+    fn lookup_path(&self, node: &Node) -> Option<Symbol> {
+        match node.get_kind() {
+            AST::Ident( name, _ ) => self.lookup_variable(name),
+            AST::NamespaceAccess( base, name, _ ) => match self.lookup_path(base)? {
+                Symbol::Module( module ) => self.globals.get(&module)?.get(name).cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn check_class_init(&mut self, node: &Node, ty: &AstType, args: &[Node]) -> Result<(AST<TypedNode, UnificationType>, UnificationType), ()> {
+        // generic arguments can be left out and get inferred from the constructor call
+        let inferred = match ty.get_ast().get_kind() {
+            AST::Ident( _, None ) | AST::NamespaceAccess( _, _, None ) => match self.lookup_path(ty.get_ast()) {
+                Some(Symbol::Type(UnificationType::Known(Type::Object { id, .. }))) if !self.objects[id].get_generics().is_empty() => {
+                    Some(self.infer_generics(id, node)?)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let class_ty = match inferred {
+            Some(ty) => ty,
+            None => self.get_type(ty.clone())?,
+        };
+        let UnificationType::Known(Type::Object { id, generics }) = class_ty.clone() else {
+            report!(self, Error::UnexpectedItem("class".to_string(), self.display_type(&class_ty)), ty.get_ast(), ErrorInfo {
+                info: Some("Only classes can be created with 'new'.".to_string()),
+                ..Default::default()
+            });
+        };
+        if self.objects[id].is_interface() {
+            report!(self, Error::UnexpectedItem("class".to_string(), self.display_type(&class_ty)), ty.get_ast(), ErrorInfo {
+                info: Some("This is an interface.".to_string()),
+                help: Some("Create an instance of a class that implements it instead.".to_string()),
+                ..Default::default()
+            });
+        }
+        if self.objects[id].get_variants().is_some() {
+            report!(self, Error::UnexpectedItem("class".to_string(), self.display_type(&class_ty)), ty.get_ast(), ErrorInfo {
+                info: Some("This is an enum.".to_string()),
+                help: Some(format!("Enums are created through their variants (e.g. '{}::<variant>').", self.objects[id].get_name())),
+                ..Default::default()
+            });
+        }
+        if self.objects[id].get_attrs().get_attrs().contains(&AstAttrs::Abstract) {
+            report!(self, Error::InstantiateAbstract(self.objects[id].get_name().clone()), node, ErrorInfo {
+                info: Some("This class is marked as 'abstract'.".to_string()),
+                note: Some("Abstract classes are used to specify that a class cannot be instantiated".to_string()),
+                see: Some("https://snowball-lang.gitbook.io/docs/language-reference/classes/abstract-classes".to_string()),
+                ..Default::default()
+            });
+        }
+        let (params, defaults) = match self.find_method_in_type(id, &generics, "constructor") {
+            Some((function, class_generics)) => {
+                self.check_method_privacy(function, node)?;
+                (self.instantiate_function(function, None, &class_generics, node)?.0, self.default_arguments(function, args.len()))
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        if params.len() != args.len() + defaults.len() {
+            report!(self, Error::ArgumentCountMismatch(params.len(), args.len()), node, ErrorInfo {
+                info: Some(format!("The constructor of '{}' takes {} argument(s).", self.objects[id].get_name(), params.len())),
+                help: Some("Declare a 'constructor' method to initialize the class with arguments.".to_string()),
+                ..Default::default()
+            });
+        }
+        let mut typed_args = Vec::new();
+        for ((_, param), arg) in params.iter().zip(args) {
+            let typed_arg = self.check_expr(arg)?;
+            self.constrain(param.clone(), typed_arg.get_type().clone(), None, &typed_arg)?;
+            typed_args.push(typed_arg);
+        }
+        typed_args.extend(defaults);
+        Ok((AST::ClassInit(class_ty.clone(), typed_args), class_ty))
+    }
+
+    /// The enum a path like `Shape` or `shapes::Shape` refers to, with the
+    /// generic arguments written after its name.
+    fn enum_of(&self, node: &Node) -> Option<(usize, Option<Vec<AstType>>)> {
+        let generics = match node.get_kind() {
+            AST::Ident( _, generics ) | AST::NamespaceAccess( _, _, generics ) => generics.clone(),
+            _ => return None,
+        };
+        match self.lookup_path(node)? {
+            Symbol::Type(UnificationType::Known(Type::Object { id, .. })) if self.objects[id].get_variants().is_some() => Some((id, generics)),
+            _ => None,
+        }
+    }
+
+    /// The type of the enum a path refers to. Generic arguments that are not
+    /// written get inferred from how the value is used.
+    fn enum_type(&mut self, path: &Node) -> Result<UnificationType, ()> {
+        let (id, generics) = self.enum_of(path).unwrap();
+        if let AST::NamespaceAccess( module, name, _ ) = path.get_kind() {
+            let module = self.get_module(module).unwrap();
+            self.lookup_module_item(&module, name, path)?;
+        }
+        let declaration = self.objects[id].get_location().cloned();
+        self.add_reference(path.get_location(), declaration.as_ref());
+        if generics.is_some() {
+            let Symbol::Type( ty ) = self.handle_symbol(Symbol::Type(UnificationType::Known(Type::Object { id, generics: Vec::new() })), path.clone())? else {
+                panic!("Expected a type");
+            };
+            return Ok(ty);
+        }
+        self.infer_generics(id, path)
+    }
+
+    /// An object type whose generic arguments are inferred from how it is used.
+    fn infer_generics(&mut self, id: usize, node: &Node) -> Result<UnificationType, ()> {
+        let generics = (0..self.objects[id].get_generics().len()).map(|_| self.new_type_variable(VariableKind::Any)).collect::<Vec<_>>();
+        let declared = self.objects[id].get_generics().clone();
+        let map = self.class_generics_map(id, &generics);
+        let owner = self.objects[id].get_name().clone();
+        self.require_bounds(&declared, &generics, &map, &owner, node)?;
+        Ok(UnificationType::Known(Type::Object { id, generics }))
+    }
+
+    /// Looks up a variant of an enum with the generic arguments of `generics`
+    /// applied to the types of its fields.
+    fn find_variant(&self, id: usize, generics: &[UnificationType], name: &str) -> Option<EnumVariant<UnificationType>> {
+        let map = self.class_generics_map(id, generics);
+        let variant = self.objects[id].get_variants()?.iter().find(|v| v.get_name() == name)?;
+        let fields = variant.get_fields().iter().map(|(field, ty)| (field.clone(), Self::substitute(ty, &map))).collect();
+        Some(EnumVariant::new(variant.get_name().clone(), variant.get_kind(), fields, variant.get_location().clone()))
+    }
+
+    /// Why a variant cannot be written with `found` fields and how it is written instead.
+    fn variant_kind_mismatch(variant: &EnumVariant<UnificationType>, path: &str, found: VariantKind) -> Option<(String, String)> {
+        let names = variant.get_fields().iter().map(|(field, _)| field.clone()).collect::<Vec<_>>();
+        match (variant.get_kind(), found) {
+            (expected, found) if expected == found => None,
+            (VariantKind::Unit, _) => Some(("has no fields".to_string(), format!("Write it as '{}'.", path))),
+            (VariantKind::Tuple, _) => Some((format!("has {} unnamed field(s)", names.len()), format!("Write it as '{}({})'.", path, vec!["_"; names.len()].join(", ")))),
+            (VariantKind::Struct, _) => Some(("has named fields".to_string(), format!("Write it as '{} {{ {} }}'.", path, names.join(", ")))),
+        }
+    }
+
+    /// Checks the creation of an enum value, e.g. `Shape::Empty`, `Shape::Circle(1.0)`
+    /// or `Shape::Rect { w: 1.0, h: 2.0 }`. `access` is the `Enum::Variant` node
+    /// the variant name is at, if there is one.
+    fn check_variant_init(&mut self, node: &Node, path: &Node, variant: &str, kind: VariantKind, fields: Vec<(String, &Node)>, access: Option<&Node>)
+        -> Result<(AST<TypedNode, UnificationType>, UnificationType), ()> {
+        let enum_ty = self.enum_type(path)?;
+        let UnificationType::Known(Type::Object { id, generics }) = enum_ty.clone() else {
+            panic!("Expected an object type");
+        };
+        let enum_name = self.objects[id].get_name().clone();
+        let Some(declared) = self.find_variant(id, &generics, variant) else {
+            report!(self, Error::UnknownVariant(enum_name.clone(), variant.to_string()), node, ErrorInfo {
+                info: Some(format!("'{}' has the variants {}.", enum_name, self.variant_names(id))),
+                ..Default::default()
+            });
+        };
+        self.add_reference(access.and_then(|a| Self::name_location(a, variant)).as_ref(), Some(declared.get_location()));
+        let variant_path = format!("{}::{}", enum_name, variant);
+        if let Some((reason, help)) = Self::variant_kind_mismatch(&declared, &variant_path, kind) {
+            report!(self, Error::InvalidVariant(variant_path, reason), node, ErrorInfo {
+                help: Some(help),
+                ..Default::default()
+            });
+        }
+        if kind == VariantKind::Tuple && fields.len() != declared.get_fields().len() {
+            report!(self, Error::ArgumentCountMismatch(declared.get_fields().len(), fields.len()), node, ErrorInfo {
+                info: Some(format!("'{}' has {} field(s).", variant_path, declared.get_fields().len())),
+                ..Default::default()
+            });
+        }
+        for (i, (field, value)) in fields.iter().enumerate() {
+            if !declared.get_fields().iter().any(|(f, _)| f == field) {
+                report!(self, Error::InvalidVariant(variant_path.clone(), format!("has no field named '{}'", field)), value, ErrorInfo {
+                    info: Some(format!("The fields of '{}' are {}.", variant_path, self.field_names(&declared))),
+                    ..Default::default()
+                });
+            }
+            if fields[..i].iter().any(|(f, _)| f == field) {
+                report!(self, Error::InvalidVariant(variant_path.clone(), format!("is given the field '{}' more than once", field)), value);
+            }
+        }
+        // the fields are evaluated in the order they are declared in
+        let mut typed_fields = Vec::new();
+        for (field, ty) in declared.get_fields() {
+            let Some((_, value)) = fields.iter().find(|(f, _)| f == field) else {
+                report!(self, Error::InvalidVariant(variant_path.clone(), format!("is missing the field '{}'", field)), node, ErrorInfo {
+                    help: Some(format!("Every field of '{}' needs a value.", variant_path)),
+                    ..Default::default()
+                });
+            };
+            let typed_value = self.check_expr(value)?;
+            self.constrain(ty.clone(), typed_value.get_type().clone(), None, &typed_value)?;
+            typed_fields.push((field.clone(), typed_value));
+        }
+        Ok((AST::VariantInit(enum_ty.clone(), variant.to_string(), typed_fields), enum_ty))
+    }
+
+    fn variant_names(&self, id: usize) -> String {
+        self.objects[id].get_variants().unwrap().iter().map(|v| format!("'{}'", v.get_name())).collect::<Vec<_>>().join(", ")
+    }
+
+    fn field_names(&self, variant: &EnumVariant<UnificationType>) -> String {
+        variant.get_fields().iter().map(|(field, _)| format!("'{}'", field)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Checks a match. The arms of a match used as a statement can be blocks
+    /// and their values are ignored, otherwise every arm is an expression of
+    /// the type of the whole match.
+    fn check_match(&mut self, node: &Node, scrutinee: &Node, arms: &[MatchArm], is_statement: bool) -> Result<(AST<TypedNode, UnificationType>, UnificationType), ()> {
+        let typed_scrutinee = self.check_expr(scrutinee)?;
+        let ty = typed_scrutinee.get_type().clone();
+        let result = if is_statement { UnificationType::Known(Type::Void) } else { self.new_type_variable(VariableKind::Any) };
+        let mut result_location = None;
+        let mut typed_arms = Vec::new();
+        for arm in arms {
+            self.add_scope();
+            let typed_arm = self.check_match_arm(arm, &ty, &result, &mut result_location, is_statement);
+            self.remove_scope();
+            typed_arms.push(typed_arm?);
+        }
+        self.check_exhaustiveness(node, &ty, &typed_arms)?;
+        Ok((AST::Match(typed_scrutinee, typed_arms), result))
+    }
+
+    fn check_match_arm(&mut self, arm: &MatchArm, ty: &UnificationType, result: &UnificationType, result_location: &mut Option<SourceLocation>, is_statement: bool)
+        -> Result<MatchArm<TypedNode, UnificationType>, ()> {
+        let pattern = self.check_pattern(arm.get_pattern(), ty, &mut Vec::new())?;
+        let guard = match arm.get_guard() {
+            Some(guard) => Some(self.check_condition(guard)?),
+            None => None,
+        };
+        let body = arm.get_body();
+        let typed_body = match body.get_kind() {
+            AST::Block( .. ) if is_statement => self.check_stmt(body)?,
+            AST::Block( .. ) => report!(self, Error::UnexpectedItem("expression".to_string(), "block".to_string()), body, ErrorInfo {
+                info: Some("The value of this match is used, so its arms have to be expressions.".to_string()),
+                help: Some("Use the match as a statement to run blocks in its arms.".to_string()),
+                ..Default::default()
+            }),
+            _ => {
+                let typed_body = self.check_expr(body)?;
+                if !is_statement {
+                    self.constrain(result.clone(), typed_body.get_type().clone(), result_location.clone(), &typed_body)?;
+                    result_location.get_or_insert_with(|| typed_body.get_location().cloned().unwrap());
+                }
+                typed_body
+            }
+        };
+        Ok(MatchArm::new(pattern, guard, typed_body))
+    }
+
+    /// Checks a pattern against the type of the value it matches, declaring
+    /// its bindings in the current scope. Typed variant patterns name their
+    /// enum and have a pattern for every field, in the order they are declared in.
+    fn check_pattern(&mut self, pattern: &Pattern, ty: &UnificationType, bindings: &mut Vec<String>) -> Result<Pattern<UnificationType>, ()> {
+        let location = pattern.get_location().unwrap().clone();
+        let literal = |this: &mut Self, literal_ty: UnificationType| {
+            let node = TypedNode::new(AST::Empty, None).with_location(Some(location.clone()));
+            this.constrain(ty.clone(), literal_ty, None, &node)
+        };
+        let kind = match pattern.get_kind() {
+            PatternKind::Wildcard => PatternKind::Wildcard,
+            PatternKind::Int( value ) => {
+                let literal_ty = self.new_type_variable(VariableKind::Integer);
+                literal(self, literal_ty)?;
+                PatternKind::Int(*value)
+            }
+            PatternKind::String( value ) => {
+                literal(self, UnificationType::Known(Type::String))?;
+                PatternKind::String(value.clone())
+            }
+            PatternKind::Bool( value ) => {
+                literal(self, UnificationType::Known(Type::Bool))?;
+                PatternKind::Bool(*value)
+            }
+            // a single name is a unit variant if the matched enum has one with that name
+            PatternKind::Binding( name ) => match self.resolve(ty) {
+                UnificationType::Known(Type::Object { id, generics }) if self.find_variant(id, &generics, name).is_some_and(|v| v.get_kind() == VariantKind::Unit) => {
+                    PatternKind::Variant(Some(self.resolve(ty)), name.clone(), VariantKind::Unit, Vec::new(), false)
+                }
+                _ => {
+                    if bindings.contains(name) {
+                        report!(self, Error::VariableAlreadyDeclared(name.clone()), pattern, ErrorInfo {
+                            info: Some(format!("'{}' is already bound by this pattern.", name)),
+                            ..Default::default()
+                        });
+                    }
+                    bindings.push(name.clone());
+                    self.declare_variable(name.clone(), ty.clone(), Some(&location));
+                    PatternKind::Binding(name.clone())
+                }
+            },
+            PatternKind::Variant( path, variant, kind, fields, has_rest ) => {
+                let resolved = self.resolve(ty);
+                let enum_ty = match path {
+                    Some(path) => {
+                        let Some((id, _)) = self.enum_of(path.get_ast()) else {
+                            report!(self, Error::UnexpectedItem("enum".to_string(), Self::node_to_path(path.get_ast())), path.get_ast(), ErrorInfo {
+                                info: Some("Only enum variants can be matched with a path.".to_string()),
+                                ..Default::default()
+                            });
+                        };
+                        let path_ty = self.enum_type(path.get_ast())?;
+                        if !matches!(resolved, UnificationType::Known(Type::Object { id: expected, .. }) if expected == id) {
+                            self.constrain(ty.clone(), path_ty, None, &TypedNode::new(AST::Empty, None).with_location(Some(location.clone())))?;
+                        }
+                        self.resolve(ty)
+                    }
+                    None => resolved,
+                };
+                let UnificationType::Known(Type::Object { id, generics }) = enum_ty.clone() else {
+                    if let UnificationType::TypeVariable( .. ) = enum_ty {
+                        report!(self, Error::TypeAnnotationsNeeded, pattern, ErrorInfo {
+                            info: Some("The type of the matched value must be known at this point.".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                    report!(self, Error::UnknownVariant(self.display_type(&enum_ty), variant.clone()), pattern, ErrorInfo {
+                        info: Some(format!("'{}' is not an enum.", self.display_type(&enum_ty))),
+                        ..Default::default()
+                    });
+                };
+                let enum_name = self.objects[id].get_name().clone();
+                let Some(declared) = self.find_variant(id, &generics, variant) else {
+                    let info = match self.objects[id].get_variants() {
+                        Some(_) => format!("'{}' has the variants {}.", enum_name, self.variant_names(id)),
+                        None => format!("'{}' is not an enum.", enum_name),
+                    };
+                    report!(self, Error::UnknownVariant(enum_name, variant.clone()), pattern, ErrorInfo {
+                        info: Some(info),
+                        ..Default::default()
+                    });
+                };
+                let variant_path = format!("{}::{}", enum_name, variant);
+                if let Some((reason, help)) = Self::variant_kind_mismatch(&declared, &variant_path, *kind) {
+                    report!(self, Error::InvalidVariant(variant_path, reason), pattern, ErrorInfo {
+                        help: Some(help),
+                        ..Default::default()
+                    });
+                }
+                if *kind == VariantKind::Tuple && fields.len() != declared.get_fields().len() {
+                    report!(self, Error::InvalidVariant(variant_path.clone(), format!("has {} field(s) but the pattern has {}", declared.get_fields().len(), fields.len())), pattern);
+                }
+                for (field, field_pattern) in fields {
+                    if !declared.get_fields().iter().any(|(f, _)| f == field) {
+                        report!(self, Error::InvalidVariant(variant_path.clone(), format!("has no field named '{}'", field)), field_pattern, ErrorInfo {
+                            info: Some(format!("The fields of '{}' are {}.", variant_path, self.field_names(&declared))),
+                            ..Default::default()
+                        });
+                    }
+                }
+                let mut typed_fields = Vec::new();
+                for (field, field_ty) in declared.get_fields() {
+                    let typed_field = match fields.iter().find(|(f, _)| f == field) {
+                        Some((_, field_pattern)) => self.check_pattern(field_pattern, field_ty, bindings)?,
+                        None if *has_rest => Pattern::new(PatternKind::Wildcard, location.clone()),
+                        None => report!(self, Error::InvalidVariant(variant_path.clone(), format!("is matched without the field '{}'", field)), pattern, ErrorInfo {
+                            help: Some("Add the field to the pattern or use '..' to ignore the remaining fields.".to_string()),
+                            ..Default::default()
+                        }),
+                    };
+                    typed_fields.push((field.clone(), typed_field));
+                }
+                PatternKind::Variant(Some(enum_ty), variant.clone(), *kind, typed_fields, false)
+            }
+        };
+        Ok(Pattern::new(kind, location))
+    }
+
+    /// Reports the arms that can never run and the values no arm matches.
+    /// Arms with a guard don't cover anything since the guard could be false.
+    fn check_exhaustiveness(&mut self, node: &Node, ty: &UnificationType, arms: &[MatchArm<TypedNode, UnificationType>]) -> Result<(), ()> {
+        let ty = self.resolve_deep(ty);
+        let mut rows: Vec<Vec<Space>> = Vec::new();
+        for arm in arms {
+            let space = Space::from_pattern(arm.get_pattern());
+            if !self.is_useful(&rows, std::slice::from_ref(&space), std::slice::from_ref(&ty)) {
+                self.reports.add_error(CompileError::warning(Error::UnreachablePattern, arm.get_pattern().get_location().unwrap().clone()).with_info(ErrorInfo {
+                    info: Some("The arms before this one match every value it matches.".to_string()),
+                    help: Some("Remove this arm or move it before the arms that hide it.".to_string()),
+                    ..Default::default()
+                }));
+            }
+            if arm.get_guard().is_none() {
+                rows.push(vec![space]);
+            }
+        }
+        let missing = self.missing_patterns(&rows, std::slice::from_ref(&ty));
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let mut patterns = missing.iter().take(MAX_MISSING_PATTERNS).map(|row| format!("'{}'", self.display_space(&row[0], &ty))).collect::<Vec<_>>();
+        if missing.len() > MAX_MISSING_PATTERNS {
+            patterns.push(format!("{} more", missing.len() - MAX_MISSING_PATTERNS));
         }
+        let missing = match patterns.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            _ => patterns.join(""),
+        };
+        report!(self, Error::NonExhaustiveMatch(missing), node, ErrorInfo {
+            help: Some("Add arms for the missing patterns or a wildcard arm ('_ => ...').".to_string()),
+            ..Default::default()
+        });
     }
 
-    pub fn get_ast(&self) -> &TypedNode {
-        &self.ast
+    /// The constructors every value of a type is made of, with the types of
+    /// their fields. Types with too many values to list have none.
+    fn constructors(&self, ty: &UnificationType) -> Option<Vec<(String, Vec<UnificationType>)>> {
+        match self.resolve(ty) {
+            UnificationType::Known(Type::Bool) => Some(vec![("true".to_string(), Vec::new()), ("false".to_string(), Vec::new())]),
+            UnificationType::Known(Type::Object { id, generics }) => {
+                let variants = self.objects[id].get_variants()?;
+                Some(variants.iter().map(|v| {
+                    let variant = self.find_variant(id, &generics, v.get_name()).unwrap();
+                    (variant.get_name().clone(), variant.get_fields().iter().map(|(_, ty)| self.resolve_deep(ty)).collect())
+                }).collect())
+            }
+            _ => None,
+        }
     }
-}
 
-pub struct Object {
-    id: usize,
-    members: Vec<ClassMember>,
-    generics: Vec<GenericDecl>
-}
+    /// The rows starting with `constructor` (or a wildcard) with its fields in
+    /// place of the first column.
+    fn specialize(rows: &[Vec<Space>], constructor: &str, arity: usize) -> Vec<Vec<Space>> {
+        rows.iter().filter_map(|row| {
+            let fields = match &row[0] {
+                Space::Wildcard => vec![Space::Wildcard; arity],
+                Space::Constructor(name, fields) if name == constructor => fields.clone(),
+                Space::Constructor( .. ) => return None,
+            };
+            Some(fields.into_iter().chain(row[1..].iter().cloned()).collect())
+        }).collect()
+    }
 
-impl Object {
-    pub fn new(id: usize, members: Vec<ClassMember>, generics: Vec<GenericDecl>) -> Object {
-        Object {
-            id,
-            members,
-            generics
+    /// The rows starting with a wildcard without their first column.
+    fn default_rows(rows: &[Vec<Space>]) -> Vec<Vec<Space>> {
+        rows.iter().filter(|row| matches!(row[0], Space::Wildcard)).map(|row| row[1..].to_vec()).collect()
+    }
+
+    fn head_constructors(rows: &[Vec<Space>]) -> Vec<&String> {
+        rows.iter().filter_map(|row| match &row[0] {
+            Space::Constructor(name, _) => Some(name),
+            Space::Wildcard => None,
+        }).collect()
+    }
+
+    /// Whether `row` matches a value none of `rows` match, the usefulness
+    /// check of "Warnings for pattern matching" (Maranget, 2007).
+    fn is_useful(&self, rows: &[Vec<Space>], row: &[Space], types: &[UnificationType]) -> bool {
+        let Some((first, rest)) = row.split_first() else {
+            return rows.is_empty();
+        };
+        let constructors = self.constructors(&types[0]);
+        let field_types = |name: &str| constructors.iter().flatten().find(|(c, _)| c == name).map(|(_, fields)| fields.clone()).unwrap_or_default();
+        match first {
+            Space::Constructor(name, fields) => {
+                let types = field_types(name).into_iter().chain(types[1..].iter().cloned()).collect::<Vec<_>>();
+                let row = fields.iter().chain(rest).cloned().collect::<Vec<_>>();
+                self.is_useful(&Self::specialize(rows, name, fields.len()), &row, &types)
+            }
+            Space::Wildcard => {
+                let heads = Self::head_constructors(rows);
+                match &constructors {
+                    Some(constructors) if constructors.iter().all(|(c, _)| heads.contains(&c)) => constructors.iter().any(|(name, fields)| {
+                        let types = fields.iter().chain(&types[1..]).cloned().collect::<Vec<_>>();
+                        let row = vec![Space::Wildcard; fields.len()].into_iter().chain(rest.iter().cloned()).collect::<Vec<_>>();
+                        self.is_useful(&Self::specialize(rows, name, fields.len()), &row, &types)
+                    }),
+                    _ => self.is_useful(&Self::default_rows(rows), rest, &types[1..]),
+                }
+            }
         }
     }
 
-    pub fn get_id(&self) -> usize {
-        self.id
+    /// The rows of patterns that are not matched by any of `rows`.
+    fn missing_patterns(&self, rows: &[Vec<Space>], types: &[UnificationType]) -> Vec<Vec<Space>> {
+        let Some((ty, rest_types)) = types.split_first() else {
+            return if rows.is_empty() { vec![Vec::new()] } else { Vec::new() };
+        };
+        let heads = Self::head_constructors(rows);
+        let constructors = self.constructors(ty);
+        if let Some(constructors) = constructors.as_ref().filter(|c| c.iter().all(|(c, _)| heads.contains(&c))) {
+            let mut missing = Vec::new();
+            for (name, fields) in constructors {
+                let types = fields.iter().chain(rest_types).cloned().collect::<Vec<_>>();
+                for mut row in self.missing_patterns(&Self::specialize(rows, name, fields.len()), &types) {
+                    let rest = row.split_off(fields.len());
+                    missing.push(std::iter::once(Space::Constructor(name.clone(), row)).chain(rest).collect());
+                }
+            }
+            return missing;
+        }
+        let rest = self.missing_patterns(&Self::default_rows(rows), rest_types);
+        let firsts = match constructors {
+            Some(constructors) => constructors.into_iter()
+                .filter(|(c, _)| !heads.contains(&c))
+                .map(|(name, fields)| Space::Constructor(name, vec![Space::Wildcard; fields.len()]))
+                .collect(),
+            None => vec![Space::Wildcard],
+        };
+        firsts.iter().flat_map(|first| rest.iter().map(move |row| std::iter::once(first.clone()).chain(row.iter().cloned()).collect())).collect()
     }
 
-    pub fn get_members(&self) -> &Vec<ClassMember> {
-        &self.members
+    /// Writes a pattern of the exhaustiveness check like it would be written in a match.
+    fn display_space(&self, space: &Space, ty: &UnificationType) -> String {
+        let Space::Constructor(name, fields) = space else {
+            return "_".to_string();
+        };
+        let UnificationType::Known(Type::Object { id, generics }) = self.resolve(ty) else {
+            return name.clone();
+        };
+        let variant = self.find_variant(id, &generics, name).unwrap();
+        let path = format!("{}::{}", self.objects[id].get_name(), name);
+        let fields = fields.iter().zip(variant.get_fields()).map(|(field, (field_name, ty))| (field_name, self.display_space(field, ty))).collect::<Vec<_>>();
+        match variant.get_kind() {
+            VariantKind::Unit => path,
+            VariantKind::Tuple => format!("{}({})", path, fields.into_iter().map(|(_, field)| field).collect::<Vec<_>>().join(", ")),
+            VariantKind::Struct if fields.iter().all(|(_, field)| field == "_") => format!("{} {{ .. }}", path),
+            VariantKind::Struct => format!("{} {{ {} }}", path, fields.into_iter().map(|(name, field)| format!("{}: {}", name, field)).collect::<Vec<_>>().join(", ")),
+        }
     }
 
-    pub fn get_generics(&self) -> &Vec<GenericDecl> {
-        &self.generics
+    fn check_binary_op(&mut self, node: &Node, op: &BinaryOp, lhs: &Node, rhs: &Node, is_unary: bool) -> Result<(AST<TypedNode, UnificationType>, UnificationType), ()> {
+        let typed_lhs = self.check_expr(lhs)?;
+        let lhs_ty = typed_lhs.get_type().clone();
+        if is_unary {
+            let valid = match op {
+                BinaryOp::Sub => self.is_numeric(&lhs_ty),
+                BinaryOp::BitNot => self.is_integer(&lhs_ty),
+                _ => {
+                    self.constrain(UnificationType::Known(Type::Bool), lhs_ty.clone(), None, &typed_lhs)?;
+                    true
+                }
+            };
+            if !valid {
+                report!(self, Error::InvalidOperands(op.to_string(), self.display_type(&lhs_ty)), node);
+            }
+            let empty = TypedNode::new(AST::Empty, None);
+            return Ok((AST::BinaryOp(op.clone(), typed_lhs, empty, true), lhs_ty));
+        }
+        let typed_rhs = self.check_expr(rhs)?;
+        let ty = self.binary_op_type(node, op, &typed_lhs, &typed_rhs)?;
+        Ok((AST::BinaryOp(op.clone(), typed_lhs, typed_rhs, false), ty))
     }
-}
 
-pub struct Typechecker {
-    types: HashMap<String, UnificationType>, // TODO: Figure out changing it to a vec
-    constraints: Vec<UnificationType>,
-    functions: Vec<(NamespacePath, FunctionSymbol)>,
-    scope: Vec<HashMap<String, Symbol>>,
-    infer_ctx: Option<UnificationType>,
-    reports: Reports,
-    objects: Vec<Object>
-}
+    /// The type of a binary operation on typed operands.
+    fn binary_op_type(&mut self, node: &Node, op: &BinaryOp, typed_lhs: &TypedNode, typed_rhs: &TypedNode) -> Result<UnificationType, ()> {
+        let lhs_ty = typed_lhs.get_type().clone();
+        Ok(match op {
+            BinaryOp::Index => {
+                let UnificationType::Known(Type::Pointer { ty }) = self.resolve(&lhs_ty) else {
+                    report!(self, Error::InvalidOperands("[]".to_string(), self.display_type(&lhs_ty)), node, ErrorInfo {
+                        info: Some("Only pointers can be indexed.".to_string()),
+                        ..Default::default()
+                    });
+                };
+                let index = self.new_type_variable(VariableKind::Integer);
+                self.constrain(index, typed_rhs.get_type().clone(), None, typed_rhs)?;
+                *ty
+            }
+            BinaryOp::And | BinaryOp::Or => {
+                let bool_ty = UnificationType::Known(Type::Bool);
+                self.constrain(bool_ty.clone(), lhs_ty, None, typed_lhs)?;
+                self.constrain(bool_ty.clone(), typed_rhs.get_type().clone(), None, typed_rhs)?;
+                bool_ty
+            }
+            _ => {
+                self.constrain(lhs_ty.clone(), typed_rhs.get_type().clone(), typed_lhs.get_location().cloned(), typed_rhs)?;
+                let valid = match op {
+                    BinaryOp::Eq | BinaryOp::Ne => true,
+                    BinaryOp::Add => self.is_numeric(&lhs_ty) || matches!(self.resolve(&lhs_ty), UnificationType::Known(Type::String)),
+                    _ => self.is_numeric(&lhs_ty),
+                };
+                if !valid {
+                    report!(self, Error::InvalidOperands(op.to_string(), self.display_type(&lhs_ty)), node);
+                }
+                match op {
+                    BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => UnificationType::Known(Type::Bool),
+                    _ => lhs_ty,
+                }
+            }
+        })
+    }
 
-macro_rules! report {
-    ($self:ident, $error_type:expr, $node:expr) => { {
-        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()));
-        return Err(());
+    /// Numbers and literals whose exact numeric type is not known yet.
+    fn is_numeric(&self, ty: &UnificationType) -> bool {
+        match self.resolve(ty) {
+            UnificationType::Known(Type::Integer { .. } | Type::Float { .. }) => true,
+            UnificationType::TypeVariable(var) => self.substitutions[var].1 != VariableKind::Any,
+            _ => false,
+        }
     }
-    };
-    ($self:ident, $error_type:expr, $node:expr, $info:expr) => {{
-        $self.reports.add_error(CompileError::new($error_type, $node.get_location().unwrap().clone()).with_info($info));
-        return Err(());
+
+    fn is_integer(&self, ty: &UnificationType) -> bool {
+        match self.resolve(ty) {
+            UnificationType::Known(Type::Integer { .. }) => true,
+            UnificationType::TypeVariable(var) => self.substitutions[var].1 == VariableKind::Integer,
+            _ => false,
+        }
     }
+
+    fn node_to_path(node: &Node) -> String {
+        match node.get_kind() {
+            AST::Ident( name, _ ) => name.clone(),
+            AST::NamespaceAccess( base, name, _ ) => format!("{}::{}", Self::node_to_path(base), name),
+            _ => "<expression>".to_string(),
+        }
     }
-}
 
-#[derive(Clone)]
-pub enum Symbol {
-    Variable(UnificationType),
-    Function(FunctionSymbol),
-    Type(UnificationType)
-}
+    /// The function a call refers to directly, either by its name or as a
+    /// method of a value.
+    fn called_function(&self, callee: &TypedNode) -> Option<usize> {
+        match callee.get_kind() {
+            AST::Ident( path, _ ) => self.functions.iter().position(|(p, _)| p.display() == *path),
+            AST::ClassAccess( base, name ) => match self.resolve(base.get_type()) {
+                UnificationType::Known(Type::Object { id, generics }) => self.find_method_in_type(id, &generics, name).map(|(function, _)| function),
+                UnificationType::Generic( generic ) => match self.bound_with_method(&generic, name)? {
+                    UnificationType::Known(Type::Object { id, generics }) => self.find_method_in_type(id, &generics, name).map(|(function, _)| function),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 
-impl Typechecker {
-    pub fn new() -> Typechecker {
-        Typechecker {
-            types: HashMap::new(),
-            scope: vec![HashMap::new()],
-            reports: Reports::new(),
-            constraints: Vec::new(),
-            infer_ctx: None,
-            functions: Vec::new(),
-            objects: Vec::new()
+    /// The default values of the parameters a call with `given` arguments leaves out.
+    fn default_arguments(&self, function: usize, given: usize) -> Vec<TypedNode> {
+        let AST::FuncDef( _, args, .. ) = self.functions[function].1.get_ast().get_kind() else {
+            panic!("Expected FuncDef");
+        };
+        args.iter()
+            .filter(|param| param.get_name() != "self")
+            .skip(given)
+            .map_while(|param| param.get_default().cloned())
+            .collect()
+    }
+
+    fn function_takes_self(&self, function: usize) -> bool {
+        match self.functions[function].1.get_ast().get_kind() {
+            AST::FuncDef( _, args, .. ) => args.iter().any(|param| param.get_name() == "self"),
+            _ => false,
         }
     }
 
-    pub fn typecheck(&mut self, mut module: &mut Module<Node>) -> Module<TypedNode> {
-        let mut new_module = Module::<TypedNode>::new(module.get_path().clone(), module.get_file_name().clone());
-        let mut new_top = Vec::new();
-        self.initialize_builtin_types();
-        self.run_checks(module, &mut new_top);
-        new_module.set_top(AST::TopLevel(new_top));
-        new_module
+    /// The first interface a generic parameter is bound by that has a method.
+    fn bound_with_method(&self, generic: &str, name: &str) -> Option<UnificationType> {
+        self.bounds_of(generic).into_iter().find(|bound| match bound {
+            UnificationType::Known(Type::Object { id, generics }) => self.find_method_in_type(*id, generics, name).is_some(),
+            _ => false,
+        })
     }
 
-    pub fn initialize_builtin_types(&mut self) {
-        self.types.insert("i32".to_string(), UnificationType::Known(Type::Integer { size: 32, signed: true }));
-        self.types.insert("i64".to_string(), UnificationType::Known(Type::Integer { size: 64, signed: true }));
-        self.types.insert("f32".to_string(), UnificationType::Known(Type::Float { size: 32 }));
-        self.types.insert("f64".to_string(), UnificationType::Known(Type::Float { size: 64 }));
-        self.types.insert("u32".to_string(), UnificationType::Known(Type::Integer { size: 32, signed: false }));
-        self.types.insert("u64".to_string(), UnificationType::Known(Type::Integer { size: 64, signed: false }));
-        self.types.insert("void".to_string(), UnificationType::Known(Type::Void));
+    /// Returns the class that declares a method.
+    pub fn get_function_owner(&self, function: usize) -> Option<usize> {
+        self.objects.iter().find(|o| o.get_methods().values().any(|f| *f == function)).map(|o| o.get_id())
     }
 
-    pub fn run_checks(&mut self, mut module: &mut Module<Node>, new_node: &mut Vec<TypedNode>) -> Result<(), ()> {
-        let x = module.clone();
-        // collect all function definitions
-        if let AST::TopLevel (nodes) = module.get_top_mut() {
-            // TODO: Do types first here and then functions
-            for mut node in nodes {
-                match node.clone().get_kind() {
-                    AST::FuncDef( name, args, ret, .., generics, id ) => {
-                        assert!(id.is_none());
-                        self.add_scope();
-                        let mut generic_types = None;
-                        if let Some(generics) = generics {
-                            generic_types = Some(Vec::new());
-                            for generic in generics {
-                                let generic_ty = UnificationType::Generic(generic.get_name().clone());
-                                let mut impls_ty = Vec::new();
-                                for impl_ty in generic.get_impls() {
-                                    impls_ty.push(self.get_type(impl_ty.clone())?);
-                                }
-                                let mut default_ty = None;
-                                if let Some(default) = generic.get_default() {
-                                    default_ty = Some(self.get_type(default.clone())?);
-                                }
-                                generic_types.as_mut().unwrap().push(GenericDecl::new(generic.get_name().clone().clone(), impls_ty, default_ty.clone()));
-                                self.scope.last_mut().unwrap().insert(generic.get_name().clone(), Symbol::Type(generic_ty.clone()));
-                            }
-                        }
-                        let mut typed_args = HashMap::new();
-                        for (name, ty) in args {
-                            let ty = self.get_type(ty.clone())?;
-                            typed_args.insert(name.clone(), ty.clone());
-                        }
-                        let ret = self.get_type(ret.clone())?;
-                        if let AST::FuncDef( .., id ) = node.get_kind_mut() {
-                            *id = Some(self.functions.len());
-                        }
-                        let typed_node = TypedNode::new(AST::FuncDef(name.clone(), typed_args, ret, None, generic_types, Some(self.functions.len())), node.get_attrs().clone().cloned());
-                        self.functions.push((Self::get_path_for_name(&x, name.clone(), None), FunctionSymbol::new(typed_node)));
-                        self.remove_scope();
-                    }
-                    _ => {}
+    fn check_method_privacy(&mut self, function: usize, node: &Node) -> Result<(), ()> {
+        let owner = self.get_function_owner(function).unwrap();
+        let is_pub = self.functions[function].1.get_ast().get_attrs().as_ref().is_some_and(|a| a.is_pub());
+        self.check_member_privacy((owner, is_pub), node)
+    }
+
+    fn check_member_privacy(&mut self, (owner, is_pub): (usize, bool), node: &Node) -> Result<(), ()> {
+        if !is_pub && self.current_class != Some(owner) {
+            report!(self, Error::PrivateMember(Self::node_to_member(node), self.objects[owner].get_name().clone()), node, ErrorInfo {
+                info: Some("This member is private.".to_string()),
+                help: Some("Mark the member as 'public' to access it from outside of its class.".to_string()),
+                ..Default::default()
+            });
+        }
+        Ok(())
+    }
+
+    fn node_to_member(node: &Node) -> String {
+        match node.get_kind() {
+            AST::ClassAccess( _, name ) | AST::NamespaceAccess( _, name, _ ) => name.clone(),
+            AST::ClassInit( .. ) => "constructor".to_string(),
+            _ => Self::node_to_path(node),
+        }
+    }
+
+    fn class_generics_map(&self, id: usize, generics: &[UnificationType]) -> HashMap<String, UnificationType> {
+        self.objects[id].get_generics().iter().map(|g| g.get_name().clone()).zip(generics.iter().cloned()).collect()
+    }
+
+    /// Looks for a member in a class and its ancestors, returning the class that
+    /// declares it and its type with the generic arguments of `generics` applied.
+    fn find_member(&self, id: usize, generics: &[UnificationType], name: &str) -> Option<(usize, UnificationType, AttrHandler)> {
+        let map = self.class_generics_map(id, generics);
+        let object = &self.objects[id];
+        if let Some(member) = object.get_members().iter().find(|m| m.get_name() == name) {
+            return Some((id, Self::substitute(member.get_ty(), &map), member.get_attrs().clone()));
+        }
+        match object.get_parent().map(|p| Self::substitute(p, &map)) {
+            Some(UnificationType::Known(Type::Object { id, generics })) if id != object.get_id() => self.find_member(id, &generics, name),
+            _ => None,
+        }
+    }
+
+    /// Same as `find_member` but for methods, returning the function id and the
+    /// generic arguments of the class that declares it.
+    fn find_method_in_type(&self, id: usize, generics: &[UnificationType], name: &str) -> Option<(usize, HashMap<String, UnificationType>)> {
+        let map = self.class_generics_map(id, generics);
+        let object = &self.objects[id];
+        if let Some(function) = object.get_methods().get(name) {
+            return Some((*function, map));
+        }
+        match object.get_parent().map(|p| Self::substitute(p, &map)) {
+            Some(UnificationType::Known(Type::Object { id, generics })) if id != object.get_id() => self.find_method_in_type(id, &generics, name),
+            _ => None,
+        }
+    }
+
+    /// Replaces the generic parameters of a function signature with either the
+    /// explicit generic arguments, their defaults or fresh type variables.
+    /// Returns the parameters (without `self`), the return type and the generic arguments used.
+    fn instantiate_function(&mut self, function: usize, explicit: Option<Vec<AstType>>, class_generics: &HashMap<String, UnificationType>, node: &Node)
+        -> Result<InstantiatedFunction, ()> {
+        let AST::FuncDef( _, args, ret, _, generics, _ ) = self.functions[function].1.get_ast().get_kind().clone() else {
+            panic!("Expected FuncDef");
+        };
+        let generics = generics.unwrap_or_default();
+        let explicit = explicit.unwrap_or_default();
+        if explicit.len() > generics.len() {
+            report!(self, Error::TooManyGenerics(generics.len(), explicit.len()), node, ErrorInfo {
+                info: Some("Too many generics for this function.".to_string()),
+                ..Default::default()
+            });
+        }
+        let mut map = class_generics.clone();
+        let mut generic_args = Vec::new();
+        for (i, generic) in generics.iter().enumerate() {
+            let ty = match explicit.get(i) {
+                Some(ty) => self.get_type(ty.clone())?,
+                None => match generic.get_default() {
+                    Some(default) => Self::substitute(default, &map),
+                    None => self.new_type_variable(VariableKind::Any),
+                },
+            };
+            map.insert(generic.get_name().clone(), ty.clone());
+            generic_args.push(ty);
+        }
+        let owner = self.functions[function].0.display();
+        self.require_bounds(&generics, &generic_args, &map, &owner, node)?;
+        let params = args.iter()
+            .filter(|param| param.get_name() != "self")
+            .map(|param| (param.get_name().clone(), Self::substitute(param.get_ty(), &map)))
+            .collect();
+        Ok((params, Self::substitute(&ret, &map), if generic_args.is_empty() { None } else { Some(generic_args) }))
+    }
+
+    /// Replaces every generic parameter found in `map`.
+    pub fn substitute(ty: &UnificationType, map: &HashMap<String, UnificationType>) -> UnificationType {
+        match ty {
+            UnificationType::Generic(name) => map.get(name).cloned().unwrap_or(ty.clone()),
+            UnificationType::Known(known) => UnificationType::Known(match known {
+                Type::Object { id, generics } => Type::Object { id: *id, generics: generics.iter().map(|g| Self::substitute(g, map)).collect() },
+                Type::Function { args, ret } => Type::Function { args: args.iter().map(|a| Self::substitute(a, map)).collect(), ret: Box::new(Self::substitute(ret, map)) },
+                Type::Pointer { ty } => Type::Pointer { ty: Box::new(Self::substitute(ty, map)) },
+                Type::Reference { ty } => Type::Reference { ty: Box::new(Self::substitute(ty, map)) },
+                _ => known.clone(),
+            }),
+            UnificationType::TypeVariable(_) => ty.clone(),
+        }
+    }
+
+    fn new_type_variable(&mut self, kind: VariableKind) -> UnificationType {
+        self.substitutions.push((None, kind));
+        UnificationType::TypeVariable(self.substitutions.len() - 1)
+    }
+
+    /// Follows the substitutions of a type variable until a type that is not a bound variable is found.
+    pub fn resolve(&self, ty: &UnificationType) -> UnificationType {
+        let mut ty = ty.clone();
+        while let UnificationType::TypeVariable(var) = ty {
+            match &self.substitutions[var].0 {
+                Some(bound) => ty = bound.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    /// Like `resolve` but also resolves the types nested inside of the type.
+    pub fn resolve_deep(&self, ty: &UnificationType) -> UnificationType {
+        match self.resolve(ty) {
+            UnificationType::Known(known) => UnificationType::Known(match known {
+                Type::Object { id, generics } => Type::Object { id, generics: generics.iter().map(|g| self.resolve_deep(g)).collect() },
+                Type::Function { args, ret } => Type::Function { args: args.iter().map(|a| self.resolve_deep(a)).collect(), ret: Box::new(self.resolve_deep(&ret)) },
+                Type::Pointer { ty } => Type::Pointer { ty: Box::new(self.resolve_deep(&ty)) },
+                Type::Reference { ty } => Type::Reference { ty: Box::new(self.resolve_deep(&ty)) },
+                known => known,
+            }),
+            ty => ty,
+        }
+    }
+
+    fn occurs(&self, var: usize, ty: &UnificationType) -> bool {
+        match self.resolve(ty) {
+            UnificationType::TypeVariable(other) => other == var,
+            UnificationType::Known(Type::Object { generics, .. }) => generics.iter().any(|g| self.occurs(var, g)),
+            UnificationType::Known(Type::Function { args, ret }) => args.iter().any(|a| self.occurs(var, a)) || self.occurs(var, &ret),
+            UnificationType::Known(Type::Pointer { ty } | Type::Reference { ty }) => self.occurs(var, &ty),
+            _ => false,
+        }
+    }
+
+    fn bind(&mut self, var: usize, ty: &UnificationType) -> bool {
+        if self.occurs(var, ty) {
+            return false;
+        }
+        let valid = match self.substitutions[var].1 {
+            VariableKind::Any => true,
+            VariableKind::Integer => matches!(ty, UnificationType::Known(Type::Integer { .. })),
+            VariableKind::Float => matches!(ty, UnificationType::Known(Type::Float { .. })),
+        };
+        if valid {
+            self.substitutions[var].0 = Some(ty.clone());
+        }
+        valid
+    }
+
+    fn unify(&mut self, a: &UnificationType, b: &UnificationType) -> bool {
+        let a = self.resolve(a);
+        let b = self.resolve(b);
+        match (&a, &b) {
+            (UnificationType::TypeVariable(x), UnificationType::TypeVariable(y)) => {
+                if x == y {
+                    return true;
                 }
+                let kind = match (self.substitutions[*x].1, self.substitutions[*y].1) {
+                    (VariableKind::Any, kind) | (kind, VariableKind::Any) => kind,
+                    (x, y) if x == y => x,
+                    _ => return false,
+                };
+                self.substitutions[*y].1 = kind;
+                self.substitutions[*x].0 = Some(b.clone());
+                true
             }
-        } else {
-            panic!("Expected TopLevel node");
+            (UnificationType::TypeVariable(var), ty) | (ty, UnificationType::TypeVariable(var)) => self.bind(*var, ty),
+            (UnificationType::Known(x), UnificationType::Known(y)) => match (x, y) {
+                (Type::Integer { size: a, signed: b }, Type::Integer { size: c, signed: d }) => a == c && b == d,
+                (Type::Float { size: a }, Type::Float { size: b }) => a == b,
+                (Type::Bool, Type::Bool) | (Type::String, Type::String) | (Type::Void, Type::Void) => true,
+                (Type::Object { id: a, generics: x }, Type::Object { id: b, generics: y }) => {
+                    a == b && x.len() == y.len() && x.clone().iter().zip(y.clone().iter()).all(|(x, y)| self.unify(x, y))
+                }
+                (Type::Function { args: a, ret: x }, Type::Function { args: b, ret: y }) => {
+                    a.len() == b.len() && a.clone().iter().zip(b.clone().iter()).all(|(a, b)| self.unify(a, b)) && self.unify(x, y)
+                }
+                (Type::Pointer { ty: x }, Type::Pointer { ty: y }) | (Type::Reference { ty: x }, Type::Reference { ty: y }) => self.unify(x, y),
+                _ => false,
+            },
+            (UnificationType::Generic(x), UnificationType::Generic(y)) => x == y,
+            _ => false,
         }
-        if let AST::TopLevel (nodes) = module.get_top().clone() {
-            for node in nodes {
-                self.check_node(node, new_node)?;
+    }
+
+    /// Adds a constraint saying that `found` must be the same type as `expected`
+    /// and solves it right away, reporting both locations if they don't unify.
+    fn constrain(&mut self, expected: UnificationType, found: UnificationType, expected_location: Option<SourceLocation>, found_node: &TypedNode) -> Result<(), ()> {
+        self.constraints.push(Constraint {
+            expected,
+            found,
+            expected_location,
+            found_location: found_node.get_location().cloned().unwrap_or_else(SourceLocation::dummy),
+        });
+        self.solve_constraints()
+    }
+
+    fn solve_constraints(&mut self) -> Result<(), ()> {
+        while let Some(constraint) = self.constraints.pop() {
+            let found = self.upcast(&constraint.expected, &constraint.found);
+            if !self.unify(&constraint.expected, &found) {
+                let expected = self.display_type(&self.resolve_deep(&constraint.expected));
+                let found = self.display_type(&self.resolve_deep(&constraint.found));
+                self.constraints.clear();
+                self.reports.add_error(CompileError::new(Error::TypeMismatch(expected.clone(), found.clone()), constraint.found_location).with_info(ErrorInfo {
+                    info: Some(format!("This has type '{}'.", found)),
+                    related: constraint.expected_location.map(|l| (format!("expected '{}' because of this", expected), l)),
+                    ..Default::default()
+                }));
+                return Err(());
             }
         }
         Ok(())
     }
 
-    pub fn check_node(&mut self, node: Node, new_node: &mut Vec<TypedNode>) -> Result<(), ()> {
-        match node.get_kind() {
-            AST::FuncDef( name, args, ret, body, generics, id ) => {
-                assert!(id.is_some());
-                if self.scope.last().unwrap().contains_key(name) {
-                    report!(self, Error::VariableAlreadyDeclared(name.clone()), node, ErrorInfo {
-                        info: Some(format!("Variable '{}' already declared in this scope.", name).to_string()),
-                        help: Some("Make sure the variable is not declared twice in the same scope.".to_string()),
-                        ..Default::default()
-                    });
+    /// Instances of a class can be used where one of its ancestors or interfaces
+    /// is expected, and generic values where one of their bounds is expected. In
+    /// that case the found type is replaced by the matching supertype.
+    fn upcast(&self, expected: &UnificationType, found: &UnificationType) -> UnificationType {
+        let UnificationType::Known(Type::Object { id: expected_id, .. }) = self.resolve(expected) else {
+            return found.clone();
+        };
+        if !matches!(self.resolve(found), UnificationType::Known(Type::Object { .. }) | UnificationType::Generic( .. )) {
+            return found.clone();
+        }
+        self.supertypes(found).into_iter()
+            .find(|ty| matches!(ty, UnificationType::Known(Type::Object { id, .. }) if *id == expected_id))
+            .unwrap_or(found.clone())
+    }
+
+    /// Replaces every type variable in a checked function with the type it was
+    /// unified with. Literals that were never constrained get their default type.
+    fn finalize_types(&mut self, node: &mut TypedNode) -> Result<(), ()> {
+        let ty = self.finalize_type(node.get_type());
+        let Some(ty) = ty else {
+            report!(self, Error::TypeAnnotationsNeeded, node, ErrorInfo {
+                info: Some("The type of this expression cannot be inferred.".to_string()),
+                help: Some("Add a type annotation to the variable declaration.".to_string()),
+                ..Default::default()
+            });
+        };
+        node.set_type(ty);
+        let location = node.get_location().cloned();
+        match &mut **node.get_kind_mut() {
+            AST::Block( nodes ) => for node in nodes {
+                self.finalize_types(node)?;
+            },
+            AST::VarDef( _, ty, value ) => {
+                if let Some(value) = value {
+                    self.finalize_types(value)?;
                 }
-                let mut typed_func = self.functions[id.unwrap()];
-                if let AST::FuncDef( _, args, ret, _, generics, .. ) = **typed_func.1.ast.get_kind_mut() {
-                    self.insert_symbol(name.clone(), Symbol::Function(FunctionSymbol::new(typed_func.1.ast.clone())));
-                    self.add_scope();
-                    let mut typed_args = HashMap::new();
-                    for (name, ty) in args {
-                        typed_args.insert(name.clone(), ty);
+                match self.finalize_type(ty.as_ref().unwrap()) {
+                    Some(finalized) => *ty = Some(finalized),
+                    None => {
+                        self.reports.add_error(CompileError::new(Error::TypeAnnotationsNeeded, location.unwrap()).with_info(ErrorInfo {
+                            info: Some("The type of this variable cannot be inferred.".to_string()),
+                            help: Some("Add a type annotation to the variable declaration.".to_string()),
+                            ..Default::default()
+                        }));
+                        return Err(());
                     }
-                } else {
-                    panic!("Expected FuncDef");
+                }
+            }
+            AST::Return( Some(value) ) => self.finalize_types(value)?,
+            AST::If( cond, then, els ) => {
+                self.finalize_types(cond)?;
+                self.finalize_types(then)?;
+                for e in els {
+                    self.finalize_types(e)?;
+                }
+            }
+            AST::While( cond, body, _ ) => {
+                self.finalize_types(cond)?;
+                self.finalize_types(body)?;
+            }
+            AST::Ident( _, Some(generics) ) => for generic in generics.iter_mut() {
+                *generic = self.finalize_type(generic).unwrap_or(generic.clone());
+            },
+            AST::ClassAccess( base, _ ) => self.finalize_types(base)?,
+            AST::Call( callee, args ) => {
+                self.finalize_types(callee)?;
+                for arg in args {
+                    self.finalize_types(arg)?;
+                }
+            }
+            AST::ClassInit( ty, args ) => {
+                *ty = self.finalize_type(ty).unwrap_or(ty.clone());
+                for arg in args {
+                    self.finalize_types(arg)?;
+                }
+            }
+            AST::BinaryOp( _, lhs, rhs, is_unary ) => {
+                self.finalize_types(lhs)?;
+                if !*is_unary {
+                    self.finalize_types(rhs)?;
+                }
+            }
+            AST::Assign( target, value, _ ) => {
+                self.finalize_types(target)?;
+                self.finalize_types(value)?;
+            }
+            AST::Match( scrutinee, arms ) => {
+                self.finalize_types(scrutinee)?;
+                let finalized = std::mem::take(arms).into_iter().map(|arm| {
+                    let mut guard = arm.get_guard().cloned();
+                    let mut body = arm.get_body().clone();
+                    if let Some(guard) = &mut guard {
+                        self.finalize_types(guard)?;
+                    }
+                    self.finalize_types(&mut body)?;
+                    Ok(MatchArm::new(self.finalize_pattern(arm.get_pattern()), guard, body))
+                }).collect::<Result<Vec<_>, ()>>()?;
+                *arms = finalized;
+            }
+            AST::VariantInit( ty, _, fields ) => {
+                *ty = self.finalize_type(ty).unwrap_or(ty.clone());
+                for (_, value) in fields {
+                    self.finalize_types(value)?;
                 }
             }
             _ => {}
@@ -232,6 +2693,50 @@ impl Typechecker {
         Ok(())
     }
 
+    fn finalize_pattern(&mut self, pattern: &Pattern<UnificationType>) -> Pattern<UnificationType> {
+        let kind = match pattern.get_kind() {
+            PatternKind::Variant( ty, name, kind, fields, has_rest ) => {
+                let ty = ty.as_ref().map(|ty| self.finalize_type(ty).unwrap_or(ty.clone()));
+                let fields = fields.iter().map(|(field, pattern)| (field.clone(), self.finalize_pattern(pattern))).collect();
+                PatternKind::Variant(ty, name.clone(), *kind, fields, *has_rest)
+            }
+            kind => kind.clone(),
+        };
+        Pattern::new(kind, pattern.get_location().unwrap().clone())
+    }
+
+    fn finalize_type(&mut self, ty: &UnificationType) -> Option<UnificationType> {
+        let ty = self.resolve_deep(ty);
+        match ty {
+            UnificationType::TypeVariable(var) => {
+                let default = match self.substitutions[var].1 {
+                    VariableKind::Integer => Type::Integer { size: 32, signed: true },
+                    VariableKind::Float => Type::Float { size: 64 },
+                    VariableKind::Any => return None,
+                };
+                self.substitutions[var].0 = Some(UnificationType::Known(default.clone()));
+                Some(UnificationType::Known(default))
+            }
+            UnificationType::Known(Type::Object { id, generics }) => {
+                let mut finalized = Vec::new();
+                for generic in generics {
+                    finalized.push(self.finalize_type(&generic)?);
+                }
+                Some(UnificationType::Known(Type::Object { id, generics: finalized }))
+            }
+            UnificationType::Known(Type::Function { args, ret }) => {
+                let mut finalized = Vec::new();
+                for arg in args {
+                    finalized.push(self.finalize_type(&arg)?);
+                }
+                Some(UnificationType::Known(Type::Function { args: finalized, ret: Box::new(self.finalize_type(&ret)?) }))
+            }
+            UnificationType::Known(Type::Pointer { ty }) => Some(UnificationType::Known(Type::Pointer { ty: Box::new(self.finalize_type(&ty)?) })),
+            UnificationType::Known(Type::Reference { ty }) => Some(UnificationType::Known(Type::Reference { ty: Box::new(self.finalize_type(&ty)?) })),
+            ty => Some(ty),
+        }
+    }
+
     pub fn insert_symbol(&mut self, name: String, symbol: Symbol) {
         self.scope.last_mut().unwrap().insert(name, symbol);
     }
@@ -251,14 +2756,25 @@ impl Typechecker {
                 note: Some("Functions cant be used as types. Only types can be used as types.".to_string()),
                 ..Default::default()
             }),
-            Symbol::Type( ty ) => Ok(ty.clone())
+            Symbol::Module( .. ) => report!(self, Error::UnexpectedItem("type".to_string(), "module".to_string()), ty.get_ast().clone(), ErrorInfo {
+                info: Some("This does not point towards a type.".to_string()),
+                help: Some("Make sure there is no conflict between module and type names.".to_string()),
+                ..Default::default()
+            }),
+            Symbol::Type( resolved ) => {
+                if let UnificationType::Known(Type::Object { id, .. }) = resolved {
+                    let declaration = self.objects[id].get_location().cloned();
+                    self.add_reference(ty.get_ast().get_location(), declaration.as_ref());
+                }
+                Ok(resolved)
+            }
         }
     }
                 
     pub fn get_symbol(&mut self, ty: Node) -> Result<Symbol, ()> {
         match ty.get_kind() {
             AST::Ident( name, _ ) => {
-                let s = self.lookup_variable(&name);
+                let s = self.lookup_variable(name);
                 match s {
                     Some( sym ) => self.handle_symbol(sym, ty.clone()),
                     None => {
@@ -270,6 +2786,27 @@ impl Typechecker {
                     }
                 }
             }
+            AST::NamespaceAccess( base, name, _ ) => {
+                let module = match self.lookup_path(base) {
+                    Some(Symbol::Module( module )) => module,
+                    // reports the segment of the path that does not exist
+                    None => return self.get_symbol(base.clone()).and(Err(())),
+                    Some(symbol) => {
+                        let found = match symbol {
+                            Symbol::Variable( .. ) => "variable",
+                            Symbol::Function( .. ) => "function",
+                            _ => "type",
+                        };
+                        report!(self, Error::UnexpectedItem("module or namespace".to_string(), found.to_string()), base, ErrorInfo {
+                            info: Some(format!("'{}' has no items that can be reached with '::'.", Self::node_to_path(base))),
+                            help: Some("Only modules and namespaces can be followed by '::' here.".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                };
+                let symbol = self.lookup_module_item(&module, name, &ty)?;
+                self.handle_symbol(symbol, ty.clone())
+            }
             _ => {
                 report!(self, Error::UnknownVariable("<todo>".to_string()), ty);
             }
@@ -278,8 +2815,7 @@ impl Typechecker {
 
     fn get_generics_from_node(&mut self, node: Node) -> Option<Vec<AstType>> {
         match node.get_kind() {
-            AST::Ident( .., generics ) => generics.clone(),
-            AST::NamespaceAccess( base, .. ) => self.get_generics_from_node(base.clone()),
+            AST::Ident( .., generics ) | AST::NamespaceAccess( .., generics ) => generics.clone(),
             _ => None
         }
     }
@@ -292,19 +2828,39 @@ impl Typechecker {
                 match ty {
                     UnificationType::Known( ty ) => {
                         match ty {
-                            Type::Object { id } => {
-                                if let Some(generics) = generics {
-                                    let object = &self.objects[*id];
-                                    if generics.len() > object.get_generics().len() {
-                                        report!(self, Error::TooManyGenerics(object.get_generics().len(), generics.len()), node, ErrorInfo {
-                                            info: Some("Too many generics for this type.".to_string()),
-                                            help: Some("Make sure the type is not generic.".to_string()),
-                                            messages: Some((format!("This generic is not used in the type definition."), generics[object.get_generics().len()-1].get_ast().get_location().unwrap().column)),
+                            Type::Object { id, .. } => {
+                                let id = *id;
+                                let generics = generics.unwrap_or_default();
+                                let expected = self.objects[id].get_generics().len();
+                                if generics.len() > expected {
+                                    report!(self, Error::TooManyGenerics(expected, generics.len()), node, ErrorInfo {
+                                        info: Some("Too many generics for this type.".to_string()),
+                                        help: Some("Make sure the type is not generic.".to_string()),
+                                        messages: Some(("This generic is not used in the type definition.".to_string(), generics[expected].get_ast().get_location().unwrap().column)),
+                                        ..Default::default()
+                                    });
+                                }
+                                let mut generic_types = Vec::new();
+                                for generic in generics {
+                                    generic_types.push(self.get_type(generic)?);
+                                }
+                                // the missing generics fall back to their default types
+                                let missing = self.objects[id].get_generics().iter().skip(generic_types.len()).cloned().collect::<Vec<_>>();
+                                for generic in missing {
+                                    match generic.get_default() {
+                                        Some(default) => generic_types.push(default.clone()),
+                                        None => report!(self, Error::TooManyGenerics(expected, generic_types.len()), node, ErrorInfo {
+                                            info: Some("Not enough generics for this type.".to_string()),
+                                            help: Some(format!("Specify a type for the generic parameter '{}'.", generic.get_name())),
                                             ..Default::default()
-                                        });
+                                        }),
                                     }
                                 }
-                                Ok(sym)
+                                let declared = self.objects[id].get_generics().clone();
+                                let map = self.class_generics_map(id, &generic_types);
+                                let owner = self.objects[id].get_name().clone();
+                                self.require_bounds(&declared, &generic_types, &map, &owner, &node)?;
+                                Ok(Symbol::Type(UnificationType::Known(Type::Object { id, generics: generic_types })))
                             },
                             _ => {
                                 if let Some(generics) = generics {
@@ -312,7 +2868,7 @@ impl Typechecker {
                                         info: Some("Too many generics for this type.".to_string()),
                                         help: Some("Make sure the type is not generic. If it is, make sure the generics are used correctly.".to_string()),
                                         note: Some("Primitive types cannot be generic thus cannot have generics.".to_string()),
-                                        messages: Some((format!("This type is not generic."), generics[0].get_ast().get_location().unwrap().column)),
+                                        messages: Some(("This type is not generic.".to_string(), generics[0].get_ast().get_location().unwrap().column)),
                                         ..Default::default()
                                     });
                                 }
@@ -320,9 +2876,13 @@ impl Typechecker {
                             }
                         }
                     },
-                    UnificationType::Generic( .. ) => {
-                        if generics.is_some() {
-                            todo!("Generic generics");
+                    UnificationType::Generic( name ) => {
+                        if let Some(generics) = generics {
+                            report!(self, Error::TooManyGenerics(0, generics.len()), node, ErrorInfo {
+                                info: Some(format!("'{}' is a generic parameter, it cannot take generic arguments.", name)),
+                                help: Some(format!("Use '{}' without generic arguments.", name)),
+                                ..Default::default()
+                            });
                         }
                         Ok(sym)
                     }
@@ -331,7 +2891,7 @@ impl Typechecker {
                             report!(self, Error::TooManyGenerics(0, generics.len()), node, ErrorInfo {
                                 info: Some("Too many generics for this type.".to_string()),
                                 help: Some("Make sure the type is not generic.".to_string()),
-                                messages: Some((format!("This type is not generic."), generics[0].get_ast().get_location().unwrap().column)),
+                                messages: Some(("This type is not generic.".to_string(), generics[0].get_ast().get_location().unwrap().column)),
                                 ..Default::default()
                             });
                         }
@@ -352,6 +2912,39 @@ impl Typechecker {
         path
     }
 
+    /// Returns the name of a type as it would be written in the source code.
+    pub fn display_type(&self, ty: &UnificationType) -> String {
+        match ty {
+            UnificationType::Known(ty) => match ty {
+                Type::Integer { size, signed } => format!("{}{}", if *signed { "i" } else { "u" }, size),
+                Type::Float { size } => format!("f{}", size),
+                Type::Object { id, generics } => {
+                    let name = self.objects[*id].get_name().clone();
+                    if generics.is_empty() {
+                        name
+                    } else {
+                        format!("{}<{}>", name, generics.iter().map(|g| self.display_type(g)).collect::<Vec<_>>().join(", "))
+                    }
+                }
+                Type::Function { args, ret } => {
+                    format!("func({}) {}", args.iter().map(|a| self.display_type(a)).collect::<Vec<_>>().join(", "), self.display_type(ret))
+                }
+                Type::Pointer { ty } => format!("*{}", self.display_type(ty)),
+                Type::Reference { ty } => format!("&{}", self.display_type(ty)),
+                Type::Bool => "bool".to_string(),
+                Type::String => "String".to_string(),
+                Type::Void => "void".to_string(),
+            },
+            UnificationType::TypeVariable(id) => match self.substitutions[*id] {
+                (Some(ref ty), _) => self.display_type(ty),
+                (None, VariableKind::Integer) => "{integer}".to_string(),
+                (None, VariableKind::Float) => "{float}".to_string(),
+                (None, VariableKind::Any) => "_".to_string(),
+            },
+            UnificationType::Generic(name) => name.clone(),
+        }
+    }
+
     pub fn get_reports(&self) -> &Reports {
         &self.reports
     }
@@ -362,18 +2955,65 @@ impl Typechecker {
                 return Some(var_type.clone());
             }
         }
+        // the items of the namespaces around the current one are in scope too
+        let file = self.file_module(&self.current_module);
+        let segments = self.current_module.get_segments();
+        for end in (file.get_segments().len()..=segments.len()).rev() {
+            let module = NamespacePath::new(segments[..end].to_vec());
+            if let Some(symbol) = self.globals.get(&module).and_then(|globals| globals.get(var_name)) {
+                return Some(symbol.clone());
+            }
+        }
+        match self.imports.get(file).and_then(|imports| imports.get(var_name)) {
+            Some(Import::Module( path )) => return Some(Symbol::Module(path.clone())),
+            Some(Import::Item( module, name )) => return self.globals.get(module).and_then(|globals| globals.get(name)).cloned(),
+            None => {}
+        }
         if let Some(var_type) = self.types.get(var_name) {
             return Some(Symbol::Type(var_type.clone()));
         }
-        // TODO: Functions
         None
     }
 
     pub fn add_scope(&mut self) {
         self.scope.push(HashMap::new());
+        self.generic_bounds.push(HashMap::new());
+        self.declarations.push(HashMap::new());
     }
 
     pub fn remove_scope(&mut self) {
         self.scope.pop();
+        self.generic_bounds.pop();
+        self.declarations.pop();
+    }
+
+    fn declare_variable(&mut self, name: String, ty: UnificationType, location: Option<&SourceLocation>) {
+        if let Some(location) = location {
+            self.declarations.last_mut().unwrap().insert(name.clone(), location.clone());
+        }
+        self.insert_symbol(name, Symbol::Variable(ty));
+    }
+
+    /// Where the variable that `name` currently refers to was declared.
+    fn lookup_declaration(&self, name: &str) -> Option<SourceLocation> {
+        let index = self.scope.iter().rposition(|scope| scope.contains_key(name))?;
+        self.declarations[index].get(name).cloned()
+    }
+
+    fn add_reference(&mut self, usage: Option<&SourceLocation>, declaration: Option<&SourceLocation>) {
+        if let (Some(usage), Some(declaration)) = (usage, declaration) {
+            self.references.push((usage.clone(), declaration.clone()));
+        }
+    }
+
+    /// The location of the last segment of a path or member access (e.g. `y` in `x.y`).
+    fn name_location(node: &Node, name: &str) -> Option<SourceLocation> {
+        let location = node.get_location()?;
+        let column = (location.column + location.width).saturating_sub(name.len());
+        Some(SourceLocation::new(location.file, location.line, column, name.len()))
+    }
+
+    pub fn get_references(&self) -> &Vec<(SourceLocation, SourceLocation)> {
+        &self.references
     }
 }
\ No newline at end of file
@@ -0,0 +1,415 @@
+pub mod transport;
+
+use std::collections::{HashMap, HashSet};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::ast::nodes::{Node, AST};
+use crate::ast::source::{FileId, SourceLocation, SourceMap};
+use crate::ast::typed::TypedNode;
+use crate::compiler::manifest::Manifest;
+use crate::compiler::{Analysis, Compiler};
+use crate::frontend::module::NamespacePath;
+use crate::reports::{CompileError, ErrorType};
+use transport::{path_to_uri, read_message, uri_to_path, write_message};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#symbolKind
+const SYMBOL_NAMESPACE: u32 = 3;
+const SYMBOL_CLASS: u32 = 5;
+const SYMBOL_METHOD: u32 = 6;
+const SYMBOL_ENUM: u32 = 10;
+const SYMBOL_INTERFACE: u32 = 11;
+const SYMBOL_FUNCTION: u32 = 12;
+const SYMBOL_ENUM_MEMBER: u32 = 22;
+
+/// How the client counts the characters of a line. The columns of the
+/// compiler count Unicode scalar values, which is what `utf-32` stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PositionEncoding {
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Turns a column of a 1 based line into the character offset of the client.
+    fn character(self, source: &str, line: usize, column: usize) -> usize {
+        let Some(text) = source.lines().nth(line.saturating_sub(1)).filter(|_| self == PositionEncoding::Utf16) else {
+            return column;
+        };
+        let units = text.chars().take(column).map(char::len_utf16).sum::<usize>();
+        units + column.saturating_sub(text.chars().count())
+    }
+
+    /// Turns a character offset of the client into a column of a 1 based line.
+    fn column(self, source: &str, line: usize, character: usize) -> usize {
+        let Some(text) = source.lines().nth(line.saturating_sub(1)).filter(|_| self == PositionEncoding::Utf16) else {
+            return character;
+        };
+        let mut units = 0;
+        for (column, c) in text.chars().enumerate() {
+            if units >= character {
+                return column;
+            }
+            units += c.len_utf16();
+        }
+        text.chars().count() + character.saturating_sub(units)
+    }
+}
+
+/// A language server that talks to an editor over stdin and stdout. The
+/// open documents are kept in memory so unsaved changes are analyzed too.
+pub struct LanguageServer {
+    documents: HashMap<PathBuf, String>,
+    /// The last analysis of each package, by the path of its manifest (or file).
+    analyses: HashMap<PathBuf, Analysis>,
+    /// The files of each package that currently have diagnostics.
+    published: HashMap<PathBuf, HashSet<String>>,
+    outgoing: Vec<Value>,
+    shutdown: bool,
+    encoding: PositionEncoding,
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer {
+            documents: HashMap::new(),
+            analyses: HashMap::new(),
+            published: HashMap::new(),
+            outgoing: Vec::new(),
+            shutdown: false,
+            encoding: PositionEncoding::Utf16,
+        }
+    }
+
+    /// Serves requests until the client exits. Returns the exit code of the server.
+    pub fn run() -> i32 {
+        // the client went away without asking the server to exit
+        LanguageServer::new().serve().unwrap_or(1)
+    }
+
+    fn serve(&mut self) -> std::io::Result<i32> {
+        let mut reader = BufReader::new(std::io::stdin());
+        let mut stdout = std::io::stdout();
+        while let Some(message) = read_message(&mut reader) {
+            if message.is_null() {
+                write_message(&mut stdout, &json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": "invalid JSON" } }))?;
+                continue;
+            }
+            let method = message["method"].as_str().unwrap_or_default();
+            if method == "exit" {
+                return Ok(if self.shutdown { 0 } else { 1 });
+            }
+            match message.get("id") {
+                Some(id) if !method.is_empty() => {
+                    let response = match self.handle_request(method, &message["params"]) {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err((code, error)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": error } }),
+                    };
+                    write_message(&mut stdout, &response)?;
+                }
+                // responses to requests of the server are not needed
+                Some(_) => {}
+                None => self.handle_notification(method, &message["params"]),
+            }
+            for notification in std::mem::take(&mut self.outgoing) {
+                write_message(&mut stdout, &notification)?;
+            }
+        }
+        Ok(1)
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => {
+                let encodings = params["capabilities"]["general"]["positionEncodings"].as_array();
+                if encodings.is_some_and(|encodings| encodings.iter().any(|e| e == "utf-32")) {
+                    self.encoding = PositionEncoding::Utf32;
+                }
+                Ok(json!({
+                    "capabilities": {
+                        "positionEncoding": if self.encoding == PositionEncoding::Utf32 { "utf-32" } else { "utf-16" },
+                        // the whole document is sent on every change
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "documentSymbolProvider": true,
+                    },
+                    "serverInfo": { "name": "snowball", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let (path, line, column) = self.get_position(params)?;
+                Ok(self.hover(&path, line, column).map(|text| json!({
+                    "contents": { "kind": "markdown", "value": format!("```snowball\n{}\n```", text) },
+                })).unwrap_or(Value::Null))
+            }
+            "textDocument/definition" => {
+                let (path, line, column) = self.get_position(params)?;
+                Ok(self.definition(&path, line, column).map(|(file, range)| json!({
+                    "uri": path_to_uri(&file),
+                    "range": range,
+                })).unwrap_or(Value::Null))
+            }
+            "textDocument/documentSymbol" => {
+                let path = Self::get_document(params)?;
+                Ok(Value::Array(self.document_symbols(&path)))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) {
+        let Ok(path) = Self::get_document(params) else {
+            return;
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(path.clone(), text.to_string());
+            }
+            "textDocument/didChange" => {
+                let Some(change) = params["contentChanges"].as_array().and_then(|changes| changes.last()) else {
+                    return;
+                };
+                self.documents.insert(path.clone(), change["text"].as_str().unwrap_or_default().to_string());
+            }
+            "textDocument/didSave" => {
+                if let Some(text) = params["text"].as_str() {
+                    self.documents.insert(path.clone(), text.to_string());
+                }
+            }
+            // from now on the file is read from the disk again
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+            }
+            _ => return,
+        }
+        self.analyze(&path);
+    }
+
+    fn get_document(params: &Value) -> Result<PathBuf, (i64, String)> {
+        params["textDocument"]["uri"].as_str().and_then(uri_to_path).ok_or((INVALID_PARAMS, "expected a 'file://' document URI".to_string()))
+    }
+
+    /// The document and the position in it, as a 1 based line and a 0 based column.
+    fn get_position(&self, params: &Value) -> Result<(PathBuf, usize, usize), (i64, String)> {
+        let path = Self::get_document(params)?;
+        let (Some(line), Some(character)) = (params["position"]["line"].as_u64(), params["position"]["character"].as_u64()) else {
+            return Err((INVALID_PARAMS, "expected a position".to_string()));
+        };
+        let line = line as usize + 1;
+        let column = self.encoding.column(&self.document_text(&path), line, character as usize);
+        Ok((path, line, column))
+    }
+
+    /// The unsaved text of a document, or the file on the disk.
+    fn document_text(&self, path: &Path) -> String {
+        match self.documents.get(path) {
+            Some(source) => source.clone(),
+            None => std::fs::read_to_string(path).unwrap_or_default(),
+        }
+    }
+
+    /// The range of a location in `source`, the file it points into.
+    fn range(location: &SourceLocation, source: &str, encoding: PositionEncoding) -> Value {
+        let line = location.line.saturating_sub(1);
+        json!({
+            "start": { "line": line, "character": encoding.character(source, location.line, location.column) },
+            "end": { "line": line, "character": encoding.character(source, location.line, location.column + location.width) },
+        })
+    }
+
+    fn source_of(sources: &SourceMap, file: FileId) -> &str {
+        sources.get(file).map(|f| f.get_content().as_str()).unwrap_or_default()
+    }
+
+    fn contains(location: &SourceLocation, file: FileId, line: usize, column: usize) -> bool {
+        location.file == file && location.line == line && location.column <= column && column < location.column + location.width.max(1)
+    }
+
+    /// Analyzes the package a file belongs to and publishes its diagnostics.
+    fn analyze(&mut self, path: &Path) {
+        let manifest = match Manifest::for_source_file(path) {
+            Ok(manifest) => manifest,
+            Err((error, help)) => {
+                self.show_message(format!("{}\n{}", error, help));
+                return;
+            }
+        };
+        let key = manifest.get_file().cloned().unwrap_or(manifest.get_root().join(crate::compiler::manifest::MANIFEST_FILE));
+        let analysis = Compiler::new(manifest).analyze(&self.documents);
+        for (error, help) in &analysis.global_errors {
+            self.show_message(format!("{}{}", error, help.as_ref().map(|h| format!("\n{}", h)).unwrap_or_default()));
+        }
+        let mut diagnostics: HashMap<String, Vec<Value>> = HashMap::new();
+        for error in &analysis.errors {
+            let file = analysis.sources.get_path(error.get_location().file).to_string();
+            diagnostics.entry(file).or_default().push(Self::diagnostic(error, &analysis.sources, self.encoding));
+        }
+        let previous = self.published.remove(&key).unwrap_or_default();
+        for file in previous.iter().filter(|file| !diagnostics.contains_key(*file)) {
+            self.publish_diagnostics(file, Vec::new());
+        }
+        self.published.insert(key.clone(), diagnostics.keys().cloned().collect());
+        for (file, diagnostics) in diagnostics {
+            self.publish_diagnostics(&file, diagnostics);
+        }
+        self.analyses.insert(key, analysis);
+    }
+
+    fn diagnostic(error: &CompileError, sources: &SourceMap, encoding: PositionEncoding) -> Value {
+        let info = error.get_info();
+        // the label under the code only repeats the title, the editor already points at the code
+        let mut message = error.get_message().to_string();
+        if let Some(help) = &info.help {
+            message.push_str(&format!("\nhelp: {}", help));
+        }
+        let location = error.get_location();
+        let mut diagnostic = json!({
+            "range": Self::range(location, Self::source_of(sources, location.file), encoding),
+            "severity": if *error.get_error_type() == ErrorType::Error { 1 } else { 2 },
+            "code": error.get_message().code(),
+            "source": "snowball",
+            "message": message,
+        });
+        if let Some((related, location)) = &info.related {
+            diagnostic["relatedInformation"] = json!([{
+                "location": { "uri": path_to_uri(sources.get_path(location.file)), "range": Self::range(location, Self::source_of(sources, location.file), encoding) },
+                "message": related,
+            }]);
+        }
+        diagnostic
+    }
+
+    fn publish_diagnostics(&mut self, file: &str, diagnostics: Vec<Value>) {
+        self.outgoing.push(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": path_to_uri(file), "diagnostics": diagnostics },
+        }));
+    }
+
+    fn show_message(&mut self, message: String) {
+        self.outgoing.push(json!({
+            "jsonrpc": "2.0",
+            "method": "window/showMessage",
+            // 1 is an error message
+            "params": { "type": 1, "message": message },
+        }));
+    }
+
+    /// The analysis of the package a file belongs to, analyzing it if it wasn't yet.
+    fn get_analysis(&mut self, path: &Path) -> Option<&Analysis> {
+        let manifest = Manifest::for_source_file(path).ok()?;
+        let key = manifest.get_file().cloned().unwrap_or(manifest.get_root().join(crate::compiler::manifest::MANIFEST_FILE));
+        if !self.analyses.contains_key(&key) {
+            self.analyze(path);
+        }
+        self.analyses.get(&key)
+    }
+
+    /// Describes the innermost typed node at a position.
+    fn hover(&mut self, path: &Path, line: usize, column: usize) -> Option<String> {
+        let analysis = self.get_analysis(path)?;
+        let typechecker = analysis.typechecker.as_ref()?;
+        let file = analysis.sources.find(&path.to_string_lossy())?;
+        // the namespaces of a file are modules of their own
+        let modules = analysis.typed_modules.iter().filter(|m| m.get_file_name().as_ref().is_some_and(|f| Path::new(f) == path));
+        let mut found: Option<&TypedNode> = None;
+        let mut pending = modules.flat_map(|module| module.get_top().children()).collect::<Vec<_>>();
+        while let Some(node) = pending.pop() {
+            if let Some(location) = node.get_location() {
+                if Self::contains(location, file, line, column) && found.is_none_or(|f| location.width <= f.get_location().unwrap().width) {
+                    found = Some(node);
+                }
+            }
+            pending.extend(node.get_kind().children());
+        }
+        let node = found?;
+        let ty = typechecker.display_type(node.get_type());
+        Some(match node.get_kind() {
+            AST::FuncDef( name, args, ret, .. ) => {
+                let args = args.iter().map(|param| format!("{}{}: {}", if param.is_mutable() { "mut " } else { "" }, param.get_name(), typechecker.display_type(param.get_ty()))).collect::<Vec<_>>();
+                format!("func {}({}) {}", name, args.join(", "), typechecker.display_type(ret))
+            }
+            AST::ClassDef( name, .. ) => format!("class {}", name),
+            AST::EnumDef( name, .. ) => format!("enum {}", name),
+            AST::InterfaceDef( name, .. ) => format!("interface {}", name),
+            AST::VarDef( name, var_ty, _ ) => format!("let {}: {}", name, var_ty.as_ref().map(|ty| typechecker.display_type(ty)).unwrap_or(ty)),
+            AST::Ident( name, _ ) | AST::ClassAccess( _, name ) | AST::NamespaceAccess( _, name, _ ) => format!("{}: {}", name, ty),
+            _ => ty,
+        })
+    }
+
+    /// Where the name at a position was declared.
+    fn definition(&mut self, path: &Path, line: usize, column: usize) -> Option<(String, Value)> {
+        let encoding = self.encoding;
+        let analysis = self.get_analysis(path)?;
+        let typechecker = analysis.typechecker.as_ref()?;
+        let file = analysis.sources.find(&path.to_string_lossy())?;
+        typechecker.get_references().iter()
+            .filter(|(usage, _)| Self::contains(usage, file, line, column))
+            .min_by_key(|(usage, _)| usage.width)
+            .map(|(_, declaration)| (analysis.sources.get_path(declaration.file).to_string(), Self::range(declaration, Self::source_of(&analysis.sources, declaration.file), encoding)))
+    }
+
+    /// The functions, classes, enums, interfaces and namespaces of a document. Only the document itself is
+    /// parsed so the outline keeps working while other files have errors.
+    fn document_symbols(&self, path: &Path) -> Vec<Value> {
+        let source = self.document_text(path);
+        let file_path = path.to_string_lossy().to_string();
+        let mut lexer = crate::frontend::lexer::Lexer::new(&source, FileId::DUMMY);
+        lexer.lex();
+        if lexer.get_reports().has_errors() {
+            return Vec::new();
+        }
+        let mut parser = crate::frontend::parser::Parser::new(NamespacePath::new(Vec::new()), file_path, &lexer);
+        let Ok(module) = parser.parse() else {
+            return Vec::new();
+        };
+        module.get_top().children().into_iter().filter_map(|node| self.document_symbol(node, SYMBOL_FUNCTION, &source)).collect()
+    }
+
+    fn document_symbol(&self, node: &Node, function_kind: u32, source: &str) -> Option<Value> {
+        let location = node.get_location()?;
+        let (name, kind, children) = match node.get_kind() {
+            AST::FuncDef( name, .. ) => (name.clone(), function_kind, Vec::new()),
+            AST::NamespaceDef( Some(path), items ) => (Self::namespace_name(path), SYMBOL_NAMESPACE, items.iter().filter_map(|n| self.document_symbol(n, SYMBOL_FUNCTION, source)).collect()),
+            AST::ClassDef( name, _, _, _, methods, .. ) => (name.clone(), SYMBOL_CLASS, methods.iter().filter_map(|m| self.document_symbol(m, SYMBOL_METHOD, source)).collect()),
+            AST::InterfaceDef( name, methods, .. ) => (name.clone(), SYMBOL_INTERFACE, methods.iter().filter_map(|m| self.document_symbol(m, SYMBOL_METHOD, source)).collect()),
+            AST::EnumDef( name, variants, .. ) => (name.clone(), SYMBOL_ENUM, variants.iter().map(|variant| json!({
+                "name": variant.get_name(),
+                "kind": SYMBOL_ENUM_MEMBER,
+                "range": Self::range(variant.get_location(), source, self.encoding),
+                "selectionRange": Self::range(variant.get_location(), source, self.encoding),
+            })).collect()),
+            _ => return None,
+        };
+        Some(json!({
+            "name": name,
+            "kind": kind,
+            "range": Self::range(location, source, self.encoding),
+            "selectionRange": Self::range(location, source, self.encoding),
+            "children": children,
+        }))
+    }
+
+    /// The name of a namespace as it is written (e.g. `a::b`).
+    fn namespace_name(node: &Node) -> String {
+        match node.get_kind() {
+            AST::NamespaceAccess( base, name, _ ) => format!("{}::{}", Self::namespace_name(base), name),
+            AST::Ident( name, _ ) => name.clone(),
+            _ => String::new(),
+        }
+    }
+}
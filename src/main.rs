@@ -1,8 +1,12 @@
+#![allow(clippy::upper_case_acronyms)]
+
 use clap::Parser as ClapParser;
 
 mod ast;
+mod backend;
 mod compiler;
 mod frontend;
+mod lsp;
 mod reports;
 mod utils;
 
@@ -17,6 +21,12 @@ struct Args {
 enum SubCommand {
     Run(Run),
     Build(Build),
+    Test(Test),
+    Bench(Bench),
+    New(New),
+    Init(Init),
+    Lsp(Lsp),
+    Explain(Explain),
 }
 
 #[derive(ClapParser)]
@@ -24,6 +34,11 @@ enum SubCommand {
 struct Run {
     #[clap(short, long, default_value = "./")]
     path: String,
+    /// How diagnostics are printed
+    #[clap(long, value_enum, default_value = "human")]
+    message_format: reports::format::MessageFormat,
+    #[clap(flatten)]
+    cfg: CfgArgs,
 }
 
 #[derive(ClapParser)]
@@ -31,6 +46,119 @@ struct Run {
 struct Build {
     #[clap(short, long, default_value = "./")]
     path: String,
+    /// How diagnostics are printed
+    #[clap(long, value_enum, default_value = "human")]
+    message_format: reports::format::MessageFormat,
+    #[clap(flatten)]
+    cfg: CfgArgs,
+    /// What the build produces
+    #[clap(long, value_enum, default_value = "executable")]
+    emit: Emit,
+}
+
+#[derive(ClapParser)]
+#[clap(name = "Test", about = "Run the functions marked with @[test]", alias = "t")]
+struct Test {
+    #[clap(short, long, default_value = "./")]
+    path: String,
+    /// Only run the tests whose path contains this text
+    filter: Option<String>,
+    /// How diagnostics are printed
+    #[clap(long, value_enum, default_value = "human")]
+    message_format: reports::format::MessageFormat,
+    #[clap(flatten)]
+    cfg: CfgArgs,
+}
+
+#[derive(ClapParser)]
+#[clap(name = "Bench", about = "Run the functions marked with @[bench] and measure them")]
+struct Bench {
+    #[clap(short, long, default_value = "./")]
+    path: String,
+    /// Only run the benchmarks whose path contains this text
+    filter: Option<String>,
+    /// How often every benchmark runs before it is measured
+    #[clap(long, default_value = "3")]
+    warmup: usize,
+    /// How often every benchmark is measured
+    #[clap(long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    iterations: u64,
+    /// Compare the results against a baseline saved before
+    #[clap(long)]
+    baseline: Option<std::path::PathBuf>,
+    /// Write the results to a baseline file
+    #[clap(long)]
+    save_baseline: Option<std::path::PathBuf>,
+    /// How diagnostics are printed
+    #[clap(long, value_enum, default_value = "human")]
+    message_format: reports::format::MessageFormat,
+    #[clap(flatten)]
+    cfg: CfgArgs,
+}
+
+#[derive(ClapParser)]
+#[clap(name = "New", about = "Create a new project in a new directory")]
+struct New {
+    /// The directory to create, its name is used as the package name
+    name: String,
+}
+
+#[derive(ClapParser)]
+#[clap(name = "Init", about = "Create a new project in an existing directory")]
+struct Init {
+    #[clap(short, long, default_value = "./")]
+    path: String,
+}
+
+#[derive(ClapParser)]
+#[clap(name = "Lsp", about = "Start a language server that talks over stdin and stdout")]
+struct Lsp {}
+
+#[derive(ClapParser)]
+#[clap(name = "Explain", about = "Explain a diagnostic code in detail")]
+struct Explain {
+    /// The code shown next to the diagnostic (e.g. E0003)
+    code: String,
+}
+
+/// The options `@[cfg(..)]` attributes are evaluated against.
+#[derive(clap::Args)]
+struct CfgArgs {
+    /// Enable a cfg option, written as `name` or `key="value"`
+    #[clap(long = "cfg", value_name = "OPTION", value_parser = frontend::cfg::CfgSet::parse_option)]
+    options: Vec<(String, Option<String>)>,
+    /// Features of the package to enable, separated by commas
+    #[clap(long, value_delimiter = ',')]
+    features: Vec<String>,
+    /// Don't enable the `default` feature of the package
+    #[clap(long)]
+    no_default_features: bool,
+}
+
+impl CfgArgs {
+    /// `debug` is set for the interpreter, which runs the code as written,
+    /// and `test` while running tests.
+    fn apply(self, compiler: compiler::Compiler, debug: bool, test: bool) -> compiler::Compiler {
+        let mut cfg = frontend::cfg::CfgSet::for_target();
+        if debug {
+            cfg.enable("debug");
+        }
+        if test {
+            cfg.enable("test");
+        }
+        for option in &self.options {
+            cfg.add_option(option);
+        }
+        compiler.with_cfg(cfg).with_features(self.features, !self.no_default_features)
+    }
+}
+
+#[derive(Clone, clap::ValueEnum)]
+enum Emit {
+    /// A native executable built from C source code
+    Executable,
+    /// Textual LLVM IR (a `.ll` file) for LLVM 15 or newer
+    LlvmIr,
 }
 
 fn main() {
@@ -39,17 +167,127 @@ fn main() {
     match args.subcmd {
         SubCommand::Run(run) => run_command(run),
         SubCommand::Build(build) => build_command(build),
+        SubCommand::Test(test) => test_command(test),
+        SubCommand::Bench(bench) => bench_command(bench),
+        SubCommand::New(new) => new_command(new),
+        SubCommand::Init(init) => init_command(init),
+        SubCommand::Explain(explain) => explain_command(explain),
+        SubCommand::Lsp(_) => std::process::exit(lsp::LanguageServer::run()),
+    }
+    reports::format::finish();
+}
+
+/// Exits once the diagnostics that are written at the end are out.
+fn exit(code: i32) -> ! {
+    reports::format::finish();
+    std::process::exit(code)
+}
+
+fn load_compiler(path: &str, message_format: reports::format::MessageFormat) -> compiler::Compiler {
+    match compiler::manifest::Manifest::from_path(path) {
+        Ok(manifest) => compiler::Compiler::new(manifest).with_message_format(message_format),
+        Err((error, help)) => {
+            reports::format::emit(message_format, &ast::source::SourceMap::new(), &[], &[(error, Some(help))]);
+            exit(1);
+        }
     }
 }
 
 fn run_command(run: Run) {
-    let compiler = compiler::Compiler::new(run.path);
-    compiler.run();
-    // TODO: Run the code
+    let mut compiler = run.cfg.apply(load_compiler(&run.path, run.message_format), true, false);
+    let Some(entry) = compiler.get_entry() else {
+        compiler.emit_global_error(reports::Error::NotExecutable(compiler.get_manifest().get_package().get_name().clone()),
+            Some("Set 'type = \"executable\"' in the manifest to run the package.".to_string()));
+        exit(1);
+    };
+    let Some(modules) = compiler.run() else {
+        exit(1);
+    };
+    exit(backend::interpreter::Interpreter::run(modules, entry, compiler.get_sources(), compiler.get_message_format()));
 }
 
 fn build_command(build: Build) {
-    let compiler = compiler::Compiler::new(build.path);
-    compiler.run();
+    let mut compiler = build.cfg.apply(load_compiler(&build.path, build.message_format), false, false);
+    let Some(modules) = compiler.run() else {
+        exit(1);
+    };
+    let output = match build.emit {
+        Emit::Executable => compiler.build(&modules),
+        Emit::LlvmIr => compiler.emit_llvm_ir(&modules),
+    };
+    if output.is_none() {
+        exit(1);
+    }
 }
 
+fn test_command(test: Test) {
+    let mut compiler = test.cfg.apply(load_compiler(&test.path, test.message_format), true, true);
+    let Some(modules) = compiler.run() else {
+        exit(1);
+    };
+    if !compiler.test(&modules, test.filter.as_deref()) {
+        exit(1);
+    }
+}
+
+fn bench_command(bench: Bench) {
+    let mut compiler = bench.cfg.apply(load_compiler(&bench.path, bench.message_format), false, false);
+    let Some(modules) = compiler.run() else {
+        exit(1);
+    };
+    let options = compiler::benchmarking::BenchOptions {
+        filter: bench.filter.as_deref(),
+        warmup: bench.warmup,
+        iterations: bench.iterations as usize,
+        baseline: bench.baseline.as_deref(),
+        save_baseline: bench.save_baseline.as_deref(),
+    };
+    if !compiler.bench(&modules, &options) {
+        exit(1);
+    }
+}
+
+fn new_command(new: New) {
+    let dir = std::path::Path::new(&new.name);
+    if dir.exists() {
+        reports::CompileError::print_global(reports::Error::AlreadyExists(new.name.clone()), Some("Use 'snowball init' to create a project in an existing directory.".to_string()));
+        exit(1);
+    }
+    create_project(dir, compiler::manifest::Manifest::directory_name(dir));
+}
+
+fn init_command(init: Init) {
+    let dir = std::path::Path::new(&init.path);
+    let manifest = dir.join(compiler::manifest::MANIFEST_FILE);
+    if manifest.exists() {
+        reports::CompileError::print_global(reports::Error::AlreadyExists(manifest.display().to_string()), None);
+        exit(1);
+    }
+    create_project(dir, compiler::manifest::Manifest::directory_name(dir));
+}
+
+fn explain_command(explain: Explain) {
+    let code = explain.code.to_uppercase();
+    let Some(explanation) = reports::explain::explain(&code) else {
+        reports::CompileError::print_global(reports::Error::UnknownErrorCode(explain.code), Some("Codes look like 'E0003' and are shown next to every diagnostic.".to_string()));
+        exit(1);
+    };
+    println!("{}", explanation);
+}
+
+fn create_project(dir: &std::path::Path, name: String) {
+    if !compiler::manifest::Manifest::is_valid_name(&name) {
+        reports::CompileError::print_global(reports::Error::InvalidPackageName(name), Some("Package names can only contain letters, digits and '_', and cannot start with a digit.".to_string()));
+        exit(1);
+    }
+    if let Err(error) = compiler::manifest::Manifest::create_project(dir, &name) {
+        let path = dir.display().to_string();
+        let error_type = match error.kind() {
+            std::io::ErrorKind::AlreadyExists => reports::Error::AlreadyExists(path),
+            _ => reports::Error::FileAccess("create the package in".to_string(), path),
+        };
+        reports::CompileError::print_global(error_type, Some(error.to_string()));
+        exit(1);
+    }
+    println!("{}{}Created{} package '{}'", bold!(), green!(), reset!(), name);
+}
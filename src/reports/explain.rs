@@ -0,0 +1,718 @@
+/// The long form explanation of a diagnostic code, as printed by `snowball explain`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "E0001" => "\
+A character that cannot start any token was found in the source code.
+
+Erroneous code example:
+
+    let x = 5 $ 3;
+
+Remove the character or replace it with a valid operator.",
+        "E0002" => "\
+A token appeared where the parser did not expect it.
+
+Erroneous code example:
+
+    func main() i32 {
+        return 0
+    }}
+
+Check the surrounding code for missing or extra punctuation.",
+        "E0003" => "\
+A name was used that isn't declared in the current scope, the module or its imports.
+
+Erroneous code example:
+
+    func main() i32 {
+        return count;
+    }
+
+Declare the variable before using it:
+
+    func main() i32 {
+        let count = 0;
+        return count;
+    }
+
+Names declared in other modules have to be imported with 'import'.",
+        "E0004" => "\
+The file ended while the parser was still in the middle of a declaration
+or a string literal.
+
+Erroneous code example:
+
+    func main() i32 {
+        return 0;
+
+Close every block and string before the end of the file.",
+        "E0005" => "\
+The parser expected a specific item (e.g. a name or a type) after a keyword
+or a symbol.
+
+Erroneous code example:
+
+    func (a: i32) i32 { return a; }
+
+Functions need a name after 'func'.",
+        "E0006" => "\
+The parser expected a specific token but found a different one.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x = 5
+        return x;
+    }
+
+Statements are terminated with ';'.",
+        "E0007" => "\
+'external' functions must say which calling convention they use, only \"C\"
+is supported.
+
+Erroneous code example:
+
+    external \"Rust\" func puts(s: String) i32;
+
+Use the C calling convention:
+
+    external \"C\" func puts(s: String) i32;",
+        "E0008" => "\
+A function declares two parameters with the same name.
+
+Erroneous code example:
+
+    func add(a: i32, a: i32) i32 { return a + a; }
+
+Give every parameter a unique name.",
+        "E0009" => "\
+A different kind of item was found than the one that is allowed here, e.g.
+a value where a type was expected.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x: 5 = 5;
+        return x;
+    }",
+        "E0010" => "\
+A generic type or function was given more generic arguments than it declares.
+
+Erroneous code example:
+
+    func id<T>(x: T) T { return x; }
+
+    func main() i32 {
+        return id<i32, i32>(1);
+    }",
+        "E0011" => "\
+A name is declared twice in the same scope, or an import conflicts with a
+declaration of the module.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x = 1;
+        let x = 2;
+        return x;
+    }
+
+Rename one of the declarations.",
+        "E0012" => "\
+A class inherits from itself, directly or through its parents.
+
+Erroneous code example:
+
+    class A: B {}
+    class B: A {}",
+        "E0013" => "\
+A class tried to inherit from a class marked as 'final'.
+
+Erroneous code example:
+
+    final class Base {}
+    class Derived: Base {}
+
+Remove 'final' from the parent class or don't inherit from it.",
+        "E0014" => "\
+A method is marked with 'override' but no parent class declares a method
+with the same name.
+
+Erroneous code example:
+
+    class Base {}
+    class Derived: Base {
+        override func run(self) {}
+    }",
+        "E0015" => "\
+A method has the same name as a method of a parent class but isn't marked
+with 'override'.
+
+Erroneous code example:
+
+    class Base {
+        func run(self) {}
+    }
+    class Derived: Base {
+        func run(self) {}
+    }
+
+Mark the method with 'override' to replace the parent method.",
+        "E0016" => "\
+An abstract method was declared in a class that isn't abstract.
+
+Erroneous code example:
+
+    class Shape {
+        abstract func area(self) f64;
+    }
+
+Mark the class as 'abstract' or give the method a body.",
+        "E0017" => "\
+A concrete class inherits an abstract method without implementing it.
+
+Erroneous code example:
+
+    abstract class Shape {
+        abstract func area(self) f64;
+    }
+    class Square: Shape {}
+
+Implement the method with 'override func area(self) f64 { ... }'.",
+        "E0018" => "\
+A value has a different type than the one that is expected.
+
+Erroneous code example:
+
+    func main() i32 {
+        return \"zero\";
+    }
+
+Make sure the value matches the expected type.",
+        "E0019" => "\
+The type of a value could not be inferred from how it is used.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x;
+        return 0;
+    }
+
+Add a type annotation ('let x: i32;') or an initial value.",
+        "E0020" => "\
+'break' or 'continue' was used outside of a loop.
+
+Erroneous code example:
+
+    func main() i32 {
+        break;
+        return 0;
+    }",
+        "E0021" => "\
+A value that isn't a function was called.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x = 5;
+        return x();
+    }",
+        "E0022" => "\
+A function was called with a different number of arguments than it declares.
+
+Erroneous code example:
+
+    func add(a: i32, b: i32) i32 { return a + b; }
+
+    func main() i32 {
+        return add(1);
+    }",
+        "E0023" => "\
+A member or method was accessed that the type doesn't have.
+
+Erroneous code example:
+
+    class Point {
+        public let x: i32;
+    }
+
+    func main() i32 {
+        let p = new Point();
+        return p.y;
+    }",
+        "E0024" => "\
+A private member or method was accessed from outside of its class.
+
+Erroneous code example:
+
+    class Account {
+        private let balance: i32;
+    }
+
+    func main() i32 {
+        let a = new Account();
+        return a.balance;
+    }
+
+Mark the member as 'public' or access it through a public method.",
+        "E0025" => "\
+An operator was applied to a type that doesn't support it.
+
+Erroneous code example:
+
+    func main() i32 {
+        let x = \"a\" - \"b\";
+        return 0;
+    }",
+        "E0026" => "\
+An abstract class was instantiated with 'new'.
+
+Erroneous code example:
+
+    abstract class Shape {}
+
+    func main() i32 {
+        let s = new Shape();
+        return 0;
+    }
+
+Instantiate a concrete class that inherits from it instead.",
+        "E0027" => "\
+The left hand side of an assignment isn't something that can be assigned to.
+
+Erroneous code example:
+
+    func main() i32 {
+        5 = 3;
+        return 0;
+    }",
+        "E0028" => "\
+A function that returns a value has a path that reaches its end without
+returning.
+
+Erroneous code example:
+
+    func sign(x: i32) i32 {
+        if x > 0 {
+            return 1;
+        }
+    }
+
+Return a value on every path.",
+        "E0029" => "\
+An import names a module or an item that doesn't exist.
+
+Erroneous code example:
+
+    import utils::missing;
+
+Check the path of the import, modules are named after their file relative
+to the source directory (e.g. 'src/utils/math.sn' is 'utils::math').",
+        "E0030" => "\
+An import names an item that isn't public.
+
+Erroneous code example:
+
+    // utils.sn
+    func helper() i32 { return 1; }
+
+    // main.sn
+    import utils::helper;
+
+Mark the item as 'public' in its module.",
+        "E0031" => "\
+Modules import each other in a cycle.
+
+Erroneous code example:
+
+    // a.sn
+    import b;
+
+    // b.sn
+    import a;
+
+Move the shared declarations into a third module.",
+        "E0032" => "\
+The entry module of an executable package has no 'main' function.
+
+Declare one in the entry module (set with 'entry' in 'snowball.toml'):
+
+    func main() i32 {
+        return 0;
+    }
+
+Packages without an entry point are built as libraries with
+'type = \"library\"'.",
+        "E0033" => "\
+The program divided an integer by zero while it was running.
+
+Erroneous code example:
+
+    func main() i32 {
+        let zero = 0;
+        return 1 / zero;
+    }
+
+Check the divisor before dividing.",
+        "E0034" => "\
+The program called functions too deeply while it was running, usually
+because of a recursion that never ends.
+
+Erroneous code example:
+
+    func forever(x: i32) i32 {
+        return forever(x + 1);
+    }
+
+Make sure every recursion has a base case.",
+        "E0035" => "\
+The code uses a feature that the selected backend cannot handle yet.
+
+Try another backend, e.g. 'snowball run' uses the interpreter which
+supports classes, while 'snowball build' generates C.",
+        "E0036" => "\
+The system C compiler failed while building the generated C code.
+
+Set the 'CC' environment variable to the C compiler to use, the default is 'cc'.",
+        "E0037" => "\
+The source directory of a package doesn't contain any '.sn' files.
+
+Check the 'src' setting in 'snowball.toml' (it defaults to \"src\").",
+        "E0038" => "\
+The 'snowball.toml' manifest could not be read. A valid manifest looks like:
+
+    [package]
+    name = \"app\"
+    version = \"0.1.0\"
+
+    [dependencies]
+    utils = { path = \"../utils\" }",
+        "E0039" => "\
+Package names can only contain letters, digits and '_', and cannot start
+with a digit.
+
+Erroneous code example:
+
+    snowball new my-app
+
+Use 'snowball new my_app' instead.",
+        "E0040" => "\
+'snowball new' or 'snowball init' would overwrite an existing file or directory.
+
+Use 'snowball init' to add a manifest to an existing directory.",
+        "E0041" => "\
+Two files resolve to the same module name, e.g. a file of the package and
+a file of a dependency with the same prefix.
+
+Rename one of the files.",
+        "E0042" => "\
+'snowball run' was used on a library package.
+
+Set 'type = \"executable\"' in 'snowball.toml' and declare a 'main' function
+to run the package.",
+        "E0043" => "\
+'snowball explain' was given a code that doesn't belong to any diagnostic.
+
+Codes look like 'E0003' for errors and 'W0001' for warnings, they are
+shown next to every diagnostic.",
+        "E0044" => "\
+An attribute was written that the compiler doesn't know.
+
+Erroneous code example:
+
+    @[tset]
+    func checks_addition() {}
+
+The known attributes are:
+
+    test, bench                  marks a function as a test or a benchmark
+    cfg(condition)               only compiles the item if the condition holds
+    deprecated(\"message\")       marks an item as deprecated
+    unstable(\"reason\")          marks an item as unstable
+    inline, no_inline            inlining hints for functions and methods
+    no_optimize                  keeps a function from being optimized
+    no_mangle, export            keeps the symbol name of a function as is
+    linkage(\"internal\")         the linkage of a function
+    builtin, unsafe, llvm_function, not_implemented, no_constructors",
+        "E0045" => "\
+An attribute was written before an item it cannot be used on.
+
+Erroneous code example:
+
+    @[no_constructors]
+    func main() i32 { return 0; }
+
+'no_constructors' can only be used on classes. Attributes cannot be used on
+imports or statements at all.",
+        "E0046" => "\
+An attribute was given arguments it doesn't accept.
+
+Erroneous code example:
+
+    @[test(\"slow\")]
+    func checks_addition() {}
+
+Most attributes take no arguments, 'cfg' takes a condition and 'deprecated',
+'unstable' and 'linkage' take a string.",
+        "E0047" => "\
+A function marked with '@[test]' cannot be run as a test.
+
+Erroneous code example:
+
+    @[test]
+    func checks_addition(a: i32) bool {
+        return a + a == 2 * a;
+    }
+
+Tests take no parameters and return nothing, an 'i32' (0 passes) or a
+'bool' (true passes). They can't be generic.",
+        "E0048" => "\
+A function marked with '@[bench]' cannot be run as a benchmark.
+
+Erroneous code example:
+
+    @[bench]
+    func sums(n: i32) i32 {
+        return n * (n + 1) / 2;
+    }
+
+Benchmarks take no parameters and can't be generic, their result is ignored.",
+        "E0049" => "\
+The baseline file given to 'snowball bench' could not be read or written.
+
+Baselines are JSON files created with:
+
+    snowball bench --save-baseline baseline.json
+
+and compared against with:
+
+    snowball bench --baseline baseline.json",
+        "E0050" => "\
+The condition of a 'cfg' attribute couldn't be understood.
+
+Erroneous code example:
+
+    @[cfg(target_os == \"linux\")]
+    func open_file() {}
+
+Conditions are built from options and the operators 'all', 'any' and 'not':
+
+    unix, windows                the family of the target
+    target_os = \"linux\"          the operating system (also target_arch, target_family)
+    debug                        set by 'snowball run' and 'snowball test'
+    test                         set by 'snowball test'
+    feature = \"json\"             a feature of the package
+    all(a, b), any(a, b), not(a)
+
+More options are set with '--cfg name' or '--cfg key=\"value\"'.",
+        "E0051" => "\
+A feature was asked for that the package doesn't declare.
+
+Features are declared in the manifest, every feature lists the features it
+enables in turn:
+
+    [features]
+    default = [\"json\"]
+    json = []
+    fast = [\"json\"]
+
+and enabled with 'snowball build --features fast'.",
+        "E0052" => "\
+A parameter has a default value that isn't allowed, or is missing one.
+
+Erroneous code example:
+
+    func greet(greeting: String = \"Hello\", name: String) {}
+
+Default values are filled in where the function is called, so they have to
+be literals (numbers, strings and booleans). Once a parameter has a default
+value, every parameter after it needs one too:
+
+    func greet(name: String, greeting: String = \"Hello\") {}",
+        "E0053" => "\
+A match doesn't handle every value it could be given.
+
+Erroneous code example:
+
+    enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }
+
+    func area(shape: Shape) f64 {
+        return match shape {
+            Shape::Circle(r) => 3.14 * r * r,
+            Shape::Empty => 0.0,
+        };
+    }
+
+The error lists the patterns that aren't covered. Add arms for them, or a
+wildcard arm ('_ => ...') that handles everything else. Arms with an 'if'
+guard don't count, since their guard could be false.",
+        "E0054" => "\
+A variant was used that its enum doesn't declare.
+
+Erroneous code example:
+
+    enum Shape { Circle(f64), Empty }
+
+    let shape = Shape::Square(2.0);
+
+Check the spelling, or add the variant to the enum.",
+        "E0055" => "\
+A variant was created or matched with the wrong kind of fields.
+
+Erroneous code example:
+
+    enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }
+
+    let a = Shape::Circle;
+    let b = Shape::Rect { w: 1.0 };
+
+Variants are written the way they are declared: 'Shape::Empty',
+'Shape::Circle(1.0)' and 'Shape::Rect { w: 1.0, h: 2.0 }'. Patterns can
+leave out named fields with '..', e.g. 'Shape::Rect { w, .. }'.",
+        "E0056" => "\
+A class says it implements an interface but is missing one of its methods.
+
+Erroneous code example:
+
+    interface Show { func show(self) String; }
+
+    class Point: Show {
+        public let x: i32;
+    }
+
+Declare every method of the interface in the class (or one of its parent
+classes) with the same signature.",
+        "E0057" => "\
+A class implements a method of an interface with a different signature.
+
+Erroneous code example:
+
+    interface Show { func show(self) String; }
+
+    class Point: Show {
+        public func show(self) i32 { return 0; }
+    }
+
+The parameters, return type and generic parameters must be the ones the
+interface declares, with the generic arguments of the interface filled in.
+The method must take 'self' and be public.",
+        "E0058" => "\
+A generic argument doesn't implement an interface its parameter requires.
+
+Erroneous code example:
+
+    interface Show { func show(self) String; }
+
+    func print<T: Show>(value: T) {}
+
+    func main() i32 {
+        print(1);
+        return 0;
+    }
+
+List the interface after the ':' of the class used as the argument (e.g.
+'class Point: Show') and implement its methods, or pass a type that already
+implements it.",
+        "E0059" => "\
+Instantiating a generic function or class needs another instantiation with
+bigger generic arguments, over and over again.
+
+Erroneous code example:
+
+    class Box<T> {
+        public let value: T;
+        public func constructor(self, value: T) { self.value = value; }
+    }
+
+    func wrap<T>(value: T, depth: i32) i32 {
+        if depth == 0 { return 0; }
+        return wrap(new Box(value), depth - 1);
+    }
+
+    func main() i32 {
+        return wrap(1, 3);
+    }
+
+Every generic function and class is compiled once for every set of generic
+arguments it is used with, so 'wrap<i32>' needs 'wrap<Box<i32>>', which needs
+'wrap<Box<Box<i32>>>' and so on. Pass the values through a type that doesn't
+grow with every call instead.",
+        "E0060" => "\
+A number literal doesn't fit in its type or isn't a valid number.
+
+Erroneous code example:
+
+    func main() i32 {
+        let big = 9223372036854775808;
+        return 0;
+    }
+
+Integers are 64 bit signed values, so they go from -9223372036854775808 to
+9223372036854775807. Hexadecimal ('0x'), binary ('0b') and octal ('0o')
+literals need at least one digit after their prefix.",
+        "E0061" => "\
+The condition passed to 'assert' was false while the program was running.
+
+Erroneous code example:
+
+    func main() i32 {
+        let total = 1 + 1;
+        assert(total == 3);
+        return 0;
+    }
+
+'assert' stops the program (or fails the test it is called from) when its
+condition doesn't hold. Fix the code that computed the value, or the
+condition if it expects the wrong thing.",
+        "E0062" => "\
+A file or directory the compiler needs could not be read or written.
+
+The message says which file it is and the help below it gives the reason
+the operating system reported, e.g. missing permissions or a source file
+that isn't valid UTF-8. Source files must be UTF-8 text.",
+        "E0063" => "\
+A function declares a 'self' parameter but isn't a method, or 'self' isn't
+its first parameter.
+
+'self' is the instance a method is called on, so only the first parameter
+of a method in a class or interface can be named 'self':
+
+    class Counter {
+        let count: i32;
+        func get(self) i32 { return self.count; }
+    }",
+        "W0001" => "\
+A statement ends with more than one semicolon.
+
+Code example:
+
+    func main() i32 {
+        return 0;;
+    }
+
+Remove the extra semicolons.",
+        "W0002" => "\
+A string or character literal contains an escape sequence that doesn't exist.
+The character after the '\\' is used as is.
+
+Code example:
+
+    let s = \"a\\qb\";
+
+The known escape sequences are '\\n', '\\r', '\\t', '\\0', '\\\\', '\\'' and '\\\"'.",
+        "W0003" => "\
+A match arm can never run because the arms before it match every value it
+matches.
+
+Code example:
+
+    match shape {
+        _ => 0,
+        Shape::Empty => 1,
+    }
+
+Remove the arm, or move it before the arms that hide it.",
+        _ => return None,
+    })
+}
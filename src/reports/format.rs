@@ -0,0 +1,179 @@
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use crate::ast::source::{SourceLocation, SourceMap};
+use crate::reports::{CompileError, Error, ErrorType};
+
+/// How diagnostics are written. Human diagnostics go to stdout, the machine
+/// readable ones to stderr so they never mix with the output of a program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum MessageFormat {
+    /// Colored snippets of the source code
+    #[default]
+    Human,
+    /// One JSON object per line and diagnostic, lines and columns start at 1
+    Json,
+    /// A single SARIF 2.1.0 log, written once the command finished
+    Sarif,
+}
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The SARIF results of the whole command, a log is only written by `finish`
+/// since every command has to produce a single document.
+static SARIF_RESULTS: Mutex<Option<Vec<Value>>> = Mutex::new(None);
+
+/// Writes the diagnostics of a compilation in the given format. Errors that
+/// don't belong to a location are written as diagnostics without a file.
+pub fn emit(format: MessageFormat, sources: &SourceMap, errors: &[CompileError], global_errors: &[(Error, Option<String>)]) {
+    match format {
+        MessageFormat::Human => {
+            for (error, help) in global_errors {
+                CompileError::print_global(error.clone(), help.clone());
+            }
+            for error in errors {
+                error.print(sources);
+            }
+        }
+        MessageFormat::Json => {
+            for (error, help) in global_errors {
+                eprintln!("{}", json!({ "severity": "error", "code": error.code(), "message": error.to_string(), "help": help, "file": null }));
+            }
+            for error in errors {
+                eprintln!("{}", to_json(error, sources));
+            }
+        }
+        MessageFormat::Sarif => {
+            let results = sarif_results(sources, errors, global_errors);
+            SARIF_RESULTS.lock().unwrap().get_or_insert_with(Vec::new).extend(results);
+        }
+    }
+}
+
+/// Writes the SARIF log of the command if there is one, called right before exiting.
+pub fn finish() {
+    if let Some(results) = SARIF_RESULTS.lock().unwrap().take() {
+        eprintln!("{}", sarif_log(results));
+    }
+}
+
+fn severity(error: &CompileError) -> &'static str {
+    match error.get_error_type() {
+        ErrorType::Error => "error",
+        ErrorType::Warning => "warning",
+    }
+}
+
+fn location_to_json(location: &SourceLocation, sources: &SourceMap) -> Value {
+    // columns are 1 based like lines, as in SARIF
+    json!({
+        "file": sources.get_path(location.file),
+        "line": location.line,
+        "column": location.column + 1,
+        "width": location.width,
+    })
+}
+
+pub fn to_json(error: &CompileError, sources: &SourceMap) -> Value {
+    let info = error.get_info();
+    let mut value = json!({
+        "severity": severity(error),
+        "code": error.get_message().code(),
+        "message": error.get_message().to_string(),
+        "info": info.info,
+        "help": info.help,
+        "note": info.note,
+        "see": info.see,
+        "related": info.related.as_ref().map(|(message, location)| {
+            let mut related = location_to_json(location, sources);
+            related["message"] = json!(message);
+            related
+        }),
+    });
+    // the location is flattened into the diagnostic itself
+    for (key, field) in location_to_json(error.get_location(), sources).as_object().unwrap() {
+        value[key] = field.clone();
+    }
+    value
+}
+
+fn sarif_location(location: &SourceLocation, sources: &SourceMap) -> Value {
+    // SARIF columns are 1 based and the end column is exclusive
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": sources.get_path(location.file) },
+            "region": {
+                "startLine": location.line,
+                "startColumn": location.column + 1,
+                "endColumn": location.column + location.width.max(1) + 1,
+            },
+        },
+    })
+}
+
+fn sarif_results(sources: &SourceMap, errors: &[CompileError], global_errors: &[(Error, Option<String>)]) -> Vec<Value> {
+    let mut results = Vec::new();
+    for (error, help) in global_errors {
+        let text = match help {
+            Some(help) => format!("{}\nhelp: {}", error, help),
+            None => error.to_string(),
+        };
+        results.push(json!({ "ruleId": error.code(), "level": "error", "message": { "text": text } }));
+    }
+    for error in errors {
+        let info = error.get_info();
+        let mut text = error.get_message().to_string();
+        for (label, extra) in [("", &info.info), ("help: ", &info.help), ("note: ", &info.note), ("see: ", &info.see)] {
+            if let Some(extra) = extra {
+                text.push_str(&format!("\n{}{}", label, extra));
+            }
+        }
+        let mut result = json!({
+            "ruleId": error.get_message().code(),
+            "level": severity(error),
+            "message": { "text": text },
+            "locations": [sarif_location(error.get_location(), sources)],
+        });
+        if let Some((message, location)) = &info.related {
+            let mut related = sarif_location(location, sources);
+            related["id"] = json!(0);
+            related["message"] = json!({ "text": message });
+            result["relatedLocations"] = json!([related]);
+        }
+        results.push(result);
+    }
+    results
+}
+
+fn sarif_log(results: Vec<Value>) -> Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "snowball",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_lines_and_columns_start_at_one() {
+        let mut sources = SourceMap::new();
+        let file = sources.add("main.sn".to_string(), "let x = y;\n".to_string());
+        let error = CompileError::new(Error::UnknownVariable("y".to_string()), SourceLocation::new(file, 1, 8, 1));
+        let value = to_json(&error, &sources);
+        assert_eq!((value["line"].as_u64(), value["column"].as_u64(), value["width"].as_u64()), (Some(1), Some(9), Some(1)));
+        let region = &sarif_results(&sources, &[error], &[])[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], value["column"]);
+    }
+}
@@ -1,6 +1,12 @@
+use crate::ast::source::{SourceLocation, SourceMap};
 use crate::{black, blue, bold, red, reset, yellow};
-use std::fs;
 
+pub mod explain;
+pub mod format;
+
+use format::MessageFormat;
+
+#[derive(Clone)]
 pub enum Error {
     UnexpectedChar(char),
     UnknownEscapeSequence(char),
@@ -13,13 +19,141 @@ pub enum Error {
     UnexpectedItem(String, String),
     TooManyGenerics(usize, usize),
     VariableAlreadyDeclared(String),
+    CyclicInheritance(String),
+    InheritFromFinal(String),
+    NothingToOverride(String),
+    MissingOverride(String),
+    AbstractMethodInConcreteClass(String, String),
+    UnimplementedAbstractMethod(String, String),
+    TypeMismatch(String, String),
+    TypeAnnotationsNeeded,
+    OutsideOfLoop(String),
+    NotCallable(String),
+    ArgumentCountMismatch(usize, usize),
+    UnknownMember(String, String),
+    PrivateMember(String, String),
+    InvalidOperands(String, String),
+    InstantiateAbstract(String),
+    InvalidAssignment,
+    MissingReturn(String, String),
+    MissingMain,
+    DivisionByZero,
+    StackOverflow,
+    Unsupported(String, String),
+    CCompilerFailed(String),
+    NoSourceFiles(String),
+    InvalidManifest(String),
+    InvalidPackageName(String),
+    AlreadyExists(String),
+    ConflictingModules(String),
+    NotExecutable(String),
+    UnresolvedImport(String),
+    PrivateImport(String, String),
+    ImportCycle(String),
     UnexpectedEOF,
     ExcessiveSemicolon,
+    UnknownErrorCode(String),
+    UnknownAttribute(String),
+    MisplacedAttribute(String, String),
+    InvalidAttributeArguments(String, String),
+    InvalidTest(String, String),
+    InvalidBenchmark(String, String),
+    InvalidBaseline(String, String),
+    InvalidCfg(String, String),
+    UnknownFeature(String, String),
+    InvalidDefaultParameter(String, String),
+    NonExhaustiveMatch(String),
+    UnknownVariant(String, String),
+    InvalidVariant(String, String),
+    MissingInterfaceMethod(String, String, String),
+    InterfaceMethodMismatch(String, String, String),
+    UnsatisfiedBound(String, String),
+    InstantiationLimit(String),
+    InvalidNumber(String),
+    AssertionFailed,
+    FileAccess(String, String),
+    InvalidSelfParameter,
+    UnreachablePattern,
 }
 
-impl ToString for Error {
-    fn to_string(&self) -> String {
+impl Error {
+    /// The stable code of the diagnostic, explained by `snowball explain`.
+    /// Codes are never reused, new variants get the next free number.
+    pub fn code(&self) -> &'static str {
         match self {
+            Error::UnexpectedChar(_) => "E0001",
+            Error::UnexpectedToken(_) => "E0002",
+            Error::UnknownVariable(_) => "E0003",
+            Error::UnexpectedEOF => "E0004",
+            Error::ExpectedItem(..) => "E0005",
+            Error::ExpectedItemAfter(..) => "E0006",
+            Error::InvalidExternalSpecifier(_) => "E0007",
+            Error::RepeatedParameter(_) => "E0008",
+            Error::UnexpectedItem(..) => "E0009",
+            Error::TooManyGenerics(..) => "E0010",
+            Error::VariableAlreadyDeclared(_) => "E0011",
+            Error::CyclicInheritance(_) => "E0012",
+            Error::InheritFromFinal(_) => "E0013",
+            Error::NothingToOverride(_) => "E0014",
+            Error::MissingOverride(_) => "E0015",
+            Error::AbstractMethodInConcreteClass(..) => "E0016",
+            Error::UnimplementedAbstractMethod(..) => "E0017",
+            Error::TypeMismatch(..) => "E0018",
+            Error::TypeAnnotationsNeeded => "E0019",
+            Error::OutsideOfLoop(_) => "E0020",
+            Error::NotCallable(_) => "E0021",
+            Error::ArgumentCountMismatch(..) => "E0022",
+            Error::UnknownMember(..) => "E0023",
+            Error::PrivateMember(..) => "E0024",
+            Error::InvalidOperands(..) => "E0025",
+            Error::InstantiateAbstract(_) => "E0026",
+            Error::InvalidAssignment => "E0027",
+            Error::MissingReturn(..) => "E0028",
+            Error::UnresolvedImport(_) => "E0029",
+            Error::PrivateImport(..) => "E0030",
+            Error::ImportCycle(_) => "E0031",
+            Error::MissingMain => "E0032",
+            Error::DivisionByZero => "E0033",
+            Error::StackOverflow => "E0034",
+            Error::Unsupported(..) => "E0035",
+            Error::CCompilerFailed(_) => "E0036",
+            Error::NoSourceFiles(_) => "E0037",
+            Error::InvalidManifest(_) => "E0038",
+            Error::InvalidPackageName(_) => "E0039",
+            Error::AlreadyExists(_) => "E0040",
+            Error::ConflictingModules(_) => "E0041",
+            Error::NotExecutable(_) => "E0042",
+            Error::UnknownErrorCode(_) => "E0043",
+            Error::UnknownAttribute(_) => "E0044",
+            Error::MisplacedAttribute(..) => "E0045",
+            Error::InvalidAttributeArguments(..) => "E0046",
+            Error::InvalidTest(..) => "E0047",
+            Error::InvalidBenchmark(..) => "E0048",
+            Error::InvalidBaseline(..) => "E0049",
+            Error::InvalidCfg(..) => "E0050",
+            Error::UnknownFeature(..) => "E0051",
+            Error::InvalidDefaultParameter(..) => "E0052",
+            Error::NonExhaustiveMatch(_) => "E0053",
+            Error::UnknownVariant(..) => "E0054",
+            Error::InvalidVariant(..) => "E0055",
+            Error::MissingInterfaceMethod(..) => "E0056",
+            Error::InterfaceMethodMismatch(..) => "E0057",
+            Error::UnsatisfiedBound(..) => "E0058",
+            Error::InstantiationLimit(_) => "E0059",
+            Error::InvalidNumber(_) => "E0060",
+            Error::AssertionFailed => "E0061",
+            Error::FileAccess(..) => "E0062",
+            Error::InvalidSelfParameter => "E0063",
+            Error::ExcessiveSemicolon => "W0001",
+            Error::UnknownEscapeSequence(_) => "W0002",
+            Error::UnreachablePattern => "W0003",
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
             Error::UnexpectedChar(c) => format!("unexpected character: '{}'", c),
             Error::UnexpectedEOF => "unexpected end of file".to_string(),
             Error::UnknownEscapeSequence(c) => format!("unknown escape sequence: '\\{}'", c),
@@ -35,14 +169,79 @@ impl ToString for Error {
             Error::UnexpectedItem(expected, found) => {
                 format!("expected '{}' here but found '{}'", expected, found)
             }
+            Error::CyclicInheritance(class) => format!("class '{}' inherits from itself!", class),
+            Error::InheritFromFinal(class) => format!("cannot inherit from final class '{}'", class),
+            Error::NothingToOverride(method) => format!("method '{}' does not override anything", method),
+            Error::MissingOverride(method) => format!("method '{}' overrides a parent method but is not marked as 'override'", method),
+            Error::AbstractMethodInConcreteClass(method, class) => {
+                format!("abstract method '{}' declared in non-abstract class '{}'", method, class)
+            }
+            Error::UnimplementedAbstractMethod(class, method) => {
+                format!("class '{}' does not implement abstract method '{}'", class, method)
+            }
+            Error::TypeMismatch(expected, found) => format!("mismatched types: expected '{}' but found '{}'", expected, found),
+            Error::TypeAnnotationsNeeded => "type annotations needed".to_string(),
+            Error::OutsideOfLoop(keyword) => format!("'{}' outside of a loop", keyword),
+            Error::NotCallable(ty) => format!("value of type '{}' is not callable", ty),
+            Error::ArgumentCountMismatch(expected, found) => {
+                format!("expected '{}' arguments but found '{}'", expected, found)
+            }
+            Error::UnknownMember(ty, member) => format!("type '{}' has no member named '{}'", ty, member),
+            Error::PrivateMember(member, class) => format!("'{}' is private to class '{}'", member, class),
+            Error::InvalidOperands(op, ty) => format!("operator '{}' cannot be applied to type '{}'", op, ty),
+            Error::InstantiateAbstract(class) => format!("cannot instantiate abstract class '{}'", class),
+            Error::InvalidAssignment => "invalid left-hand side of assignment".to_string(),
+            Error::MissingReturn(function, ty) => format!("function '{}' must return a value of type '{}'", function, ty),
+            Error::MissingMain => "no 'main' function found".to_string(),
+            Error::DivisionByZero => "attempt to divide by zero".to_string(),
+            Error::StackOverflow => "stack overflow".to_string(),
+            Error::Unsupported(what, by) => format!("{} are not supported by {}", what, by),
+            Error::UnresolvedImport(path) => format!("unresolved import '{}'", path),
+            Error::PrivateImport(item, module) => format!("'{}' is private to module '{}'", item, module),
+            Error::ImportCycle(cycle) => format!("import cycle detected: {}", cycle),
+            Error::InvalidManifest(path) => format!("invalid manifest '{}'", path),
+            Error::InvalidPackageName(name) => format!("'{}' is not a valid package name", name),
+            Error::AlreadyExists(path) => format!("'{}' already exists", path),
+            Error::ConflictingModules(module) => format!("module '{}' is declared more than once", module),
+            Error::NotExecutable(package) => format!("package '{}' is a library and cannot be run", package),
+            Error::NoSourceFiles(path) => format!("no source files found in '{}'", path),
+            Error::CCompilerFailed(reason) => format!("the C compiler failed: {}", reason),
             Error::ExcessiveSemicolon => "excessive semicolons found!".to_string(),
+            Error::UnknownErrorCode(code) => format!("unknown diagnostic code '{}'", code),
+            Error::UnknownAttribute(name) => format!("unknown attribute '{}'", name),
+            Error::MisplacedAttribute(name, target) => format!("attribute '{}' cannot be used on {}", name, target),
+            Error::InvalidAttributeArguments(name, reason) => format!("invalid arguments for attribute '{}': {}", name, reason),
+            Error::InvalidTest(name, reason) => format!("test '{}' {}", name, reason),
+            Error::InvalidBenchmark(name, reason) => format!("benchmark '{}' {}", name, reason),
+            Error::InvalidBaseline(path, reason) => format!("could not use the baseline '{}': {}", path, reason),
+            Error::InvalidCfg(predicate, reason) => format!("invalid cfg predicate '{}': {}", predicate, reason),
+            Error::UnknownFeature(feature, package) => format!("package '{}' has no feature '{}'", package, feature),
+            Error::InvalidDefaultParameter(param, reason) => format!("parameter '{}' {}", param, reason),
+            Error::NonExhaustiveMatch(missing) => format!("non-exhaustive match, {} not covered", missing),
+            Error::UnknownVariant(ty, variant) => format!("enum '{}' has no variant named '{}'", ty, variant),
+            Error::InvalidVariant(variant, reason) => format!("variant '{}' {}", variant, reason),
+            Error::MissingInterfaceMethod(class, method, interface) => {
+                format!("class '{}' does not implement method '{}' of interface '{}'", class, method, interface)
+            }
+            Error::InterfaceMethodMismatch(method, interface, reason) => {
+                format!("method '{}' does not match interface '{}', it {}", method, interface, reason)
+            }
+            Error::UnsatisfiedBound(ty, bound) => format!("type '{}' does not implement '{}'", ty, bound),
+            Error::InstantiationLimit(item) => format!("reached the limit of nested generic instantiations while instantiating '{}'", item),
+            Error::InvalidNumber(number) => format!("invalid number literal '{}'", number),
+            Error::AssertionFailed => "assertion failed".to_string(),
+            Error::FileAccess(action, path) => format!("could not {} '{}'", action, path),
+            Error::InvalidSelfParameter => "'self' can only be the first parameter of a method".to_string(),
+            Error::UnreachablePattern => "unreachable match arm".to_string(),
             Error::ExpectedItemAfter(item, after, found) => {
                 format!("expected '{}' after '{}' but found '{}'", item, after, found)
             }
-        }
+        };
+        write!(f, "{}", message)
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub enum ErrorType {
     Error,
     Warning,
@@ -55,18 +254,21 @@ pub struct ErrorInfo {
     pub info: Option<String>,
     pub see: Option<String>,
     pub messages: Option<(String, usize)>,
+    /// A second location that explains the error (e.g. where an expected type comes from).
+    pub related: Option<(String, SourceLocation)>,
 }
 
+#[derive(Clone)]
 pub struct CompileError {
     error_type: ErrorType,
     message: Error,
-    location: crate::ast::source::SourceLocation,
+    location: SourceLocation,
 
     info: ErrorInfo,
 }
 
 impl CompileError {
-    pub fn new(error_type: Error, location: crate::ast::source::SourceLocation) -> CompileError {
+    pub fn new(error_type: Error, location: SourceLocation) -> CompileError {
         CompileError {
             error_type: ErrorType::Error,
             message: error_type,
@@ -77,13 +279,14 @@ impl CompileError {
                 info: None,
                 see: None,
                 messages: None,
+                related: None,
             },
         }
     }
 
     pub fn warning(
         error_type: Error,
-        location: crate::ast::source::SourceLocation,
+        location: SourceLocation,
     ) -> CompileError {
         CompileError {
             error_type: ErrorType::Warning,
@@ -95,6 +298,7 @@ impl CompileError {
                 info: None,
                 see: None,
                 messages: None,
+                related: None,
             },
         }
     }
@@ -104,12 +308,28 @@ impl CompileError {
         self
     }
 
+    pub fn get_error_type(&self) -> &ErrorType {
+        &self.error_type
+    }
+
+    pub fn get_message(&self) -> &Error {
+        &self.message
+    }
+
+    pub fn get_location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    pub fn get_info(&self) -> &ErrorInfo {
+        &self.info
+    }
+
     fn print_highlight(s: String) -> String {
         let mut result = String::new();
         let mut i = 0;
         let str: Vec<char> = s.chars().collect();
         let mut in_highlight = false;
-        while i < s.len() {
+        while i < str.len() {
             let mut add = false;
             if str[i] == '\'' {
                 if in_highlight {
@@ -129,10 +349,10 @@ impl CompileError {
         result
     }
 
-    fn print(&self) {
+    pub fn print(&self, sources: &SourceMap) {
         let prefix = match self.error_type {
-            ErrorType::Error => format!("{}error", red!()),
-            ErrorType::Warning => format!("{}warning", yellow!()),
+            ErrorType::Error => format!("{}error[{}]", red!(), self.message.code()),
+            ErrorType::Warning => format!("{}warning[{}]", yellow!(), self.message.code()),
         };
         let mut result = format!(
             "\n{}{}{}: {}{}\n at [{}{}{}:{}{}{}:{}{}{}]\n",
@@ -142,7 +362,7 @@ impl CompileError {
             Self::print_highlight(self.message.to_string()),
             bold!(),
             black!(),
-            self.location.path,
+            sources.get_path(self.location.file),
             reset!(),
             bold!(),
             blue!(),
@@ -153,7 +373,8 @@ impl CompileError {
         );
 
         let mut line: usize = 0;
-        let file_content = fs::read_to_string(self.location.path.clone()).unwrap();
+        // locations that don't point into a loaded source have no snippet
+        let file_content = sources.get(self.location.file).map(|f| f.get_content().as_str()).unwrap_or_default();
         result.push_str(format!("{}    |\n", black!()).as_str());
         for l in file_content.lines() {
             line += 1;
@@ -175,22 +396,19 @@ impl CompileError {
                     }
                     result.push_str(format!("{}{}{:3} | {}{}{}\n", black!(), bold!(), line, reset!(), black!(), result_line).as_str());
                     result.push_str(format!("{}{}    | ", bold!(), black!()).as_str());
-                    for i in 0..l.len() {
+                    for i in 0..l.chars().count() {
                         if i == self.location.column {
                             result.push_str(reset!());
                             result.push_str(bold!());
                             result.push_str(red!());
                             for w in 0..self.location.width {
-                                if self.info.messages.is_some() {
-                                    if self.info.messages.as_ref().unwrap().1 == i+w {
+                                match &self.info.messages {
+                                    Some((_, col)) if *col == i + w => {
                                         result.push_str(blue!());
-                                        result.push_str("|");
+                                        result.push('|');
                                         result.push_str(red!());
-                                    } else {
-                                        result.push_str("^");
                                     }
-                                } else {
-                                    result.push_str("^");
+                                    _ => result.push('^'),
                                 }
                             }
                             if let Some(info) = &self.info.info {
@@ -199,12 +417,12 @@ impl CompileError {
                             result.push_str(reset!());
                             break;
                         } else {
-                            result.push_str(" ");
+                            result.push(' ');
                         }
                     }
-                    result.push_str("\n");
-                    if let Some((msg, col)) = &self.info.messages {
-                        let mut line = String::from(" ".repeat(self.location.column));
+                    result.push('\n');
+                    if let Some((msg, _col)) = &self.info.messages {
+                        let mut line = " ".repeat(self.location.column);
                         line.push_str(blue!());
                         line.push_str(bold!());
                         line.push_str("|-- ");
@@ -219,6 +437,9 @@ impl CompileError {
         }
         let mut append_extra = false;
         result.push_str(format!("{}{}    |\n", black!(), bold!()).as_str());
+        if let Some((msg, location)) = &self.info.related {
+            Self::print_related(&mut result, sources, msg, location);
+        }
         if let Some(help) = &self.info.help {
             result.push_str(format!("{}{}help: {}{}\n", reset!(), bold!(), reset!(), self.get_help_msg(help)).as_str());
             append_extra = true;
@@ -238,7 +459,28 @@ impl CompileError {
         print!("{}", result);
     }
 
-    fn get_help_msg(&self, msg: &String) -> String {
+    /// Prints an error that does not belong to any location in the source code.
+    pub fn print_global(error: Error, help: Option<String>) {
+        let mut result = format!("\n{}{}error[{}]{}: {}\n", bold!(), red!(), error.code(), reset!(), Self::print_highlight(error.to_string()));
+        if let Some(help) = help {
+            result.push_str(format!("{}{}help: {}{}\n", reset!(), bold!(), reset!(), Self::print_highlight(help)).as_str());
+        }
+        result.push_str(reset!());
+        print!("{}", result);
+    }
+
+    fn print_related(result: &mut String, sources: &SourceMap, msg: &str, location: &SourceLocation) {
+        let Some(line) = sources.get(location.file).and_then(|f| f.get_line(location.line)) else {
+            return;
+        };
+        result.push_str(format!("{}{}   --> {}{}:{}:{}\n", reset!(), blue!(), reset!(), sources.get_path(location.file), location.line, location.column).as_str());
+        result.push_str(format!("{}{}{:3} | {}{}{}\n", black!(), bold!(), location.line, reset!(), black!(), line).as_str());
+        result.push_str(format!("{}{}    | {}{}{}{} {}{}\n", bold!(), black!(), " ".repeat(location.column), reset!(), blue!(),
+            "-".repeat(location.width.max(1)), msg, reset!()).as_str());
+        result.push_str(format!("{}{}    |\n", black!(), bold!()).as_str());
+    }
+
+    fn get_help_msg(&self, msg: &str) -> String {
         let mut result = String::new();
         for (i, line) in msg.lines().enumerate() {
             if i == 0 {
@@ -253,29 +495,33 @@ impl CompileError {
 
 pub struct Reports {
     errors: Vec<CompileError>,
+    /// Errors that don't belong to a location (e.g. a missing `main` function).
+    global_errors: Vec<(Error, Option<String>)>,
 }
 
 impl Reports {
     pub fn new() -> Reports {
-        Reports { errors: Vec::new() }
+        Reports { errors: Vec::new(), global_errors: Vec::new() }
     }
 
     pub fn add_error(&mut self, error: CompileError) {
         self.errors.push(error);
     }
 
+    pub fn add_global_error(&mut self, error: Error, help: Option<String>) {
+        self.global_errors.push((error, help));
+    }
+
     pub fn has_errors(&self) -> bool {
-        self.errors.len() > 0
+        !self.errors.is_empty() || !self.global_errors.is_empty()
     }
 
-    pub fn handle_errors(&self) -> bool {
-        let mut should_exit = false;
-        for error in &self.errors {
-            error.print();
-            if let ErrorType::Error = error.error_type {
-                should_exit = true;
-            }
-        }
-        return should_exit;
+    pub fn get_errors(&self) -> &Vec<CompileError> {
+        &self.errors
+    }
+
+    pub fn handle_errors(&self, sources: &SourceMap, format: MessageFormat) -> bool {
+        format::emit(format, sources, &self.errors, &self.global_errors);
+        !self.global_errors.is_empty() || self.errors.iter().any(|e| e.error_type == ErrorType::Error)
     }
 }